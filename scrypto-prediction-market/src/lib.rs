@@ -22,54 +22,184 @@
 //!
 //! 3. Dynamic Odds:
 //!     - The odds for each outcome should dynamically adjust based on the volume of bets placed. More bets on a particular outcome should decrease its odds, reflecting the perceived likelihood of that outcome.
+//!     - Markets created in `PayoutMode::Parimutuel` already behave this way: the whole pool is shared among the winners, so the treasury carries no risk.
 //!
 //! 4. Authorization:
 //!     - Currently, the blueprint provides a `super_admin` and an `admin` badge upon instantiation of the prediction market. These badges control access to various administrative functions within the blueprint. Developers interested in a more robust or decentralized authorization mechanism may want to clone this repository and extend the blueprint to integrate with an external authorization blueprint or system. This could provide enhanced control over who can perform administrative actions, and potentially allow for a more flexible governance model.
 //!
 //! ## SPECIFIC FUNCTION AND METHOD OVERVIEWS:
 //! 1.  Initialization and Setup:
-//!         - `instantiate_prediction_market`: Set up the market with given parameters (when setting the minimum bet amount for XRD it must be over 5 for spam prevention).
+//!         - `instantiate_prediction_market`: Set up the market with given parameters (when setting the minimum bet amount for XRD it must be over 5 for spam prevention). Optional settings are passed as a `MarketConfig`; the betting token defaults to XRD but any fungible resource can be used.
+//!         - `instantiate_from_probabilities`: Set up an XRD market from each outcome's implied probability instead of its odds.
 //!         - `deposit_to_xrd_vault`: Allow deposits to the market's XRD vault.
 //!         - `get_xrd_vault_balance`: Fetch the current balance of the XRD vault.
+//!         - `provide_liquidity`: Fund the XRD vault as a liquidity provider in exchange for LP tokens.
+//!         - `redeem_liquidity`: Redeem LP tokens for a share of the XRD vault, profit or loss included, once the market is settled.
 //!
-//! 2.  Market Management (Admin-only):
+//! 2.  Market Management (Admin-only; resolution is restricted to the oracle):
 //!         - `lock_market`: Prevent further bets on this market.
-//!         - `withdraw_from_vault`: A super-admin can withdraw a specified amount from the xrd_vault.
+//!         - `unlock_market`: Reopen betting on a locked market that has not been resolved.
+//!         - `lock_outcome`: Stop bets on a single outcome while the others stay open.
+//!         - `seed_outcome_liquidity`: Pre-fund an outcome's pool without placing a bet, backstopping fixed-odds payouts.
+//!         - `pause_market`: Temporarily stop bets, e.g. during maintenance or suspicious activity, without locking the market.
+//!         - `unpause_market`: Accept bets again on a paused market.
+//!         - `update_odds`: Change the odds of an outcome while betting is still open.
+//!         - `update_all_odds`: Replace the odds of every outcome at once, before the first bet is placed.
+//!         - `add_outcome`: Append an outcome that was left out at creation, before the first bet.
+//!         - `update_bet_limits`: Change the minimum and maximum bet while betting is still open.
+//!         - `set_resolution_source`: Record how an outcome will be judged while betting is still open.
+//!         - `update_market_metadata`: Change the market's description and resolution source while betting is still open.
+//!         - `set_hash_claims_enabled`: Turn the deprecated `claim_reward_by_hash` path on or off.
+//!         - `set_strict_resolution`: Turn on rejecting resolutions to an outcome nobody bet on.
+//!         - `set_max_user_total`: Cap the stake a single user may hold across all outcomes.
+//!         - `withdraw_from_vault`: A super-admin can withdraw a specified amount from the xrd_vault, as long as the market stays solvent.
+//!         - `withdraw_all_from_vault`: A super-admin can withdraw everything the market doesn't need to stay solvent, e.g. the whole xrd_vault after resolution.
 //!         - `admin_claim`: Admin can claim tokens from their admin_vault.
-//!         - `resolve_market`: Determine the winning outcome and distribute rewards.
+//!         - `rotate_admin_badge`: Hand off the admin role to a freshly minted badge, retiring the current one.
+//!         - `resolve_market`: Determine the winning outcome and distribute rewards, in markets without a dispute window.
+//!         - `propose_resolution`: Propose a winning outcome in a market with a dispute window; no funds move until it is finalized.
+//!         - `cancel_proposed_resolution`: Withdraw a pending resolution proposal.
+//!         - `resolve_market_with_report`: Resolve the market like `resolve_market`, returning a `ResolutionReport` with the pool, payouts, fee and the house's net.
+//!         - `resolve_market_pro_rata`: Resolve the market, cutting every winner's payout by the same ratio if the collateral can't cover them all.
+//!         - `resolve_market_multi`: Determine several winning outcomes (e.g. a dead heat) and distribute rewards with the dead-heat reduction.
+//!         - `mint_resolver_badge`: Issue a weighted resolver badge for decentralized settlement.
+//!         - `vote_resolution`: Resolver badge holders vote on the winning outcome; the market resolves once an outcome has a majority of the weight.
 //!         - `resolve_market_as_void`: Void the market and refund all bets.
+//!         - `reinstate_user`: Lift a user's self-exclusion once the cooldown has passed.
+//!         - `sweep_unclaimed`: Move a page of the rewards and refunds left unclaimed past the claim deadline to the house admin vault.
 //!
 //! 3.  Betting and Claiming Rewards (Users only):
 //!         - `place_bet`: A user places a bet on an outcome. Validation ensures the bet is valid, and the bet amount is staked on the chosen outcome.
-//!         - `claim_reward`: A user claims their reward. If the user has a reward in their vault, it's returned to them.
+//!         - `place_bet_by_index`: Like `place_bet`, but the outcome is given by its index instead of its name.
+//!         - `place_bets`: A user places bets on several outcomes at once; if any leg is rejected, none are placed.
+//!         - `buy_shares`: Stake on an outcome for transferable outcome share tokens instead of a user-bound bet.
+//!         - `redeem_shares`: Burn outcome shares for their payout once the market is settled; losing shares pay nothing.
+//!         - `trigger_close`: Anyone can lock the market once its betting deadline has passed.
+//!         - `finalize_resolution`: Anyone can pay out a proposed resolution once its dispute window has elapsed.
+//!         - `force_void_expired_market`: Anyone can void and refund a market the admin failed to resolve before its resolution deadline.
+//!         - `deposit_user_balance`: A user pre-funds a personal balance to bet from without supplying a bucket each time.
+//!         - `place_bet_from_balance`: A user places a bet drawn from their pre-funded balance.
+//!         - `withdraw_user_balance`: A user withdraws whatever is left of their pre-funded balance.
+//!         - `self_exclude`: A user excludes themselves from placing further bets on this market.
+//!         - `cancel_bet`: A user withdraws a bet before the market is locked. The stake is refunded to their vault.
+//!         - `close_out`: A user withdraws all of their bets before the market is locked and gets the combined stake back in one call.
+//!         - `claim_reward`: A user claims their reward by presenting their claim badge. If the user has a reward in their vault, it's returned to them.
+//!         - `claim_reward_by_hash`: Deprecated unauthenticated claim path, disabled unless the admin turns it on.
+//!         - `redeem_receipt`: The holder of a bet receipt burns it to collect that bet's own payout or refund once the market is settled.
+//!         - `claim_refund`: A user claims their refunded stake from a voided market by presenting their claim badge.
 //!
 //! 4.  Getters:
 //!         - `list_outcomes`: List all possible outcomes in the market.
 //!         - `get_total_staked`: Get the total amount staked in the market.
-//!         - `get_market_details`: Fetch the market's details, including title, possible outcomes, odds, and total staked amount.
+//!         - `get_bettor_pool`: Get the pool contributed by bettors, excluding treasury funds.
+//!         - `get_market_details`: Fetch the market's details as a `MarketDetails` struct, including title, possible outcomes, odds, total staked amount, bet limits, lock and resolution state, treasury balance, betting deadline, payout mode and pool-implied odds.
+//!         - `get_market_status`: Get whether the market is open, locked, resolved or voided.
+//!         - `bootstrap_state`: Get everything an indexer needs to reconstruct the market's state in one call.
+//!         - `get_odds_for_outcome`: Get the odds of a single outcome.
+//!         - `get_effective_odds`: Get the odds of a single outcome after the decay since the market opened.
+//!         - `get_implied_probabilities`: Get the probability implied by each outcome's odds, optionally without the overround.
+//!         - `get_resolution_source`: Get how a single outcome will be judged.
+//!         - `get_betting_deadline`: Get the epoch after which no more bets are accepted, if any.
+//!         - `get_pending_resolution`: Get the proposed winning outcome and the epoch it was proposed in, if a proposal is pending.
+//!         - `get_effective_fee_percent`: Get the house fee after the volume tier discount the market has reached.
+//!         - `get_user_balance`: Get a user's pre-funded balance, which is kept separate from their winnings.
+//!         - `get_claimable_balance`: Get the winnings and refunds a user can currently claim, without claiming them.
+//!         - `get_user_vault_balance`: Deprecated alias of `get_claimable_balance`.
+//!         - `get_claimable_amount`: Deprecated alias of `get_claimable_balance`.
+//!         - `get_odds_delta`: Get how far an outcome's odds have moved since a given epoch.
+//!         - `get_parimutuel_odds`: Get the payout multiple each outcome would currently pay in a parimutuel market.
+//!         - `get_pool_implied_odds`: Get the odds implied by each outcome's current pool, falling back to the configured odds.
+//!         - `get_potential_payout`: Preview what a bet of a given amount on an outcome would pay if it won.
+//!         - `preview_resolution`: Preview the rewards `resolve_market` would pay for a winning outcome, without resolving.
+//!         - `preview_void`: Preview the refunds voiding the market would credit, without voiding.
+//!         - `get_depth_data`: Get each outcome's pool, implied probability and potential payout for a depth chart.
 //!         - `get_outcome_balance`: Get the total amount staked for a specific outcome.
+//!         - `get_outcome_staked`: Get the tracked stake on a specific outcome, without reading its vault.
+//!         - `get_all_outcome_balances`: Get the total amount staked on every outcome in one call.
+//!         - `get_staked_breakdown`: Get the per-outcome breakdown of the amount staked, e.g. for a bar chart.
+//!         - `get_outcome_stakes`: Get the amount staked on and the number of bettors of every outcome.
+//!         - `get_unique_bettor_count`: Get the number of distinct users with a bet in the market.
+//!         - `get_remaining_capacity`: Get how much more can be staked across the market before it reaches its total cap.
+//!         - `get_outcome_remaining_capacity`: Get how much more can be staked on an outcome before it reaches the exposure cap.
+//!         - `get_outcome_liability`: Get the total payout owed if a specific outcome wins.
+//!         - `get_max_liability`: Get the worst-case payout across all outcomes.
+//!         - `get_withdrawable_amount`: Get how much of the treasury the super-admin can currently withdraw.
+//!         - `get_user_bets`: Get a user's stake on every outcome they have bet on.
+//!         - `get_user_total_staked`: Get a user's combined stake across all outcomes.
+//!         - `get_user_bets_with_odds`: Get a user's stake on every outcome they have bet on, with the odds it is locked in at.
+//!         - `get_bets_for_outcome_paged`: Get a page of the bets placed on an outcome, along with the total number of bets.
+//!         - `get_all_bets`: Get every bet in the market as `(outcome, user_hash, amount)`, for auditing.
+//!         - `get_current_sequence`: Get the sequence number of the last emitted event.
+//!         - `kelly_stake`: Compute the Kelly-optimal stake for a bankroll given decimal odds and an estimated probability.
 //!
 //! 5.  Helper Functions (Internal utility functions):
 //!         - `ensure_market_not_resolved`: Ensure the market hasn't been resolved before proceeding.
-//!         - `ensure_user_vault_exists`: Ensure a user vault exists or create one if it doesn't.
+//!         - `ensure_market_not_locked`: Ensure the market isn't locked before proceeding.
+//!         - `is_settled`: Check whether the market was resolved or voided.
+//!         - `ensure_winning_bets`: In strict resolution mode, reject winning outcomes nobody bet on.
+//!         - `ensure_outcome_in_bounds`: Ensure an outcome index refers to an outcome of the market.
+//!         - `effective_odds`: Apply the odds decay since the market opened to an outcome's base odds.
+//!         - `validate_market_metadata`: Ensure the description and resolution source are at most `MAX_METADATA_LENGTH` characters long.
+//!         - `validate_bet_limits`: Ensure the minimum bet is at least 5, below the maximum bet and within the exposure cap.
+//!         - `validate_odds`: Ensure odds are greater than 1 and at most `MAX_ODDS`.
+//!         - `ensure_no_dispute_window`: Ensure the market can be resolved directly, without a dispute window.
+//!         - `resolve_validated`: Resolve to a single outcome after the checks shared by every resolution path, strict mode included.
+//!         - `resolve_to_outcome`: Resolve the market to a single winning outcome and emit a `MarketResolvedEvent`.
+//!         - `ensure_user_vault_exists`: Ensure a user vault exists or create one (and mint the user's claim badge) if it doesn't.
 //!         - `ensure_admin_vault_exists`: Ensure an admin vault exists or create one if it doesn't.
-//!         - `validate_bet`: Validate the provided bet ensuring the amount is within limits and the market isn't locked.
+//!         - `validate_bet`: Validate the provided bet ensuring the amount is at least the minimum, the market isn't locked and the betting deadline hasn't passed.
+//!         - `take_user_vault`: Empty a user's vault, if it holds anything.
+//!         - `authenticate_user`: Check a claim badge proof and return the `user_hash` it belongs to.
+//!         - `place_bet_at`: Create the user's vault if needed and stake a bet on an outcome given by position.
+//!         - `stake_bet`: Validate and record a bet, returning any change and the bet receipt.
+//!         - `ensure_bet_token`: Ensure a bucket holds the market's betting token.
 //!         - `get_outcome_position`: Get the index position of a specified outcome in the market.
-//!         - `reset_and_resolve_market`: Reset the total staked amount and mark the market as resolved.
+//!         - `bet_amount`: Get a user's stake on an outcome from the bet store.
+//!         - `user_total_staked`: Get a user's stake across all outcomes from the bet store.
+//!         - `locked_payout`: Get a user's locked-in gross payout on an outcome from the bet store.
+//!         - `remove_bet`: Remove a user's bet on an outcome and return its amount and locked-in payout.
+//!         - `outcome_bets_page`: Get a page of the bets on an outcome as `(user_hash, amount)` pairs.
+//!         - `outcome_bettor_page`: Get a page of the users with a stake on an outcome.
+//!         - `add_outcome_bettor`: Add a user to the list of bettors on an outcome.
+//!         - `user_hash_page`: Get a page of the users with a vault, in the order the vaults were created.
+//!         - `required_reserve`: Compute the part of the treasury needed to pay out whichever outcome wins.
+//!         - `reset_and_resolve_market`: Reset the total staked amount and move the market to its final status.
+//!         - `next_sequence`: Advance and return the event sequence number.
+//!         - `lp_units_total`: Get the LP units issued against the XRD vault, the house's share included.
+//!         - `lp_units_for`: Convert an amount into LP units at the current XRD vault balance.
+//!         - `credit_house_liquidity`: Give the house LP units for funds it adds to a shared XRD vault.
+//!         - `credit_seeded_liquidity`: Credit the house for the seeded liquidity that joins the XRD vault on settlement.
+//!         - `settle_market`: Pool the losing stakes, pay every winning bettor and mark the market as resolved.
+//!         - `calculate_rewards`: Compute the reward owed to every winning bettor for the market's payout mode.
+//!         - `calculate_share_payouts`: Compute the payout owed to each winning outcome's shares for the market's payout mode.
+//!         - `set_receipt_payout_rates`: Record what a winning bet receipt pays per unit of its locked payout or stake.
+//!         - `set_aside_share_payouts`: Move the outcome shares' payouts from the XRD vault to the share vault.
+//!         - `add_stake`: Deposit a stake into its outcome's pool within the caps and update the totals and liability.
+//!         - `net_of_fee`: Deduct the effective house fee from an amount.
+//!         - `round_to_bet_token`: Round an amount down to the bet token's divisibility.
+//!         - `withdraw_user_reward`: Take everything from a user's vault and emit a `ClaimRewardEvent`.
+//!         - `void_market`: Refund every bet and emit a `MarketResolvedAsVoidEvent`.
 
 
 use scrypto::prelude::*;
 
+pub mod market_manager;
+
 /// About the `market_id` field in the events below:
 /// - The `market_id` serves as the identifier for the market.
 /// - Currently, it's set using the title of the market.
 /// - For unique identification, especially in cases with multiple instances of the same market,
 ///   consider transitioning to a UUID.
+///
+/// About the `sequence` field in the events below:
+/// - Every event emitted by a market carries the next value of a monotonically increasing counter, starting at 1.
+/// - Indexers can use it to detect gaps and resume from a known point (see `get_current_sequence`).
 
 /// Event emitted when a new prediction market is created.
 #[derive(ScryptoSbor, ScryptoEvent)]
 struct MarketCreatedEvent {
     market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
 }
 
 /// Represents an event that gets emitted when a market is resolved.
@@ -77,7 +207,19 @@ struct MarketCreatedEvent {
 #[derive(ScryptoSbor, ScryptoEvent)]
 struct MarketResolvedEvent {
     market_id: String,  
+    sequence: u64,      // Position of the event in the market's event stream.
     winning_outcome: u32, // The index representing the winning outcome of the market.
+    house_fee: Decimal, // Total house fee deducted from the winners' payouts.
+    haircut_ratio: Option<Decimal>, // Share of what they were owed the winners were paid, if the collateral fell short.
+}
+
+/// Represents an event that gets emitted when a market is resolved with several winning outcomes.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct MarketResolvedMultiEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    winning_outcomes: Vec<u32>, // The indices of all winning outcomes of the market.
+    house_fee: Decimal, // Total house fee deducted from the winners' payouts.
 }
 
 /// Represents an event when a market is resolved as void.
@@ -85,34 +227,505 @@ struct MarketResolvedEvent {
 #[derive(ScryptoSbor, ScryptoEvent)]
 struct MarketResolvedAsVoidEvent {
     market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    forced: bool,       // True if voided by anyone through `force_void_expired_market` rather than by the admin.
+}
+
+/// Event emitted when a resolver badge holder votes for the winning outcome.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct ResolutionVoteEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    outcome: u32,       // The index of the outcome voted for.
+    weight: Decimal,    // Combined weight of the badges that voted.
+}
+
+/// Event emitted when the admin proposes a winning outcome, opening the dispute window.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct ResolutionProposedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    winning_outcome: u32, // The index of the proposed winning outcome.
+    finalizable_from: Epoch, // First epoch in which `finalize_resolution` can be called.
+}
+
+/// Event emitted when the admin withdraws a pending resolution proposal.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct ResolutionProposalCancelledEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    winning_outcome: u32, // The index of the withdrawn proposed outcome.
 }
 
 /// Event that indicates when a market is locked, preventing further bets.
 #[derive(ScryptoSbor, ScryptoEvent)]
 struct MarketLockedEvent {
     market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+}
+
+/// Event that indicates when a locked market is reopened for betting.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct MarketUnlockedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+}
+
+/// Event emitted when the admin pauses betting with `pause_market`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct MarketPausedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+}
+
+/// Event emitted when the admin resumes betting with `unpause_market`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct MarketUnpausedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+}
+
+/// Event emitted when the admin closes a single outcome to new bets with `lock_outcome`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct OutcomeLockedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    outcome: String,    // Outcome that no longer accepts bets.
+}
+
+/// Event emitted when the admin changes the market's description or resolution source with `update_market_metadata`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct MarketMetadataUpdatedEvent {
+    market_id: String,
+    sequence: u64,              // Position of the event in the market's event stream.
+    description: String,        // The new description.
+    resolution_source: String,  // The new resolution source.
+}
+
+/// Event emitted when the admin changes the odds of an outcome.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct OddsUpdatedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    outcome: String,    // Outcome whose odds changed.
+    old_odds: Decimal,  // Odds before the update.
+    new_odds: Decimal,  // Odds after the update.
+}
+
+/// Event emitted when the admin replaces all odds with `update_all_odds`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct AllOddsUpdatedEvent {
+    market_id: String,
+    sequence: u64,          // Position of the event in the market's event stream.
+    old_odds: Vec<Decimal>, // Odds before the update, in outcome order.
+    new_odds: Vec<Decimal>, // Odds after the update, in outcome order.
+}
+
+/// Event emitted when the admin adds an outcome with `add_outcome`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct OutcomeAddedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    outcome: String,    // The new outcome.
+    outcome_index: u32, // Index of the new outcome, always the last.
+    odds: Decimal,      // Odds the outcome is offered at.
 }
 
 /// Event emitted when a user places a bet on a specific market outcome.
 #[derive(ScryptoSbor, ScryptoEvent)]
 struct BetPlacedEvent {
     market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
     user_hash: String,  // Unique identifier for the user placing the bet.
     outcome: String,    // Chosen outcome the user is betting on.
     amount: Decimal,    // Amount of XRD the user is betting.
+    outcome_index: u32, // Position of the chosen outcome, as taken by `place_bet_by_index`.
+    odds: Decimal,      // Odds locked in for this bet.
+    cumulative_amount: Decimal,     // The user's total stake on the outcome, including this bet.
+    outcome_total_staked: Decimal,  // Total stake of all users on the outcome, including this bet.
+}
+
+/// Event emitted when a user cancels a bet before the market is locked.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct BetCancelledEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    user_hash: String,  // Unique identifier for the user cancelling the bet.
+    outcome: String,    // Outcome the cancelled bet was placed on.
+    amount: Decimal,    // Amount refunded to the user's vault.
+}
+
+/// Event emitted when funds are deposited into the treasury `xrd_vault`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct VaultDepositEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    amount: Decimal,    // Amount deposited into the `xrd_vault`.
+    new_balance: Decimal, // Balance of the `xrd_vault` afterwards.
+}
+
+/// Event emitted when outcome shares are bought with `buy_shares`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct SharesBoughtEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    outcome: String,    // The outcome the shares are on.
+    amount: Decimal,    // Amount staked, equal to the number of shares minted.
+    odds: Decimal,      // Odds the stake was placed at.
+}
+
+/// Event emitted when outcome shares are burned with `redeem_shares`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct SharesRedeemedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    outcome: String,    // The outcome the shares are on.
+    shares: Decimal,    // Shares burned.
+    payout: Decimal,    // Amount paid out for them. Zero for a losing outcome.
+}
+
+/// Event emitted when a liquidity provider funds the treasury with `provide_liquidity`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct LiquidityProvidedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    amount: Decimal,    // Amount deposited into the `xrd_vault`.
+    lp_minted: Decimal, // LP tokens minted for it.
+}
+
+/// Event emitted when a liquidity provider redeems LP tokens with `redeem_liquidity`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct LiquidityRedeemedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    lp_burned: Decimal, // LP tokens burned.
+    payout: Decimal,    // Share of the `xrd_vault` paid out for them.
+}
+
+/// Event emitted when the admin seeds an outcome's pool with `seed_outcome_liquidity`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct LiquiditySeededEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    outcome: String,    // The outcome whose pool was seeded.
+    amount: Decimal,    // Amount seeded.
+}
+
+/// Event emitted when the super-admin moves funds from the treasury `xrd_vault` to an admin vault.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct VaultWithdrawalEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    amount: Decimal,    // Amount moved out of the `xrd_vault`.
+    remaining_balance: Decimal, // Balance left in the `xrd_vault` afterwards.
+}
+
+/// Event emitted when an admin claims the funds held in their admin vault.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct AdminClaimEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    amount: Decimal,    // Amount claimed from the admin vault.
 }
 
 /// Event emitted when a user claims their reward after a market's resolution.
 #[derive(ScryptoSbor, ScryptoEvent)]
 struct ClaimRewardEvent {
     market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
     user_hash: String,  // Unique identifier for the user claiming the reward.
     reward: Decimal,    // Amount of the XRD reward being claimed.
+    haircut_ratio: Option<Decimal>, // Share of the owed reward that was paid, if it was cut by `resolve_market_pro_rata`.
+}
+
+/// Event emitted when a user claims their refund from a voided market with `claim_refund`.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct RefundClaimedEvent {
+    market_id: String,
+    sequence: u64,      // Position of the event in the market's event stream.
+    user_hash: String,  // Unique identifier for the user claiming the refund.
+    refund: Decimal,    // Amount refunded.
+}
+
+/// Event emitted when the rewards left unclaimed after the claim deadline are swept to the house.
+#[derive(ScryptoSbor, ScryptoEvent)]
+struct UnclaimedSweptEvent {
+    market_id: String,
+    sequence: u64,          // Position of the event in the market's event stream.
+    total_swept: Decimal,   // Combined amount moved from the user vaults to the house admin vault.
+    users_affected: u32,    // Number of user vaults that still held funds.
+}
+
+/// Admin vault key under which house fees are accumulated. Claim them with `admin_claim("house")`.
+const HOUSE_FEE_ADMIN_HASH: &str = "house";
+
+/// Number of epochs a self-excluded user must wait before an admin can reinstate them (roughly one day).
+const SELF_EXCLUSION_COOLDOWN_EPOCHS: u64 = 288;
+
+/// House fee tiers as `(minimum lifetime volume, percentage of the configured fee still charged)`, lowest tier first.
+/// A market whose lifetime volume reached a tier's minimum pays the reduced fee at resolution.
+const FEE_TIERS: [(u64, u64); 2] = [(1_000, 75), (10_000, 50)];
+
+/// Highest house fee percentage a market can be created with.
+const MAX_FEE_PERCENT: u64 = 10;
+
+/// Highest odds an outcome can be offered at, keeping `bet_amount * odds` far from overflowing.
+const MAX_ODDS: u64 = 1_000;
+
+/// How far, in percentage points, the probabilities passed to `instantiate_from_probabilities` may sum from 100%.
+const PROBABILITY_SUM_TOLERANCE_PERCENT: u64 = 1;
+
+/// Maximum length, in characters, of a market's description and resolution source, bounding the component state.
+const MAX_METADATA_LENGTH: usize = 1_000;
+
+/// Maximum number of bets per outcome included in `bootstrap_state`. Larger outcomes are read with `get_bets_for_outcome_paged`.
+const BOOTSTRAP_BETS_PER_OUTCOME: usize = 100;
+
+/// How winnings are calculated when a market is resolved.
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayoutMode {
+    /// Winners are paid `stake * odds`, minus the house fee, from the treasury, which carries the risk.
+    FixedOdds,
+    /// The whole pool, minus the house fee, is shared among the winners pro-rata to their stake.
+    /// The `odds` vector is ignored.
+    Parimutuel,
+}
+
+/// Optional settings of a market, passed to `instantiate_prediction_market`.
+/// `MarketConfig::default()` is an XRD fixed-odds market without a fee, deadlines, caps, dispute window or decay.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct MarketConfig {
+    /// The resource address of the token bets are placed in.
+    pub bet_token: ResourceAddress,
+    /// The last epoch in which bets are accepted. `None` for a market that only closes via `lock_market`.
+    pub betting_deadline: Option<Epoch>,
+    /// `FixedOdds` to pay winners `stake * odds`, or `Parimutuel` to share the whole pool among the winners.
+    pub payout_mode: PayoutMode,
+    /// Percentage of each winning payout kept by the house and accumulated in the house admin vault.
+    /// Must be between 0 and `MAX_FEE_PERCENT` (10). In `Parimutuel` mode this is the same as keeping that share of
+    /// the pool. High-volume markets pay a reduced fee according to `FEE_TIERS`.
+    pub fee_percent: Decimal,
+    /// The last epoch in which the admin is expected to resolve the market. If the market is still unresolved
+    /// afterwards, anyone can void it with `force_void_expired_market`. `None` leaves resolution to the oracle only.
+    pub resolution_deadline: Option<Epoch>,
+    /// The maximum total stake allowed on any single outcome, capping the admin's liability on high-odds outcomes.
+    /// `None` for no cap.
+    pub max_outcome_exposure: Option<Decimal>,
+    /// The number of epochs a resolution proposed with `propose_resolution` must stand before `finalize_resolution`
+    /// can pay it out. `0` to resolve directly with `resolve_market` instead.
+    pub dispute_window_epochs: u64,
+    /// The number of epochs users have to claim their rewards or refunds once the market is resolved or voided.
+    /// Afterwards the admin can sweep whatever is left with `sweep_unclaimed`. `None` lets users claim forever.
+    pub claim_deadline_epochs: Option<u64>,
+    /// How much every outcome's odds drop for each epoch the market has been open, making late bets less attractive.
+    /// The odds stop decaying at the betting deadline and never fall below 1.01. `0` keeps the odds fixed.
+    pub odds_decay_per_epoch: Decimal,
+    /// The question the market asks, in more detail than the title. May be empty.
+    pub description: String,
+    /// Where the result will come from, e.g. an official results page. May be empty. Individual outcomes can be
+    /// given their own source with `set_resolution_source`.
+    pub resolution_source: String,
+    /// Whether methods taking an outcome name, such as `place_bet`, match it regardless of case, so `"YES"` finds
+    /// the outcome `"Yes"`. Outcomes are stored and reported as provided either way.
+    pub case_insensitive_outcomes: bool,
+    /// The maximum total stake allowed across all outcomes, capping the size of the market. Top-ups count towards
+    /// it, and cancelled bets free it up again. `None` for no cap.
+    pub max_total_staked: Option<Decimal>,
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self {
+            bet_token: XRD,
+            betting_deadline: None,
+            payout_mode: PayoutMode::FixedOdds,
+            fee_percent: Decimal::zero(),
+            resolution_deadline: None,
+            max_outcome_exposure: None,
+            dispute_window_epochs: 0,
+            claim_deadline_epochs: None,
+            odds_decay_per_epoch: Decimal::zero(),
+            description: String::new(),
+            resolution_source: String::new(),
+            case_insensitive_outcomes: false,
+            max_total_staked: None,
+        }
+    }
+}
+
+/// Lifecycle state of a market, as stored by the market and returned by `get_market_status`.
+///
+/// A market moves from `Open` to `Locked` and back until it is either `Resolved` or `Voided`, which are final.
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketStatus {
+    /// Bets are being accepted.
+    Open,
+    /// No more bets are accepted and the market awaits resolution, either because it is locked or because a
+    /// resolution proposal is pending.
+    Locked,
+    /// A winning outcome was picked and rewards were distributed. After a dead heat resolved with
+    /// `resolve_market_multi` this is the lowest winning index; `MarketResolvedMultiEvent` lists all of them.
+    Resolved { winning_outcome: u32 },
+    /// The market was resolved as void and every bet was refunded.
+    Voided,
+}
+
+/// Reasons a market operation can fail.
+///
+/// Methods that abort panic with the `Debug` rendering of the error, e.g. `BetBelowMinimum { amount: 4, minimum: 5 }`,
+/// so the variant can be matched in the transaction receipt. The resolution methods return it as their `Err`.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub enum MarketError {
+    /// The market is locked and no longer accepts changes to its bets, odds or resolution sources.
+    MarketLocked,
+    /// The market was already resolved or voided.
+    MarketResolved,
+    /// A resolution proposal is pending, so no more bets are accepted.
+    ResolutionPending,
+    /// There is no resolution proposal to cancel or finalize.
+    NoPendingResolution,
+    /// The market has a dispute window and must be resolved with `propose_resolution` and `finalize_resolution`.
+    DisputeWindowRequired { dispute_window_epochs: u64 },
+    /// The dispute window of the pending proposal hasn't elapsed yet.
+    DisputeWindowOpen { finalizable_from: Epoch },
+    /// The betting deadline has passed.
+    BettingClosed { deadline: Epoch },
+    /// The payment is not in the market's bet token.
+    WrongBetToken { expected: ResourceAddress, provided: ResourceAddress },
+    /// The bet is not positive, or a user's first bet on an outcome is smaller than `min_bet`.
+    BetBelowMinimum { amount: Decimal, minimum: Decimal },
+    /// The bet, or the user's total on the outcome, is larger than `max_bet`.
+    BetAboveMaximum { amount: Decimal, maximum: Decimal },
+    /// The bet would take the outcome past `max_outcome_exposure`.
+    ExposureExceeded { outcome: String, remaining_capacity: Decimal },
+    /// The user has self-excluded from the market.
+    UserExcluded { user_hash: String },
+    /// No outcome with this name exists in the market.
+    UnknownOutcome { outcome: String },
+    /// No outcome with this index exists in the market.
+    OutcomeOutOfBounds { outcome: u32 },
+    /// The funds held can't cover the requested amount.
+    InsufficientCollateral { required: Decimal, available: Decimal },
+    /// In strict resolution mode, a winning outcome nobody bet on. Usually a mistyped outcome index.
+    WinningOutcomeHasNoBets { outcome: u32 },
+    /// The outcome was closed to new bets with `lock_outcome`.
+    OutcomeLocked { outcome: String },
+    /// The change is only allowed before any stake is placed.
+    BetsAlreadyPlaced { total_staked: Decimal },
+    /// Betting is paused with `pause_market`.
+    MarketPaused,
+    /// The bet would take the market's total stake past `max_total_staked`.
+    PoolCapExceeded { remaining_capacity: Decimal },
+    /// The bet would take the user's stake across all outcomes past `max_user_total`.
+    UserTotalExceeded { user_hash: String, remaining_capacity: Decimal },
+    /// Liquidity can't be redeemed until the market is settled and its claim deadline, if any, has passed.
+    /// `redeemable_after` is the claim deadline, or `None` while the market is unsettled.
+    LiquidityLocked { redeemable_after: Option<Epoch> },
+}
+
+/// Snapshot of a market's configuration and state, returned by `get_market_details`.
+/// New fields are added at the end so existing off-ledger decoders keep working.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct MarketDetails {
+    pub title: String,
+    pub outcomes: Vec<String>,
+    pub odds: Vec<Decimal>,
+    pub total_staked: Decimal,
+    pub min_bet: Decimal,
+    pub max_bet: Decimal,
+    pub market_locked: bool,
+    pub market_resolved: bool,
+    pub xrd_vault_balance: Decimal,
+    pub betting_deadline: Option<Epoch>,
+    pub payout_mode: PayoutMode,
+    pub fee_percent: Decimal,
+    pub status: MarketStatus,
+    pub description: String,
+    pub resolution_source: String,
+    pub implied_odds: Vec<Decimal>,
+}
+
+/// Summary of a resolution, returned by `resolve_market_with_report`.
+/// `total_pool` is every stake in the market, excluding seeded liquidity, and `total_paid` what the winning bets and
+/// shares were paid, net of the house fee. `house_net` is `total_pool - total_paid`, and is negative when the winners
+/// were paid from the treasury.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct ResolutionReport {
+    pub winning_outcome: u32,
+    pub total_pool: Decimal,
+    pub total_paid: Decimal,
+    pub house_fee: Decimal,
+    pub house_net: Decimal,
+    pub winning_bettors: u64,
+    pub haircut_ratio: Option<Decimal>,
+    pub rewards: Vec<(String, Decimal)>,
+}
+
+/// Full state of a market, returned by `bootstrap_state` for an indexer's initial sync.
+/// Per-outcome vectors are aligned with `details.outcomes`; per-user vectors are sorted by user hash.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct BootstrapState {
+    pub details: MarketDetails,
+    pub status: MarketStatus,
+    pub current_sequence: u64,
+    pub effective_fee_percent: Decimal,
+    pub outcome_balances: Vec<Decimal>,
+    pub outcome_liabilities: Vec<Decimal>,
+    /// Total number of bets on each outcome. When it exceeds the bets included below, page through the rest
+    /// with `get_bets_for_outcome_paged`, starting at offset `BOOTSTRAP_BETS_PER_OUTCOME`.
+    pub bet_counts: Vec<usize>,
+    /// Up to `BOOTSTRAP_BETS_PER_OUTCOME` `(user_hash, amount)` bets per outcome.
+    pub bets: Vec<Vec<(String, Decimal)>>,
+    /// Claimable winnings and refunds per user.
+    pub user_vault_balances: Vec<(String, Decimal)>,
+    /// Pre-funded betting balances per user.
+    pub user_balances: Vec<(String, Decimal)>,
+}
+
+/// Descriptive information attached to an outcome.
+#[derive(ScryptoSbor, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OutcomeMetadata {
+    /// How the outcome will be judged, e.g. the data feed or website used to verify it. Empty if not set.
+    pub resolution_source: String,
+}
+
+/// A user's stakes in a market, stored per user so that bets don't weigh on the component state.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct UserBets {
+    /// Amount staked on each outcome, aligned with the market's outcomes. Zero where the user has no bet.
+    pub amounts: Vec<Decimal>,
+    /// Gross payout locked in on each outcome: every stake times the odds it was placed at. Later odds changes
+    /// don't affect it.
+    pub locked_payouts: Vec<Decimal>,
+}
+
+/// Data held by a resolver badge. Holders vote on the winning outcome with `vote_resolution`.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct ResolverBadgeData {
+    pub weight: Decimal,
+}
+
+/// Data held by the bet receipt minted for every bet. Redeem it with `redeem_receipt` once the market is resolved.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct BetReceiptData {
+    pub market_id: String,
+    pub user_hash: String,
+    pub outcome: u32,
+    pub amount: Decimal,
+    pub odds: Decimal,
+    pub epoch: Epoch,
+}
+
+/// Data held by the claim badge minted to each new bettor.
+/// The badge must be presented as a `Proof` to claim the rewards of `user_hash`.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct ClaimBadgeData {
+    pub user_hash: String,
 }
 
 
 #[blueprint]
-#[events(MarketCreatedEvent, MarketResolvedEvent, MarketLockedEvent, BetPlacedEvent, MarketResolvedAsVoidEvent, ClaimRewardEvent)]
+#[events(MarketCreatedEvent, MarketResolvedEvent, MarketResolvedMultiEvent, ResolutionProposedEvent, ResolutionProposalCancelledEvent, ResolutionVoteEvent, MarketLockedEvent, MarketUnlockedEvent, MarketPausedEvent, MarketUnpausedEvent, OutcomeLockedEvent, BetPlacedEvent, BetCancelledEvent, MarketResolvedAsVoidEvent, ClaimRewardEvent, RefundClaimedEvent, VaultDepositEvent, LiquiditySeededEvent, LiquidityProvidedEvent, LiquidityRedeemedEvent, SharesBoughtEvent, SharesRedeemedEvent, VaultWithdrawalEvent, AdminClaimEvent, OddsUpdatedEvent, AllOddsUpdatedEvent, OutcomeAddedEvent, MarketMetadataUpdatedEvent, UnclaimedSweptEvent)]
 mod prediction_market {
     
     // Method authentication setup. 
@@ -122,28 +735,110 @@ mod prediction_market {
         // Roles and their updatable conditions.
         roles {
             super_admin => updatable_by: [];
-            admin => updatable_by: [super_admin];
+            admin => updatable_by: [super_admin, SELF];
+            oracle => updatable_by: [admin];
         },
         
         // Specify which methods can be accessed by which roles.
         methods {
             // These methods can only be accessed by the `super_admin`.
             withdraw_from_vault => restrict_to: [super_admin];
-            // Only the `admin` can resolve, lock, and resolve the market as void.
-            resolve_market => restrict_to: [admin]; 
-            resolve_market_as_void => restrict_to: [admin];
+            withdraw_all_from_vault => restrict_to: [super_admin];
+            // Only the `oracle` can resolve the market or resolve it as void.
+            resolve_market => restrict_to: [oracle]; 
+            resolve_market_with_report => restrict_to: [oracle];
+            resolve_market_pro_rata => restrict_to: [oracle];
+            resolve_market_multi => restrict_to: [oracle];
+            propose_resolution => restrict_to: [oracle];
+            cancel_proposed_resolution => restrict_to: [oracle];
+            resolve_market_as_void => restrict_to: [oracle];
+            // Only the `admin` can manage the market and its treasury.
             lock_market => restrict_to: [admin];
+            unlock_market => restrict_to: [admin];
+            lock_outcome => restrict_to: [admin];
+            seed_outcome_liquidity => restrict_to: [admin];
+            pause_market => restrict_to: [admin];
+            unpause_market => restrict_to: [admin];
+            update_odds => restrict_to: [admin];
+            update_all_odds => restrict_to: [admin];
+            add_outcome => restrict_to: [admin];
+            update_bet_limits => restrict_to: [admin];
+            set_resolution_source => restrict_to: [admin];
+            update_market_metadata => restrict_to: [admin];
+            set_hash_claims_enabled => restrict_to: [admin];
+            set_strict_resolution => restrict_to: [admin];
+            set_max_user_total => restrict_to: [admin];
             admin_claim => restrict_to: [admin];
+            rotate_admin_badge => restrict_to: [admin];
+            mint_resolver_badge => restrict_to: [admin];
+            reinstate_user => restrict_to: [admin];
+            sweep_unclaimed => restrict_to: [admin];
             
             // These methods can be accessed by any user.
             claim_reward => PUBLIC;
+            claim_refund => PUBLIC;
+            claim_reward_by_hash => PUBLIC;
+            redeem_receipt => PUBLIC;
+            finalize_resolution => PUBLIC;
+            get_pending_resolution => PUBLIC;
             deposit_to_xrd_vault => PUBLIC;
+            provide_liquidity => PUBLIC;
+            redeem_liquidity => PUBLIC;
             list_outcomes => PUBLIC;
             get_total_staked => PUBLIC;
+            get_bettor_pool => PUBLIC;
             get_outcome_balance => PUBLIC;
+            get_outcome_staked => PUBLIC;
+            get_all_outcome_balances => PUBLIC;
+            get_staked_breakdown => PUBLIC;
+            get_outcome_stakes => PUBLIC;
+            get_unique_bettor_count => PUBLIC;
+            get_outcome_remaining_capacity => PUBLIC;
+            get_remaining_capacity => PUBLIC;
+            vote_resolution => PUBLIC;
             place_bet => PUBLIC;
+            place_bet_by_index => PUBLIC;
+            place_bets => PUBLIC;
+            buy_shares => PUBLIC;
+            redeem_shares => PUBLIC;
+            cancel_bet => PUBLIC;
+            close_out => PUBLIC;
+            self_exclude => PUBLIC;
+            trigger_close => PUBLIC;
+            force_void_expired_market => PUBLIC;
+            deposit_user_balance => PUBLIC;
+            place_bet_from_balance => PUBLIC;
+            withdraw_user_balance => PUBLIC;
+            get_user_balance => PUBLIC;
+            get_claimable_balance => PUBLIC;
+            get_user_vault_balance => PUBLIC;
+            get_claimable_amount => PUBLIC;
             get_xrd_vault_balance => PUBLIC;
             get_market_details => PUBLIC;
+            get_market_status => PUBLIC;
+            bootstrap_state => PUBLIC;
+            get_odds_for_outcome => PUBLIC;
+            get_effective_odds => PUBLIC;
+            get_implied_probabilities => PUBLIC;
+            get_resolution_source => PUBLIC;
+            get_user_bets => PUBLIC;
+            get_user_total_staked => PUBLIC;
+            get_user_bets_with_odds => PUBLIC;
+            get_bets_for_outcome_paged => PUBLIC;
+            get_all_bets => PUBLIC;
+            get_current_sequence => PUBLIC;
+            get_betting_deadline => PUBLIC;
+            get_effective_fee_percent => PUBLIC;
+            get_outcome_liability => PUBLIC;
+            get_max_liability => PUBLIC;
+            get_withdrawable_amount => PUBLIC;
+            get_parimutuel_odds => PUBLIC;
+            get_pool_implied_odds => PUBLIC;
+            get_potential_payout => PUBLIC;
+            preview_resolution => PUBLIC;
+            preview_void => PUBLIC;
+            get_depth_data => PUBLIC;
+            get_odds_delta => PUBLIC;
         }
     }
     
@@ -162,30 +857,206 @@ mod prediction_market {
         // Possible outcomes in the market.
         outcomes: Vec<String>,
         
-        // Odds associated with each outcome.
+        // Base odds associated with each outcome. Bets are priced at the decayed `effective_odds` and paid at the odds
+        // they were placed at.
         odds: Vec<Decimal>,   
         
+        // Descriptive information about each outcome, mapped by outcome.
+        outcome_metadata: HashMap<String, OutcomeMetadata>,
+        
+        // History of the odds of each outcome as `(epoch, odds)` entries, oldest first.
+        odds_history: Vec<Vec<(Epoch, Decimal)>>,
+        
         // Total amount staked in the market.
         total_staked: Decimal,
         
-        // Records of all bets placed, categorized by outcome.
-        // Each entry consists of the user's hash and the amount they bet.
-        bets: HashMap<String, Vec<(String, Decimal)>>,
+        // Amount staked on each outcome, aligned with `outcomes`. Sums to `total_staked` and is reset with it.
+        per_outcome_staked: Vec<Decimal>,
+        
+        // Payout owed to the bettors of each outcome if it wins, i.e. the sum of the bets' locked-in payouts per outcome.
+        outcome_liabilities: Vec<Decimal>,
+        
+        // Volume staked over the market's life, used for fee tiers. Unlike `total_staked` it isn't reset on resolution.
+        // Cancelled bets are removed so stakes can't be recycled to reach a cheaper tier.
+        lifetime_staked: Decimal,
         
-        // Treasury Vault for the XRD token.
+        // Stakes of every user, mapped by user hash. Kept in a store so each call only loads the entries it touches.
+        bets: KeyValueStore<String, UserBets>,
+        
+        // Users with a stake on each outcome, mapped by `(outcome position, index)`. Indices run from zero to the
+        // outcome's entry in `outcome_bettor_counts` in the order the bets were placed, except that a cancelled bet's
+        // slot is taken over by the outcome's latest bettor. Settlement walks these lists, so it only reads the
+        // entries of the winning bettors.
+        outcome_bettors: KeyValueStore<(u32, u64), String>,
+        
+        // Number of users with a stake on each outcome, aligned with `outcomes`.
+        outcome_bettor_counts: Vec<u64>,
+        
+        // Index of each user's entry in `outcome_bettors`, mapped by `(user hash, outcome position)`, so a bet can be
+        // removed without scanning the outcome's list.
+        outcome_bettor_indices: KeyValueStore<(String, u32), u64>,
+        
+        // Resource used for bets, rewards and the treasury (XRD by default).
+        bet_token: ResourceAddress,
+        
+        // Number of decimal places the bet token supports. Computed rewards are rounded down to it.
+        bet_token_divisibility: u8,
+        
+        // Treasury Vault for the betting token.
         xrd_vault: Vault,
         
         // Vaults for the admins.
         admin_vaults: HashMap<String, Vault>,
         
         // Vaults for individual users, mapped by user hash.
-        user_vaults: HashMap<String, Vault>,
+        user_vaults: KeyValueStore<String, Vault>,
+        
+        // Users with a vault, mapped by the order the vaults were created in, from zero to `user_count`. The vault
+        // store can't be iterated, so this lists its keys.
+        user_hashes: KeyValueStore<u64, String>,
+        
+        // Number of users with a vault, used as the next entry's index in `user_hashes`.
+        user_count: u64,
+        
+        // Pre-funded betting balances, mapped by user hash. Kept separate from `user_vaults`, which hold winnings.
+        user_balances: KeyValueStore<String, Vault>,
+        
+        // Self-excluded users, mapped to the epoch they excluded themselves in.
+        excluded_users: KeyValueStore<String, Epoch>,
+        
+        // Resource address of the super-admin badge, which always satisfies the `admin` role too.
+        super_admin_badge_address: ResourceAddress,
+        
+        // Resource manager of the claim badges minted to bettors.
+        claim_badge_manager: ResourceManager,
+        
+        // Number of claim badges issued, used as the next badge's local id.
+        claim_badges_issued: u64,
+        
+        // Resource manager of the bet receipts minted for every bet.
+        bet_receipt_manager: ResourceManager,
+        
+        // Number of bet receipts issued, used as the next receipt's local id.
+        bet_receipts_issued: u64,
+
+        // Share of a winning bet's locked payout (`FixedOdds`) or stake (`Parimutuel`) its receipt pays, set when the
+        // market resolves. Covers the fee, the dead-heat reduction and any haircut; zero for losing outcomes.
+        receipt_payout_rates: Vec<Decimal>,
+
+        // Stake each user has already redeemed receipts for, per outcome, so a receipt can't pay for a cancelled bet.
+        receipt_redeemed_stakes: KeyValueStore<(String, u32), Decimal>,
+        
+        // Resource manager of the resolver badges used to vote on the winning outcome.
+        resolver_badge_manager: ResourceManager,
+        
+        // Number of resolver badges issued, used as the next badge's local id.
+        resolver_badges_issued: u64,
+        
+        // Combined weight of all resolver badges issued.
+        total_resolver_weight: Decimal,
+        
+        // Outcome each resolver badge voted for, mapped by badge id.
+        resolution_votes: HashMap<NonFungibleLocalId, u32>,
+        
+        // Combined weight of the votes for each outcome.
+        outcome_vote_weights: Vec<Decimal>,
+        
+        // Sequence number of the last emitted event.
+        event_sequence: u64,
+        
+        // Lifecycle state of the market. A pending resolution proposal is tracked separately in `pending_resolution`.
+        status: MarketStatus,
         
-        // Flag to indicate if the market has been resolved.
-        market_resolved: bool,
+        // Last epoch in which bets are accepted, if the market has a deadline.
+        betting_deadline: Option<Epoch>,
         
-        // Flag to indicate if the market is locked (no more betting allowed).
-        market_locked: bool,
+        // How winnings are calculated on resolution.
+        payout_mode: PayoutMode,
+        
+        // Percentage of each winning payout kept by the house.
+        fee_percent: Decimal,
+        
+        // Last epoch in which the admin is expected to resolve the market, if any. Afterwards anyone can void it.
+        resolution_deadline: Option<Epoch>,
+        
+        // Maximum total stake allowed on a single outcome, if any.
+        max_outcome_exposure: Option<Decimal>,
+
+        // Maximum total stake allowed across the whole market, if any.
+        max_total_staked: Option<Decimal>,
+
+        // Maximum stake a single user may hold across all outcomes, if any. Set with `set_max_user_total`.
+        max_user_total: Option<Decimal>,
+        
+        // Number of epochs a proposed resolution must stand before it can be finalized. Zero allows direct resolution.
+        dispute_window_epochs: u64,
+        
+        // Proposed winning outcome and the epoch it was proposed in, while a proposal is pending.
+        pending_resolution: Option<(u32, Epoch)>,
+        
+        // Number of epochs after settlement users have to claim, if any. Afterwards the admin can sweep what's left.
+        claim_deadline_epochs: Option<u64>,
+        
+        // Amount every outcome's odds drop per epoch since `market_open_epoch`, until the betting deadline. Zero disables decay.
+        odds_decay_per_epoch: Decimal,
+        
+        // Epoch in which the market was instantiated, from which the odds decay.
+        market_open_epoch: Epoch,
+        
+        // Epoch in which the market was resolved or voided.
+        resolved_at: Option<Epoch>,
+        
+        // Whether the unauthenticated `claim_reward_by_hash` path is accepted. Off by default.
+        hash_claims_enabled: bool,
+        
+        // Whether direct resolutions to an outcome without bets are rejected. Off by default.
+        strict_resolution: bool,
+
+        // Positions of the outcomes closed to new bets with `lock_outcome`.
+        locked_outcomes: HashSet<usize>,
+
+        // The question the market asks, and where its result will come from. Either may be empty.
+        description: String,
+        resolution_source: String,
+
+        // Whether outcome names passed to methods are matched regardless of case.
+        case_insensitive_outcomes: bool,
+
+        // Whether betting is paused with `pause_market`. Independent of `status`.
+        market_paused: bool,
+
+        // Share of what they were owed the winners were paid, if `resolve_market_pro_rata` had to cut the payouts.
+        haircut_ratio: Option<Decimal>,
+
+        // Liquidity the admin seeded into each outcome's vault with `seed_outcome_liquidity`, keyed by outcome position.
+        // It isn't a bet, and returns to the `xrd_vault` when the market is resolved or voided.
+        seeded_liquidity: HashMap<usize, Decimal>,
+
+        // Resource manager of the LP tokens minted by `provide_liquidity`, each a share of the `xrd_vault`.
+        lp_token_manager: ResourceManager,
+
+        // LP tokens in circulation, i.e. minted and not yet redeemed.
+        lp_token_supply: Decimal,
+
+        // Shares of the `xrd_vault` held by the house for the funds the admin put in, counted in LP units but not minted.
+        // Only tracked while LP tokens are in circulation; until then the house owns the whole treasury.
+        house_lp_units: Decimal,
+
+        // Resource manager of each outcome's share token, aligned with `outcomes`. One share is minted per token staked
+        // with `buy_shares`.
+        share_managers: Vec<ResourceManager>,
+
+        // Shares of each outcome in circulation, i.e. bought and not yet redeemed.
+        shares_outstanding: Vec<Decimal>,
+
+        // Gross payout locked in by the shares of each outcome: every stake times the odds it was bought at.
+        share_locked_payouts: Vec<Decimal>,
+
+        // Funds set aside on settlement for each outcome's shares, not yet redeemed. Held in `share_vault`.
+        share_payouts: Vec<Decimal>,
+
+        // Vault holding the payouts set aside for outcome shares until they are redeemed.
+        share_vault: Vault,
     }
 
 
@@ -199,22 +1070,36 @@ mod prediction_market {
 ///
 /// `title`: Represents the name or title of the prediction market.
 ///
-/// `outcomes_str`: A comma-separated string of possible outcomes in the market. Must not contain duplicate outcomes.
+/// `outcomes_str`: A comma-separated string of possible outcomes in the market. Each outcome is trimmed of surrounding
+/// whitespace. Must not contain duplicate outcomes, compared without regard to case.
 ///
 /// `odds_str`: A comma-separated string of odds associated with each outcome. The number of odds provided must match the number of outcomes.
+/// Each odds value must be a decimal number greater than 1 and at most `MAX_ODDS`.
 ///
 /// `min_bet`: Minimum amount that can be placed as a bet.
 ///
 /// `max_bet`: Maximum amount that can be placed as a bet. It must be greater than `min_bet`.
 ///
+/// `config`: The market's optional settings, such as the bet token, payout mode, house fee, deadlines and caps.
+/// See `MarketConfig` for each setting; `MarketConfig::default()` is a plain XRD fixed-odds market.
+///
 /// The function ensures that:
-/// - Outcomes provided are unique.
-/// - Odds are greater than 1.
+/// - At least two outcomes are provided and none of them is empty.
+/// - Outcomes provided are unique once trimmed, ignoring case.
+/// - Odds are valid decimal numbers, greater than 1 and at most `MAX_ODDS`.
 /// - The number of odds matches the number of outcomes.
 /// - `min_bet` is at least 5 and `max_bet` is greater than `min_bet`.
+/// - `config.fee_percent` is between 0 and `MAX_FEE_PERCENT`.
+/// - `config.resolution_deadline` is not before `config.betting_deadline`.
+/// - `config.max_outcome_exposure`, if set, is at least `min_bet`.
+/// - `config.odds_decay_per_epoch` is between 0 and `MAX_ODDS`.
+/// - `config.description` and `config.resolution_source` are at most `MAX_METADATA_LENGTH` characters long.
+/// - `config.max_total_staked`, if set, is positive.
 ///
 /// After validation, the function creates a vault for each outcome and initializes the prediction market with the provided data. 
-/// An `admin_badge` and `super_badge` is also created to represent the admin role for this prediction market.
+/// An `admin_badge` and `super_badge` is also created to represent the admin role for this prediction market,
+/// along with the claim badge resource that only this component can mint. An `oracle_badge` is created for the
+/// oracle role, which alone can resolve the market; the admin can change the oracle role's rule to rotate it.
 ///
 /// This function emits a `MarketCreatedEvent` once the market is successfully created.
 ///
@@ -225,31 +1110,61 @@ mod prediction_market {
 /// **Transaction manifest:**
 /// `transactions/instantiate_prediction_market.rtm`
         pub fn instantiate_prediction_market(title: String, outcomes_str: String, odds_str: String, min_bet: Decimal, 
-        max_bet: Decimal
-        ) -> (Global<PredictionMarket>, FungibleBucket, FungibleBucket) {
+        max_bet: Decimal, config: MarketConfig
+        ) -> (Global<PredictionMarket>, FungibleBucket, FungibleBucket, FungibleBucket) {
+            let MarketConfig {
+                bet_token,
+                betting_deadline,
+                payout_mode,
+                fee_percent,
+                resolution_deadline,
+                max_outcome_exposure,
+                dispute_window_epochs,
+                claim_deadline_epochs,
+                odds_decay_per_epoch,
+                description,
+                resolution_source,
+                case_insensitive_outcomes,
+                max_total_staked,
+            } = config;
 
             let outcomes: Vec<String> = outcomes_str.split(',').map(|s| s.trim().to_string()).collect();
-            // Validate Uniqueness of Outcomes
-            let unique_outcomes: HashSet<&str> = outcomes_str.split(',').collect();
-            assert_eq!(
-                unique_outcomes.len(),
-                outcomes.len(),
-                "Duplicate outcomes provided."
+            // Validate the number of outcomes and that none is empty
+            assert!(
+                outcomes.len() >= 2,
+                "A prediction market requires at least two outcomes."
+            );
+            assert!(
+                outcomes.iter().all(|outcome| !outcome.is_empty()),
+                "Outcomes must not be empty. Check for a leading, trailing or double comma. Provided: '{}'",
+                outcomes_str
             );
+            // Validate Uniqueness of Outcomes, ignoring surrounding whitespace and case so "Win" and " win" clash
+            let mut unique_outcomes: HashSet<String> = HashSet::new();
+            for outcome in &outcomes {
+                assert!(
+                    unique_outcomes.insert(outcome.to_lowercase()),
+                    "Duplicate outcomes provided: '{}'. Outcomes are compared ignoring whitespace and case.",
+                    outcome
+                );
+            }
 
 
+            // Parse and validate odds, naming the offending token on failure
             let odds: Vec<Decimal> = odds_str.split(',')
-                .map(|s| Decimal::from_str(s.trim()).expect("Failed to parse odds as Decimal"))
-                .collect();
-
-                // Validate Odds
-                for odd in &odds {
+                .map(|token| {
+                    let token = token.trim();
                     assert!(
-                        *odd > Decimal::from(1),
-                        "Odds must be greater than 1. Provided: {}",
-                        odd
+                        !token.is_empty(),
+                        "Odds must not be empty. Check for a leading, trailing or double comma. Provided: '{}'",
+                        odds_str
                     );
-                }
+                    let odd = Decimal::from_str(token)
+                        .unwrap_or_else(|_| panic!("Failed to parse odds '{}' as a decimal number.", token));
+                    Self::validate_odds(odd);
+                    odd
+                })
+                .collect();
         
                 assert_eq!(
                     outcomes.len(),
@@ -258,22 +1173,48 @@ mod prediction_market {
                 );
 
               // Validate Min and Max Bet
+                Self::validate_bet_limits(min_bet, max_bet, max_outcome_exposure);
+
                 assert!(
-                    min_bet >= Decimal::from(5),
-                    "Minimum bet must be atleast 5. Provided: {}",
-                    min_bet
+                    fee_percent >= Decimal::zero() && fee_percent <= Decimal::from(MAX_FEE_PERCENT),
+                    "Fee percent must be between 0 and {}. Provided: {}",
+                    MAX_FEE_PERCENT, fee_percent
                 );
 
                 assert!(
-                    max_bet > min_bet,
-                    "Maximum bet must be greater than the minimum bet. Provided: Max bet: {}, Min bet: {}",
-                    max_bet, min_bet
+                    odds_decay_per_epoch >= Decimal::zero() && odds_decay_per_epoch <= Decimal::from(MAX_ODDS),
+                    "Odds decay per epoch must be between 0 and {}. Provided: {}",
+                    MAX_ODDS, odds_decay_per_epoch
                 );
 
+                Self::validate_market_metadata(&description, &resolution_source);
+
+                if let Some(max_total_staked) = max_total_staked {
+                    assert!(
+                        max_total_staked > Decimal::zero(),
+                        "Maximum total stake must be positive. Provided: {}",
+                        max_total_staked
+                    );
+                }
+
+                if let (Some(betting_deadline), Some(resolution_deadline)) = (betting_deadline, resolution_deadline) {
+                    assert!(
+                        resolution_deadline >= betting_deadline,
+                        "Resolution deadline must not be before the betting deadline. Provided: Resolution deadline: {}, Betting deadline: {}",
+                        resolution_deadline.number(), betting_deadline.number()
+                    );
+                }
+
+                let bet_token_divisibility = match ResourceManager::from(bet_token).resource_type() {
+                    ResourceType::Fungible { divisibility } => divisibility,
+                    ResourceType::NonFungible { .. } => panic!("The bet token must be a fungible resource."),
+                };
+
         
+            let outcome_count = outcomes.len();
             let mut outcome_tokens = Vec::new();
             for _ in &outcomes {
-                outcome_tokens.push(Vault::new(XRD)); // Create a new XRD vault for each outcome
+                outcome_tokens.push(Vault::new(bet_token)); // Create a new bet token vault for each outcome
             }
 
             let super_admin_badge = ResourceBuilder::new_fungible(OwnerRole::None)
@@ -286,7 +1227,82 @@ mod prediction_market {
             .divisibility(DIVISIBILITY_NONE)
             .mint_initial_supply(1);
 
+            // The oracle badge resolves the market, separately from the admin badge that manages the treasury.
+            let oracle_badge = ResourceBuilder::new_fungible(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>"Oracle Badge", locked;}))
+            .divisibility(DIVISIBILITY_NONE)
+            .mint_initial_supply(1);
+
+            let (address_reservation, component_address) =
+                Runtime::allocate_component_address(PredictionMarket::blueprint_id());
+
+            // Claim badges are minted by the component for each new bettor.
+            let claim_badge_manager = ResourceBuilder::new_integer_non_fungible::<ClaimBadgeData>(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>"Claim Badge", locked;}))
+            .mint_roles(mint_roles!(
+                minter => rule!(require(global_caller(component_address)));
+                minter_updater => rule!(deny_all);
+            ))
+            .create_with_no_initial_supply();
+
+            // Bet receipts are minted by the component for every bet and burned when redeemed.
+            let bet_receipt_manager = ResourceBuilder::new_integer_non_fungible::<BetReceiptData>(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>"Bet Receipt", locked;}))
+            .mint_roles(mint_roles!(
+                minter => rule!(require(global_caller(component_address)));
+                minter_updater => rule!(deny_all);
+            ))
+            .burn_roles(burn_roles!(
+                burner => rule!(require(global_caller(component_address)));
+                burner_updater => rule!(deny_all);
+            ))
+            .create_with_no_initial_supply();
+
+            // Resolver badges are minted by the component when the admin appoints a resolver.
+            let resolver_badge_manager = ResourceBuilder::new_integer_non_fungible::<ResolverBadgeData>(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>"Resolver Badge", locked;}))
+            .mint_roles(mint_roles!(
+                minter => rule!(require(global_caller(component_address)));
+                minter_updater => rule!(deny_all);
+            ))
+            .create_with_no_initial_supply();
+
+            // LP tokens are minted by the component to liquidity providers and burned when redeemed.
+            let lp_token_manager = ResourceBuilder::new_fungible(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>"Liquidity Provider Token", locked;}))
+            .divisibility(DIVISIBILITY_MAXIMUM)
+            .mint_roles(mint_roles!(
+                minter => rule!(require(global_caller(component_address)));
+                minter_updater => rule!(deny_all);
+            ))
+            .burn_roles(burn_roles!(
+                burner => rule!(require(global_caller(component_address)));
+                burner_updater => rule!(deny_all);
+            ))
+            .create_with_no_initial_supply();
+
+            // Each outcome gets a fungible share token, minted by `buy_shares` and burned by `redeem_shares`.
+            let share_managers: Vec<ResourceManager> = outcomes.iter()
+                .map(|outcome| {
+                    ResourceBuilder::new_fungible(OwnerRole::None)
+                    .metadata(metadata!(init{"name"=>format!("{} - {}", title, outcome), locked;}))
+                    .divisibility(bet_token_divisibility)
+                    .mint_roles(mint_roles!(
+                        minter => rule!(require(global_caller(component_address)));
+                        minter_updater => rule!(deny_all);
+                    ))
+                    .burn_roles(burn_roles!(
+                        burner => rule!(require(global_caller(component_address)));
+                        burner_updater => rule!(deny_all);
+                    ))
+                    .create_with_no_initial_supply()
+                })
+                .collect();
+
             
+            let odds_history = odds.iter().map(|odd| vec![(Runtime::current_epoch(), *odd)]).collect();
+            let outcome_metadata = outcomes.iter().map(|outcome| (outcome.clone(), OutcomeMetadata::default())).collect();
+
             let component = Self {
                 title: title.clone(),
                 min_bet,
@@ -294,13 +1310,69 @@ mod prediction_market {
                 outcome_tokens,
                 outcomes,
                 odds,  
+                outcome_metadata,
+                odds_history,
                 total_staked: Decimal::from(0),
-                bets: HashMap::new(),
-                xrd_vault: Vault::new(XRD),
+                per_outcome_staked: vec![Decimal::zero(); outcome_count],
+                lifetime_staked: Decimal::from(0),
+                outcome_liabilities: vec![Decimal::zero(); outcome_count],
+                bets: KeyValueStore::new(),
+                outcome_bettors: KeyValueStore::new(),
+                outcome_bettor_counts: vec![0; outcome_count],
+                outcome_bettor_indices: KeyValueStore::new(),
+                bet_token,
+                bet_token_divisibility,
+                xrd_vault: Vault::new(bet_token),
                 admin_vaults: HashMap::new(),
-                user_vaults: HashMap::new(),
-                market_resolved: false,
-                market_locked: false,
+                user_vaults: KeyValueStore::new(),
+                user_hashes: KeyValueStore::new(),
+                user_count: 0,
+                user_balances: KeyValueStore::new(),
+                excluded_users: KeyValueStore::new(),
+                super_admin_badge_address: super_admin_badge.resource_address(),
+                claim_badge_manager,
+                claim_badges_issued: 0,
+                bet_receipt_manager,
+                bet_receipts_issued: 0,
+                receipt_payout_rates: vec![Decimal::zero(); outcome_count],
+                receipt_redeemed_stakes: KeyValueStore::new(),
+                resolver_badge_manager,
+                resolver_badges_issued: 0,
+                total_resolver_weight: Decimal::zero(),
+                resolution_votes: HashMap::new(),
+                outcome_vote_weights: vec![Decimal::zero(); outcome_count],
+                event_sequence: 1,   // The `MarketCreatedEvent` below.
+                status: MarketStatus::Open,
+                betting_deadline,
+                payout_mode,
+                fee_percent,
+                resolution_deadline,
+                max_outcome_exposure,
+                max_total_staked,
+                max_user_total: None,
+                dispute_window_epochs,
+                pending_resolution: None,
+                claim_deadline_epochs,
+                odds_decay_per_epoch,
+                market_open_epoch: Runtime::current_epoch(),
+                resolved_at: None,
+                hash_claims_enabled: false,
+                strict_resolution: false,
+                locked_outcomes: HashSet::new(),
+                description,
+                resolution_source,
+                case_insensitive_outcomes,
+                market_paused: false,
+                haircut_ratio: None,
+                seeded_liquidity: HashMap::new(),
+                lp_token_manager,
+                lp_token_supply: Decimal::zero(),
+                house_lp_units: Decimal::zero(),
+                share_managers,
+                shares_outstanding: vec![Decimal::zero(); outcome_count],
+                share_locked_payouts: vec![Decimal::zero(); outcome_count],
+                share_payouts: vec![Decimal::zero(); outcome_count],
+                share_vault: Vault::new(bet_token),
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::None)
@@ -309,11 +1381,14 @@ mod prediction_market {
                     require_amount(dec!(1), super_admin_badge.resource_address()) 
                 );
                 admin => rule!(require_any_of(vec![admin_badge.resource_address(), super_admin_badge.resource_address()]));
+                oracle => rule!(require(oracle_badge.resource_address()));
             ))
+            .with_address(address_reservation)
             .globalize();
 
             Runtime::emit_event(MarketCreatedEvent {
                 market_id: title,  
+                sequence: 1,
             });
             
 
@@ -322,16 +1397,66 @@ mod prediction_market {
                 component,
                 super_admin_badge,
                 admin_badge,
+                oracle_badge,
             )
         }
 
+/// Instantiates a prediction market from the implied probability of each outcome instead of its decimal odds.
+///
+/// `probabilities_str` is a comma-separated list of probabilities aligned with `outcomes_str`, e.g. `"0.25,0.75"`.
+/// Each one must be strictly between 0 and 1, and together they must sum to 1 within
+/// `PROBABILITY_SUM_TOLERANCE_PERCENT`. Every probability `p` is converted to the odds `1 / p`, which must also be at
+/// most `MAX_ODDS`. The market is then set up exactly as by `instantiate_prediction_market`, with its defaults for
+/// every other parameter: an XRD `FixedOdds` market with no fee, deadlines, caps or metadata.
+///
+/// ---
+///
+/// **Access control:** Anyone can instantiate a prediction market, as with `instantiate_prediction_market`.
+///
+/// **Transaction manifest:**
+/// `transactions/instantiate_from_probabilities.rtm`
+        pub fn instantiate_from_probabilities(title: String, outcomes_str: String, probabilities_str: String,
+        min_bet: Decimal, max_bet: Decimal
+        ) -> (Global<PredictionMarket>, FungibleBucket, FungibleBucket, FungibleBucket) {
+            let probabilities: Vec<Decimal> = probabilities_str.split(',')
+                .map(|token| {
+                    let token = token.trim();
+                    let probability = Decimal::from_str(token)
+                        .unwrap_or_else(|_| panic!("Failed to parse probability '{}' as a decimal number.", token));
+                    assert!(
+                        probability > Decimal::zero() && probability < Decimal::one(),
+                        "Probabilities must be between 0 and 1, exclusive. Provided: {}",
+                        probability
+                    );
+                    probability
+                })
+                .collect();
+
+            let total = probabilities.iter().fold(Decimal::zero(), |total, probability| total + *probability);
+            let tolerance = Decimal::from(PROBABILITY_SUM_TOLERANCE_PERCENT) / Decimal::from(100);
+            assert!(
+                total >= Decimal::one() - tolerance && total <= Decimal::one() + tolerance,
+                "Probabilities must sum to 1, within {}. Provided: '{}', which sums to {}",
+                tolerance, probabilities_str, total
+            );
+
+            let odds_str = probabilities.iter()
+                .map(|probability| (Decimal::one() / *probability).to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+
+            Self::instantiate_prediction_market(title, outcomes_str, odds_str, min_bet, max_bet, MarketConfig::default())
+        }
+
 /// Deposits a given `Bucket` into the `xrd_vault`.
 ///
 /// Updates the internal `xrd_vault` of the struct by adding the amount specified 
 /// in the given `deposit` Bucket.
 ///
-/// Will panic if the `deposit` value is negative or if adding the `deposit` to 
-/// the `xrd_vault` results in an overflow.
+/// Will panic if the `deposit` value is negative, if the `deposit` does not hold the
+/// market's `bet_token`, or if adding the `deposit` to the `xrd_vault` results in an overflow.
+///
+/// A `VaultDepositEvent` is emitted with the deposited amount and the new balance.
 ///
 /// ---
 ///
@@ -343,7 +1468,17 @@ mod prediction_market {
                 !deposit.is_empty(),
                 "Deposit bucket is empty."
             );
+            self.ensure_bet_token(&deposit);
+            let amount = deposit.amount();
+            self.credit_house_liquidity(amount);
             self.xrd_vault.put(deposit);
+
+            Runtime::emit_event(VaultDepositEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                amount,
+                new_balance: self.xrd_vault.amount(),
+            });
         }
 
 /// Retrieves the current balance of the `xrd_vault`.
@@ -356,479 +1491,3570 @@ mod prediction_market {
 ///
 /// **Transaction manifest:** `transactions/get_xrd_vault_balance.rtm`    
         pub fn get_xrd_vault_balance(&self) -> Decimal {
-            Decimal::from(self.xrd_vault.amount())
+            self.xrd_vault.amount()
         }
 
         //2. Market Management - Admin only:
 
 /// Locks the market to prevent further bets from being placed.
 ///
-/// Once the market is locked, no new bets can be accepted. This action is irreversible for the lifecycle of the market.
+/// Once the market is locked, no new bets can be accepted until the admin calls `unlock_market`.
 /// After the lock operation, a `MarketLockedEvent` is emitted, signaling listeners or other components of the status change.
 ///
 /// ---
 ///
 /// **Access control:** Admin only. Only the market's administrator has the authority to lock the market.
 ///
+/// **Errors:** If the market has already been resolved.
+///
 /// **Transaction manifest:**
 /// `transactions/lock_market.rtm`
         pub fn lock_market(&mut self) {
-            self.market_locked = true;
+            self.ensure_market_not_resolved();
+            self.status = MarketStatus::Locked;
 
             Runtime::emit_event(MarketLockedEvent {
                 market_id: self.title.clone(),
+                sequence: self.next_sequence(),
             });
         }
 
-/// Withdraws a specified amount from the treasuary `xrd_vault`.
-/// 
+/// Unlocks the market so bets can be placed again.
+///
+/// Useful when the market was locked by mistake or too early, e.g. because the event was postponed.
+/// The betting deadline, if any, still applies. A `MarketUnlockedEvent` is emitted.
+///
 /// ---
 ///
-/// **Access control:** Super-Admin only.
-/// 
-/// **Transaction manifest:**
-/// `transactions/withdraw_from_vault.rtm
-        pub fn withdraw_from_vault(&mut self, admin_hash: String, amount: Decimal) {
-            // Ensure the xrd_vault has enough funds.
-            assert!(self.xrd_vault.amount() >= amount, 
-                    "Insufficient funds in xrd_vault. Requested: {}, Available: {}", 
-                    amount, 
-                    self.xrd_vault.amount());
-
-            // Ensure admin vault exists.
-            self.ensure_admin_vault_exists(admin_hash.clone());
+/// **Access control:** Admin only.
+///
+/// **Errors:** If the market has already been resolved.
+///
+/// **Transaction manifest:**
+/// `transactions/unlock_market.rtm`
+        pub fn unlock_market(&mut self) {
+            self.ensure_market_not_resolved();
+            self.status = MarketStatus::Open;
 
-            // Get the vault for the admin_hash
-            let admin_vault = self.admin_vaults.get_mut(&admin_hash).unwrap();
-            
-            // Transfer the amount.
-            let withdrawal_bucket = self.xrd_vault.take(amount);
-            admin_vault.put(withdrawal_bucket);
+            Runtime::emit_event(MarketUnlockedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+            });
         }
 
-
-/// Claims all tokens from the `admin_vault`.
-/// 
+/// Pauses betting, e.g. for maintenance or while suspicious activity is investigated.
+///
+/// Unlike `lock_market`, a pause only stops new bets and leaves the market's status alone: users can still cancel
+/// bets and claim, and the oracle can still resolve. Betting resumes with `unpause_market`. A `MarketPausedEvent`
+/// is emitted.
+///
 /// ---
 ///
 /// **Access control:** Admin only.
-/// 
+///
+/// **Errors:** If the market has already been resolved or is already paused.
+///
 /// **Transaction manifest:**
-/// `transactions/admin_claim.rtm`
-        pub fn admin_claim(&mut self, admin_hash: String) -> Option<Bucket> {
-            // Ensure admin's vault exists.
-            let admin_vault = self.admin_vaults.get_mut(&admin_hash).expect("Admin vault not found.");
+/// `transactions/pause_market.rtm`
+        pub fn pause_market(&mut self) {
+            self.ensure_market_not_resolved();
+            assert!(!self.market_paused, "{:?}", MarketError::MarketPaused);
+            self.market_paused = true;
 
-            // Take all tokens from the admin's vault.
-            let bucket = admin_vault.take_all();
+            Runtime::emit_event(MarketPausedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+            });
+        }
 
-            // Assert that the bucket is not empty.
-            assert!(!bucket.is_empty(), "Bucket is empty");
+/// Resumes betting on a market paused with `pause_market`.
+///
+/// The betting deadline and any lock still apply. A `MarketUnpausedEvent` is emitted.
+///
+/// ---
+///
+/// **Access control:** Admin only.
+///
+/// **Errors:** If the market is not paused.
+///
+/// **Transaction manifest:**
+/// `transactions/unpause_market.rtm`
+        pub fn unpause_market(&mut self) {
+            assert!(self.market_paused, "Market '{}' is not paused.", self.title);
+            self.market_paused = false;
 
-            Some(bucket)
+            Runtime::emit_event(MarketUnpausedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+            });
         }
 
-
-/// Resolves the market by determining the winning outcome and distributing rewards accordingly.
-///
-/// This method identifies the winning outcome and transfers tokens from the losing vaults to the `xrd_vault`.
-/// It then processes the bets for the winning outcome and calculates the reward for each user based on 
-/// their stake and the odds. Rewards are transferred to the user's vault.
+/// Closes a single outcome to new bets, e.g. when a player is ruled out, while the other outcomes stay open.
 ///
-/// After the market is resolved, it resets the total staked amount and prevents any further interactions 
-/// with this market. The function emits a `MarketResolvedEvent` signaling the market's resolution status.
+/// Existing bets on the outcome are unaffected and still pay out if it wins. The lock can't be undone.
+/// An `OutcomeLockedEvent` is emitted.
 ///
 /// # Parameters:
 /// 
-/// * `winning_outcome`: The index of the winning outcome. This must be within the range of valid outcomes.
-///
-/// # Returns:
-///
-/// A `Result` containing a vector of tuples with user IDs and their corresponding rewards if successful, 
-/// or an error message string if the market resolution fails for some reason.
+/// * `outcome`: The outcome to close.
 ///
 /// ---
 ///
-/// **Access control:** Admin only. Only the market's administrator has the authority to resolve the market.
+/// **Access control:** Admin only.
+///
+/// **Errors:** If the market has already been resolved, or the outcome doesn't exist.
 ///
 /// **Transaction manifest:**
-/// `transactions/resolve_market.rtm`
-        pub fn resolve_market(&mut self, winning_outcome: u32) -> Result<Vec<(String, Decimal)>, String> {
-            // Check that the market is unresolved and the winning outcome is valid.
+/// `transactions/lock_outcome.rtm`
+        pub fn lock_outcome(&mut self, outcome: String) {
             self.ensure_market_not_resolved();
-            assert!((winning_outcome as usize) < self.outcome_tokens.len(), "Winning outcome is out of bounds.");
-
-            // Prepare to calculate rewards.
-            let mut rewards = Vec::new();
-
-            // Transfer tokens from losing outcome vaults to the main vault (xrd_vault).
-            for (index, outcome_vault) in self.outcome_tokens.iter_mut().enumerate() {
-                if index != winning_outcome as usize {
-                    let tokens = outcome_vault.take_all();
-                    self.xrd_vault.put(tokens);
-                }
-            }
-
-            // Calculate rewards for users who bet on the winning outcome.
-            if let Some(winning_bets) = self.bets.get(&self.outcomes[winning_outcome as usize]) {
-                for (user, bet_amt) in winning_bets {
-                    let user_reward = *bet_amt * self.odds[winning_outcome as usize];
-                    rewards.push((user.clone(), user_reward));
-
-                    // Transfer the reward from the main vault to the user's individual vault.
-                    if let Some(user_vault) = self.user_vaults.get_mut(user) {
-                        user_vault.put(self.xrd_vault.take(user_reward));
-                    }
-                }
-            }
-
-            // Reset the market and finalize it as resolved.
-            self.reset_and_resolve_market();
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.locked_outcomes.insert(outcome_position);
 
-            // Emit that the market has been resolved.
-            Runtime::emit_event(MarketResolvedEvent {
+            Runtime::emit_event(OutcomeLockedEvent {
                 market_id: self.title.clone(),
-                winning_outcome,
+                sequence: self.next_sequence(),
+                outcome,
             });
-
-            Ok(rewards)
         }
 
-/// Resolves the market as void, refunding all participants with their betted amounts.
+/// Seeds an outcome's pool with liquidity from the admin, e.g. to backstop fixed-odds payouts when betting is lopsided.
 ///
-/// This method is utilized in situations where the market cannot be settled based on a specific outcome, 
-/// due to unforeseen circumstances or other reasons that prevent a definitive resolution. As a result, 
-/// all participants are refunded their initial stake, ensuring no loss or gain from their bets.
+/// The funds are deposited into the outcome's vault but are not a bet: they don't count towards `total_staked` or
+/// the outcome's exposure cap, and the admin isn't paid out as a bettor. When the market is settled the seeded
+/// liquidity joins the `xrd_vault`, even if the outcome wins, from where it can be reclaimed with
+/// `withdraw_all_from_vault`. A `LiquiditySeededEvent` is emitted.
 ///
-/// # Preconditions
+/// # Parameters:
 ///
-/// - The market should not have been resolved before.
+/// * `outcome`: The outcome whose pool is seeded.
+/// * `funds`: The liquidity, in the market's bet token.
 ///
-/// # Side Effects
+/// ---
 ///
-/// - All tokens in the outcome vaults are transferred to the xrd_vault.
-/// - All users are refunded their original staked amounts of XRD from the xrd_vault back to their respective vaults. 
-///   Users can subsequently claim these amounts.
-/// - The market is marked as resolved to prevent further bets or interactions.
-/// - An event, `MarketResolvedAsVoidEvent`, is emitted to signal the resolution.
+/// **Access control:** Admin only.
 ///
-/// # Errors
+/// **Errors:** If the market has already been resolved, the outcome doesn't exist, or `funds` is empty or not the
+/// market's bet token.
 ///
-/// - If the market was already resolved.
-/// 
-///  # Returns
+/// **Transaction manifest:**
+/// `transactions/seed_outcome_liquidity.rtm`
+        pub fn seed_outcome_liquidity(&mut self, outcome: String, funds: Bucket) {
+            self.ensure_market_not_resolved();
+            let outcome_position = self.get_outcome_position(&outcome);
+            assert!(!funds.is_empty(), "Liquidity bucket is empty.");
+            self.ensure_bet_token(&funds);
+
+            let amount = funds.amount();
+            self.outcome_tokens[outcome_position].put(funds);
+            *self.seeded_liquidity.entry(outcome_position).or_insert(Decimal::zero()) += amount;
+
+            Runtime::emit_event(LiquiditySeededEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                outcome,
+                amount,
+            });
+        }
+
+/// Changes the odds of a single outcome.
 ///
-/// - `Ok(())` if the market is successfully resolved as void.
+/// Odds can only change while betting is open, i.e. before the market is locked or resolved. The new odds apply to
+/// bets placed afterwards; existing bets keep the odds they were placed at. An `OddsUpdatedEvent` is emitted with the
+/// old and new odds.
+///
+/// # Parameters:
+/// 
+/// * `outcome`: The outcome whose odds should change.
+/// * `new_odds`: The new odds. Must be greater than 1.
 ///
 /// ---
 ///
-/// **Access control:** Admin only. Only the market's administrator has the authority to resolve the market.
+/// **Access control:** Admin only.
 ///
 /// **Transaction manifest:**
-/// `transactions/resolve_market_as_void.rtm`
-        pub fn resolve_market_as_void(&mut self) -> Result<(), String> {
-            // Ensure the market hasn't been resolved before.
+/// `transactions/update_odds.rtm`
+        pub fn update_odds(&mut self, outcome: String, new_odds: Decimal) {
             self.ensure_market_not_resolved();
-    
-            // Iterate through each outcome's vault.
-            for outcome_vault in &mut self.outcome_tokens {
-                // Take all tokens from the outcome vault.
-                let tokens = outcome_vault.take_all();
-    
-                // Transfer tokens from outcome vaults to the xrd_vault.
-                self.xrd_vault.put(tokens);
-            }
-    
-          // Iterate over all the user bets and refund them.
-            for (_, outcome_bets) in &self.bets {
-                for (user, bet_amt) in outcome_bets {
-                    // Extract the refund amount from the xrd_vault.
-                    let refund_bucket = self.xrd_vault.take(*bet_amt);
-    
-                    // Transfer the refund to the user's vault.
-                    if let Some(user_vault) = self.user_vaults.get_mut(user) {
-                        user_vault.put(refund_bucket);
-                    }
-                }
-            }
-    
-            // Reset the total_staked amount to 0 and mark the market as resolved to prevent further interactions.
-            self.reset_and_resolve_market();
+            self.ensure_market_not_locked();
+            Self::validate_odds(new_odds);
 
-            // Emit the MarketResolvedAsVoidEvent right after the market is resolved as void.
-            Runtime::emit_event(MarketResolvedAsVoidEvent {
+            let outcome_position = self.get_outcome_position(&outcome);
+            let old_odds = self.odds[outcome_position];
+            self.odds[outcome_position] = new_odds;
+            self.odds_history[outcome_position].push((Runtime::current_epoch(), new_odds));
+
+            Runtime::emit_event(OddsUpdatedEvent {
                 market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                outcome,
+                old_odds,
+                new_odds,
             });
-
-    
-            // Return Ok to indicate the market was successfully resolved as void.
-            Ok(())
         }
 
-      // 3. Betting and Claiming Rewards - Users only:
-
-/// Allows a user to place a bet on a specific outcome of the market.
+/// Replaces the odds of every outcome at once, e.g. when the lines move before the market opens to the public.
 ///
-/// This method enables users to stake a certain amount of tokens (contained within the `payment` bucket)
-/// on an outcome they predict will win. Once the bet is placed, the staked amount is added to the outcome's
-/// vault and the bet is recorded. If the outcome is correct when the market is resolved, the user can
-/// claim their rewards.
+/// Unlike `update_odds`, this is only allowed while nothing is staked, so no bettor's expected payout changes.
+/// The new odds are validated like at instantiation. An `AllOddsUpdatedEvent` is emitted with the old and new odds.
 ///
-/// # Preconditions:
+/// # Parameters:
 /// 
-/// * The market should not have been resolved before.
-/// * The payment amount should be within valid bounds.
-/// * The outcome on which the bet is placed should be valid.
+/// * `new_odds`: The new odds, one per outcome in the order of `list_outcomes`. Each must be greater than 1.
 ///
-/// # Side Effects:
+/// ---
 ///
-/// * The payment amount is added to the vault associated with the chosen outcome.
-/// * The total staked amount in the market is updated.
-/// * The bet is either updated (if it exists) or added to the list of bets.
-/// * An event, `BetPlacedEvent`, is emitted to signal the bet placement.
+/// **Access control:** Admin only.
 ///
-/// # Parameters:
-/// 
-/// * `user_hash`: A unique identifier (hash) for the user placing the bet.
-/// * `outcome`: The outcome on which the user is betting.
-/// * `payment`: A `Bucket` object containing the staked tokens for the bet.
+/// **Errors:** If the market is locked or resolved, if anything is staked, or if the number of odds doesn't match
+/// the number of outcomes.
 ///
-/// # Errors:
+/// **Transaction manifest:**
+/// `transactions/update_all_odds.rtm`
+        pub fn update_all_odds(&mut self, new_odds: Vec<Decimal>) {
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+            assert!(
+                self.total_staked.is_zero(),
+                "{:?}",
+                MarketError::BetsAlreadyPlaced { total_staked: self.total_staked }
+            );
+            assert_eq!(
+                self.outcomes.len(),
+                new_odds.len(),
+                "The number of odds provided does not match the number of outcomes."
+            );
+            new_odds.iter().for_each(|odds| Self::validate_odds(*odds));
+
+            let epoch = Runtime::current_epoch();
+            for (history, odds) in self.odds_history.iter_mut().zip(&new_odds) {
+                history.push((epoch, *odds));
+            }
+            let old_odds = std::mem::replace(&mut self.odds, new_odds.clone());
+
+            Runtime::emit_event(AllOddsUpdatedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                old_odds,
+                new_odds,
+            });
+        }
+
+/// Appends an outcome that was left out when the market was created.
 ///
-/// * If the market was already resolved.
-/// * If the total bet exceeds the allowed limit.
+/// The outcome is added at the end, so the indices of the existing outcomes don't change, and gets its own vault in
+/// the market's bet token, share token and odds history. Outcomes can only be added while the market is open and
+/// nothing is staked. An `OutcomeAddedEvent` is emitted.
 ///
-/// # Returns:
+/// # Parameters:
 ///
-/// No explicit return. The function updates internal structures and emits an event.
+/// * `outcome`: The new outcome, trimmed of surrounding whitespace. Must not be empty or match an existing outcome,
+///   compared without regard to case.
+/// * `odds`: The odds the outcome is offered at. Must be greater than 1 and at most `MAX_ODDS`.
 ///
 /// ---
 ///
-/// **Access control:** Public method, can be called by anyone.
-/// 
-///  **Transaction manifest:**
-/// `transactions/place_bet.rtm`
-        pub fn place_bet(&mut self, user_hash: String, outcome: String, payment: Bucket) {
-            // Ensure the market hasn't been resolved before.
+/// **Access control:** Admin only.
+///
+/// **Errors:** If the market is locked or settled. Fails with `MarketError::BetsAlreadyPlaced` once anything is staked.
+///
+/// **Transaction manifest:**
+/// `transactions/add_outcome.rtm`
+        pub fn add_outcome(&mut self, outcome: String, odds: Decimal) {
             self.ensure_market_not_resolved();
-            
-            // Validate the bet.
-            self.validate_bet(&payment);
-        
-            // Get the outcome's position.
-            let outcome_position = self.get_outcome_position(&outcome);
-        
-            // Ensure user vault exists.
-            self.ensure_user_vault_exists(user_hash.clone());
-        
-            // Extract payment amount before moving `payment`
-            let payment_amount = payment.amount();
+            self.ensure_market_not_locked();
+            assert!(
+                self.total_staked.is_zero(),
+                "{:?}",
+                MarketError::BetsAlreadyPlaced { total_staked: self.total_staked }
+            );
+            let outcome = outcome.trim().to_string();
+            assert!(!outcome.is_empty(), "Outcomes must not be empty.");
+            assert!(
+                self.outcomes.iter().all(|existing| existing.to_lowercase() != outcome.to_lowercase()),
+                "Duplicate outcomes provided: '{}'. Outcomes are compared ignoring whitespace and case.",
+                outcome
+            );
+            Self::validate_odds(odds);
 
-            // Get a mutable reference to the vault associated with the outcome.
-            let outcome_token = &mut self.outcome_tokens[outcome_position];
-            // Deposit the payment into the outcome's vault.
-            outcome_token.put(payment);
-            // Update the total amount staked in the market.
-            self.total_staked += payment_amount;
-            // Record the bet.
-            let outcome_clone = self.outcomes[outcome_position].clone();
-            let outcome_bets = self.bets.entry(outcome_clone).or_insert_with(Vec::new);
-
-            if let Some(existing_bet) = outcome_bets.iter_mut().find(|(existing_user, _)| existing_user == &user_hash) {
-                let excess_amount = existing_bet.1 + payment_amount - self.max_bet;
-                assert!(existing_bet.1 + payment_amount <= self.max_bet, 
-                        "Total bet exceeds the allowed limit by {}. You can bet up to {} more.", excess_amount, self.max_bet - existing_bet.1);
-                        existing_bet.1 += payment_amount;  // Update the bet amount
-                } else {
-                    outcome_bets.push((user_hash.clone(), payment_amount)); // Insert a new bet
-                }
+            let component_address = Runtime::global_address();
+            let share_manager = ResourceBuilder::new_fungible(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>format!("{} - {}", self.title, outcome), locked;}))
+            .divisibility(self.bet_token_divisibility)
+            .mint_roles(mint_roles!(
+                minter => rule!(require(global_caller(component_address)));
+                minter_updater => rule!(deny_all);
+            ))
+            .burn_roles(burn_roles!(
+                burner => rule!(require(global_caller(component_address)));
+                burner_updater => rule!(deny_all);
+            ))
+            .create_with_no_initial_supply();
 
+            // Every per-outcome field gets an entry for the new outcome.
+            self.outcomes.push(outcome.clone());
+            self.odds.push(odds);
+            self.odds_history.push(vec![(Runtime::current_epoch(), odds)]);
+            self.outcome_metadata.insert(outcome.clone(), OutcomeMetadata::default());
+            self.outcome_tokens.push(Vault::new(self.bet_token));
+            self.per_outcome_staked.push(Decimal::zero());
+            self.outcome_liabilities.push(Decimal::zero());
+            self.outcome_bettor_counts.push(0);
+            self.outcome_vote_weights.push(Decimal::zero());
+            self.share_managers.push(share_manager);
+            self.shares_outstanding.push(Decimal::zero());
+            self.share_locked_payouts.push(Decimal::zero());
+            self.share_payouts.push(Decimal::zero());
+            self.receipt_payout_rates.push(Decimal::zero());
 
-            // Emit the BetPlacedEvent.
-            Runtime::emit_event(BetPlacedEvent {
+            Runtime::emit_event(OutcomeAddedEvent {
                 market_id: self.title.clone(),
-                user_hash,
+                sequence: self.next_sequence(),
                 outcome,
-                amount: payment_amount,
+                outcome_index: (self.outcomes.len() - 1) as u32,
+                odds,
             });
+        }
 
-    }
-
-/// Allows a user to claim their reward after a market is resolved.
+/// Changes the minimum and maximum bet.
 ///
-/// This method enables users to retrieve their rewards from a previously placed bet, given that their prediction was accurate. 
-/// The reward tokens are extracted from the user's vault, and an event is emitted to indicate a successful claim.
+/// Like the odds, the limits can only change while betting is open. They apply to bets placed afterwards; existing
+/// bets stay valid even if they fall outside the new limits, but a top-up can't take a user's total past `new_max`.
 ///
-/// # Preconditions:
+/// # Parameters:
 /// 
-/// * The market should have been resolved before a user attempts to claim their reward.
-/// * The user should have a non-empty vault, meaning they have won a bet in the past.
+/// * `new_min`: The new minimum bet. Must be at least 5, and at most `max_outcome_exposure` if the market has one.
+/// * `new_max`: The new maximum bet. Must be greater than `new_min`.
 ///
-/// # Side Effects:
+/// ---
 ///
-/// * The tokens equivalent to the user's reward are removed from their vault.
-/// * An event, `ClaimRewardEvent`, is emitted to signal the successful reward claim.
+/// **Access control:** Admin only.
+///
+/// **Transaction manifest:**
+/// `transactions/update_bet_limits.rtm`
+        pub fn update_bet_limits(&mut self, new_min: Decimal, new_max: Decimal) {
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+            Self::validate_bet_limits(new_min, new_max, self.max_outcome_exposure);
+
+            self.min_bet = new_min;
+            self.max_bet = new_max;
+        }
+
+/// Records how a single outcome will be judged.
+///
+/// Like the odds, an outcome's resolution source can only change while betting is open, so bettors always know
+/// how the outcome they bet on will be verified.
 ///
 /// # Parameters:
 /// 
-/// * `user_hash`: A unique identifier (hash) for the user claiming the reward.
+/// * `outcome`: The outcome whose resolution source should be set.
+/// * `resolution_source`: A description of the source, e.g. the data feed or website used to verify the outcome.
 ///
-/// # Errors:
+/// ---
 ///
-/// * If the user's vault is empty when trying to claim the reward.
+/// **Access control:** Admin only.
 ///
-/// # Returns:
+/// **Transaction manifest:**
+/// `transactions/set_resolution_source.rtm`
+        pub fn set_resolution_source(&mut self, outcome: String, resolution_source: String) {
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+
+            let outcome_position = self.get_outcome_position(&outcome);
+            let outcome_clone = self.outcomes[outcome_position].clone();
+            self.outcome_metadata.entry(outcome_clone).or_default().resolution_source = resolution_source;
+        }
+
+/// Changes the market's description and resolution source.
 ///
-/// * An `Option<Bucket>`: 
-///     - `Some(Bucket)` containing the tokens if the claim is successful.
-///     - `None` if the user does not have a vault or no reward to claim.
+/// Like the per-outcome resolution sources, they can only change while betting is open. Both may be empty and are
+/// capped at `MAX_METADATA_LENGTH` characters. A `MarketMetadataUpdatedEvent` is emitted.
+///
+/// # Parameters:
+/// 
+/// * `description`: The question the market asks.
+/// * `resolution_source`: Where the market's result will come from.
 ///
 /// ---
 ///
-/// **Access control:** Public method, can be called by anyone.
+/// **Access control:** Admin only.
 ///
-///  **Transaction manifest:**
-/// `transactions/claim_reward.rtm`
-    pub fn claim_reward(&mut self, user_hash: String) -> Option<Bucket> {
-        // Attempt to get a mutable reference to the user's vault using the provided user_hash.
-        if let Some(vault) = self.user_vaults.get_mut(&user_hash) {
-            // If the user's vault exists, take all tokens from the vault as the reward.
-            let bucket = vault.take_all();
-            
-            // Assert that the bucket is not empty.
-            assert!(!bucket.is_empty(), "Bucket is empty");
+/// **Errors:** If the market is locked or resolved, or either value is too long.
+///
+/// **Transaction manifest:**
+/// `transactions/update_market_metadata.rtm`
+        pub fn update_market_metadata(&mut self, description: String, resolution_source: String) {
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+            Self::validate_market_metadata(&description, &resolution_source);
 
-            // Emit an event to indicate successful reward claim.
-            Runtime::emit_event(ClaimRewardEvent {
+            self.description = description.clone();
+            self.resolution_source = resolution_source.clone();
+
+            Runtime::emit_event(MarketMetadataUpdatedEvent {
                 market_id: self.title.clone(),
-                user_hash: user_hash.clone(),
-                reward: bucket.amount(),
+                sequence: self.next_sequence(),
+                description,
+                resolution_source,
             });
-            
-            Some(bucket)
-        
+        }
 
-            } else {
-            // If the user's vault does not exist, return None.
-            None
+/// Turns the deprecated `claim_reward_by_hash` path on or off.
+///
+/// The path is off by default, since anyone who knows a user's hash could claim their reward through it. It can be
+/// turned on for integrations that have not moved to claim badges or bet receipts yet.
+///
+/// ---
+///
+/// **Access control:** Admin only.
+///
+/// **Transaction manifest:**
+/// `transactions/set_hash_claims_enabled.rtm`
+        pub fn set_hash_claims_enabled(&mut self, enabled: bool) {
+            self.hash_claims_enabled = enabled;
         }
-    }
 
-        // 4. Getters:
-        
-/// Lists all the outcomes for the market.
+/// Turns strict resolution mode on or off.
+///
+/// By default, resolving to an outcome nobody bet on is accepted and the house keeps every stake. In strict mode
+/// `resolve_market` and `resolve_market_multi` instead return `Err(MarketError::WinningOutcomeHasNoBets)` without
+/// moving any funds, so a mistyped outcome index can be caught and corrected.
 ///
 /// ---
 ///
-/// **Access control:** Public method, can be called by anyone.
+/// **Access control:** Admin only.
 ///
 /// **Transaction manifest:**
-/// `transactions/list_outcomes.rtm`
-        pub fn list_outcomes(&self) -> Vec<String> {
-            self.outcomes.clone()
+/// `transactions/set_strict_resolution.rtm`
+        pub fn set_strict_resolution(&mut self, enabled: bool) {
+            self.strict_resolution = enabled;
         }
 
-/// Retrieves the total amount staked in the market.
+/// Caps the stake a single user may hold across all outcomes.
+///
+/// `max_bet` applies per outcome, so without this cap a user can stake `max_bet` on every outcome. Like the bet
+/// limits, the cap can only change while betting is open. It applies to bets placed afterwards; users already above
+/// a lowered cap keep their bets but can't add to them.
+///
+/// # Parameters:
+/// 
+/// * `max_user_total`: The new cap, which must be at least `min_bet`. Pass `None` to remove it.
 ///
 /// ---
 ///
-/// **Access control:** Public method, can be called by anyone.
+/// **Access control:** Admin only.
+///
+/// **Errors:** If the market is locked or resolved, or the cap is below `min_bet`.
 ///
 /// **Transaction manifest:**
-/// `transactions/get_total_staked.rtm`
-        pub fn get_total_staked(&self) -> Decimal {
-            self.total_staked.clone()
+/// `transactions/set_max_user_total.rtm`
+        pub fn set_max_user_total(&mut self, max_user_total: Option<Decimal>) {
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+            if let Some(max_user_total) = max_user_total {
+                assert!(
+                    max_user_total >= self.min_bet,
+                    "Maximum user total must be at least the minimum bet. Provided: {}, Minimum bet: {}",
+                    max_user_total, self.min_bet
+                );
+            }
+
+            self.max_user_total = max_user_total;
         }
 
-/// Retrieves the details of the market.
+/// Withdraws a specified amount from the treasuary `xrd_vault`.
 ///
-/// Details include the market title, outcomes, odds for each outcome, and the total amount staked in the market.
+/// The funds are moved to the admin vault of `admin_hash` and a `VaultWithdrawalEvent` is emitted.
+/// The amount must be positive.
 ///
+/// While a `FixedOdds` market is unresolved, withdrawals that would leave the market unable to pay out the
+/// winners of any single outcome are denied, i.e. at most `get_withdrawable_amount` can be withdrawn. Once liquidity
+/// providers have funded the treasury, only the house's share of it can be withdrawn.
+/// 
 /// ---
 ///
-/// **Access control:** Public method, can be called by anyone.
+/// **Access control:** Super-Admin only.
 /// 
 /// **Transaction manifest:**
-/// `transactions/get_market_details.rtm`
-        pub fn get_market_details(&self) -> (String, Vec<String>, Vec<Decimal>, Decimal) {
-            (self.title.clone(), self.outcomes.clone(), self.odds.clone(), self.total_staked.clone())
+/// `transactions/withdraw_from_vault.rtm
+        pub fn withdraw_from_vault(&mut self, admin_hash: String, amount: Decimal) {
+            assert!(amount > Decimal::zero(), "Withdrawal amount must be positive. Provided: {}", amount);
+
+            // Ensure the xrd_vault has enough funds.
+            assert!(self.xrd_vault.amount() >= amount, 
+                    "{:?}",
+                    MarketError::InsufficientCollateral { required: amount, available: self.xrd_vault.amount() });
+
+            // Ensure the market can still pay out whichever outcome wins.
+            assert!(
+                amount <= self.get_withdrawable_amount(),
+                "{:?}",
+                MarketError::InsufficientCollateral { required: amount, available: self.get_withdrawable_amount() }
+            );
+
+            // Ensure admin vault exists.
+            self.ensure_admin_vault_exists(admin_hash.clone());
+
+            // The house gives up the LP units the withdrawal is worth.
+            if self.lp_token_supply > Decimal::zero() {
+                self.house_lp_units -= amount * self.lp_units_total() / self.xrd_vault.amount();
+            }
+
+            // Get the vault for the admin_hash
+            let admin_vault = self.admin_vaults.get_mut(&admin_hash).unwrap();
+            
+            // Transfer the amount.
+            let withdrawal_bucket = self.xrd_vault.take(amount);
+            admin_vault.put(withdrawal_bucket);
+
+            Runtime::emit_event(VaultWithdrawalEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                amount,
+                remaining_balance: self.xrd_vault.amount(),
+            });
         }
 
-/// Fetches the balance associated with a particular market outcome.
+/// Withdraws everything that can be withdrawn from the treasury `xrd_vault` in one call.
+///
+/// Moves `get_withdrawable_amount` to the admin vault of `admin_hash`, exactly like `withdraw_from_vault`, and emits a
+/// `VaultWithdrawalEvent` with the moved amount. Once the market is resolved or voided this empties the `xrd_vault`.
+///
+/// ---
+///
+/// **Access control:** Super-Admin only, like `withdraw_from_vault`.
+///
+/// **Errors:** If nothing can be withdrawn.
+///
+/// **Transaction manifest:**
+/// `transactions/withdraw_all_from_vault.rtm`
+        pub fn withdraw_all_from_vault(&mut self, admin_hash: String) {
+            let amount = self.get_withdrawable_amount();
+            self.withdraw_from_vault(admin_hash, amount);
+        }
+
+/// Funds the treasury `xrd_vault` as a liquidity provider, in exchange for LP tokens.
+///
+/// The LP tokens are a share of the `xrd_vault` proportional to the funds contributed: the first provider gets one LP
+/// token per token deposited, later ones `amount * lp_units / xrd_vault_balance`. Whatever the admin had already put
+/// in the treasury stays the house's share. Providers carry the treasury's profit or loss with the house, and redeem
+/// their share with `redeem_liquidity` once the market is settled. A `LiquidityProvidedEvent` is emitted.
+///
+/// # Parameters:
+///
+/// * `payment`: The liquidity, in the market's bet token.
+///
+/// # Returns:
+///
+/// A `Bucket` of LP tokens.
 ///
 /// ---
 ///
 /// **Access control:** Public method, can be called by anyone.
-/// 
-/// **Errors:** If the provided outcome doesn't exist in the market.
-/// 
+///
+/// **Errors:** If the market is locked, has a pending resolution proposal or is settled (resolved or voided), or
+/// `payment` is empty or not the market's bet token.
+///
 /// **Transaction manifest:**
-/// `transactions/get_outcome_balance.rtm`
-        pub fn get_outcome_balance(&self, outcome: String) -> Decimal {
-            assert!(self.outcomes.contains(&outcome), "Outcome does not exist.");
+/// `transactions/provide_liquidity.rtm`
+        pub fn provide_liquidity(&mut self, payment: Bucket) -> Bucket {
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+            assert!(!payment.is_empty(), "Liquidity bucket is empty.");
+            self.ensure_bet_token(&payment);
 
-            let index = self.outcomes.iter().position(|o| o == &outcome).expect("Outcome not found.");
-            Decimal::from(self.outcome_tokens[index].amount())
-        }
+            // The first provider freezes the house's share at the treasury it funded so far, one unit per token.
+            let amount = payment.amount();
+            if self.lp_token_supply.is_zero() {
+                self.house_lp_units = self.xrd_vault.amount();
+            }
+            let lp_minted = self.lp_units_for(amount);
+            self.xrd_vault.put(payment);
+            self.lp_token_supply += lp_minted;
 
-        // 5. Helpers:
-        
-        fn ensure_market_not_resolved(&self) {
-            assert!(!self.market_resolved, "Market '{}' has already been resolved.", self.title);
+            Runtime::emit_event(LiquidityProvidedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                amount,
+                lp_minted,
+            });
+
+            self.lp_token_manager.mint(lp_minted)
         }
 
-        fn ensure_user_vault_exists(&mut self, user_hash: String) {
-            // Check if a vault exists for the user, if not, create a new one.
-            if !self.user_vaults.contains_key(&user_hash) {
-            self.user_vaults.insert(user_hash.clone(), Vault::new(XRD));
+/// Redeems LP tokens for their share of the treasury `xrd_vault`, profit or loss included.
+///
+/// Each LP token pays `xrd_vault_balance / lp_units`, rounded down to the bet token's divisibility, and is burned.
+/// Redemption opens once the market is resolved or voided and, if it has a claim deadline, that deadline has passed.
+/// A `LiquidityRedeemedEvent` is emitted.
+///
+/// # Parameters:
+///
+/// * `lp_tokens`: The market's LP tokens to redeem.
+///
+/// # Returns:
+///
+/// A `Bucket` with the provider's share of the treasury.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone holding LP tokens.
+///
+/// **Errors:** `MarketError::LiquidityLocked` before the market is settled or while users can still claim. Also if
+/// the bucket is empty or doesn't hold the market's LP tokens.
+///
+/// **Transaction manifest:**
+/// `transactions/redeem_liquidity.rtm`
+        pub fn redeem_liquidity(&mut self, lp_tokens: Bucket) -> Bucket {
+            assert!(self.is_settled(), "{:?}", MarketError::LiquidityLocked { redeemable_after: None });
+            if let Some(claim_deadline_epochs) = self.claim_deadline_epochs {
+                let claim_deadline = self.resolved_at.unwrap().after(claim_deadline_epochs).unwrap();
+                assert!(
+                    Runtime::current_epoch() > claim_deadline,
+                    "{:?}",
+                    MarketError::LiquidityLocked { redeemable_after: Some(claim_deadline) }
+                );
             }
+            assert_eq!(lp_tokens.resource_address(), self.lp_token_manager.address(), "Bucket doesn't hold this market's LP tokens.");
+            assert!(!lp_tokens.is_empty(), "LP token bucket is empty.");
+
+            let lp_burned = lp_tokens.amount();
+            let payout = self.round_to_bet_token(lp_burned * self.xrd_vault.amount() / self.lp_units_total());
+            self.lp_token_supply -= lp_burned;
+            lp_tokens.burn();
+
+            Runtime::emit_event(LiquidityRedeemedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                lp_burned,
+                payout,
+            });
+
+            self.xrd_vault.take(payout)
         }
 
-        fn ensure_admin_vault_exists(&mut self, admin_hash: String){
-            // Check if a vault exists for the admin, if not, create a new one.
-            if !self.admin_vaults.contains_key(&admin_hash) {
-                self.admin_vaults.insert(admin_hash.clone(), Vault::new(XRD));
+
+/// Claims all tokens from the `admin_vault`.
+///
+/// Emits an `AdminClaimEvent` with the claimed amount. Returns `None` without emitting an event if the admin has
+/// no vault or nothing to claim, so the transaction still commits.
+/// 
+/// ---
+///
+/// **Access control:** Admin only.
+/// 
+/// **Transaction manifest:**
+/// `transactions/admin_claim.rtm`
+        pub fn admin_claim(&mut self, admin_hash: String) -> Option<Bucket> {
+            // Nothing to claim if the admin's vault is missing or empty.
+            let admin_vault = self.admin_vaults.get_mut(&admin_hash)?;
+            if admin_vault.is_empty() {
+                return None;
             }
+
+            // Take all tokens from the admin's vault.
+            let bucket = admin_vault.take_all();
+
+            Runtime::emit_event(AdminClaimEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                amount: bucket.amount(),
+            });
+
+            Some(bucket)
         }
 
-        // Validate the bet using assertions.
-        fn validate_bet(&self, payment: &Bucket) {
-            // Assert the market is not locked.
-            assert!(
-                !self.market_locked, 
-                "Market '{}' is locked. No more bets can be placed.", 
-                self.title
-            );
-        
-        let bet_amount = payment.amount();
-        
-        assert!(
-                bet_amount >= self.min_bet,
-                "Bet amount {} is below the minimum allowed of {}.", 
-                bet_amount, self.min_bet
-            );
-        
-        assert!(
-                bet_amount <= self.max_bet, 
-                "Bet amount {} exceeds the maximum allowed of {}.", 
-                bet_amount, self.max_bet
-            );
+/// Hands off the admin role to a freshly minted admin badge.
+///
+/// A new admin badge resource is created and the `admin` role is updated to require it (or the super-admin badge),
+/// so control can be transferred by sending the returned badge to the new admin.
+///
+/// **Edge case:** the previous admin badge becomes useless as soon as the rotation commits. Any admin-only call
+/// presenting it afterwards fails, and there is no way to reinstate it other than rotating again.
+///
+/// ---
+///
+/// **Access control:** Admin only.
+///
+/// **Transaction manifest:**
+/// `transactions/rotate_admin_badge.rtm`
+        pub fn rotate_admin_badge(&mut self) -> FungibleBucket {
+            let new_admin_badge = ResourceBuilder::new_fungible(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>"admin badge", locked;}))
+            .divisibility(DIVISIBILITY_NONE)
+            .mint_initial_supply(1);
 
-        assert!(
-                bet_amount > Decimal::from(0),
-                "Invalid bet amount."
+            Runtime::global_component().set_role(
+                "admin",
+                rule!(require_any_of(vec![new_admin_badge.resource_address(), self.super_admin_badge_address])),
             );
+
+            new_admin_badge
         }
 
-        // Get outcome position using assertion
-        fn get_outcome_position(&self, outcome: &String) -> usize {
-            self.outcomes.iter().position(|o| o == outcome)
-            .expect(&format!("Outcome '{}' does not exist. The available outcomes are: {:?}", outcome, self.outcomes))
-        } 
+/// Resolves the market by determining the winning outcome and distributing rewards accordingly.
+///
+/// This method identifies the winning outcome and transfers tokens from the losing vaults to the `xrd_vault`.
+/// It then processes the bets for the winning outcome and calculates the reward for each user based on 
+/// their stake and the odds. The house fee is deducted from every reward and moved to the house admin vault,
+/// claimable with `admin_claim("house")`. Rewards are transferred to the user's vault.
+///
+/// In `Parimutuel` mode the winning vault is pooled too and the odds are ignored: each winner receives
+/// `(user_bet / total_winning_stake) * (total_pool - fee)`.
+///
+/// Rewards and fees are rounded down to the bet token's divisibility, so e.g. a bet of 10 at odds of 1.333... pays
+/// 13.33 in a token with two decimal places. Any rounding remainder stays in the `xrd_vault`.
+///
+/// After the market is resolved, it resets the total staked amount and prevents any further interactions 
+/// with this market. The function emits a `MarketResolvedEvent` signaling the market's resolution status and the
+/// total house fee collected.
+///
+/// # Parameters:
+/// 
+/// * `winning_outcome`: The index of the winning outcome. This must be within the range of valid outcomes.
+///
+/// # Returns:
+///
+/// A `Result` containing a vector of tuples with user IDs and their corresponding rewards if successful, 
+/// or a `MarketError` if the market resolution fails for some reason. Rewards are listed, and paid out,
+/// in the order the winning bets were placed.
+///
+/// ---
+///
+/// **Access control:** Oracle only. Only the holder of the market's oracle badge can resolve the market.
+///
+/// **Errors:** If the market has a dispute window; such markets are resolved with `propose_resolution` and
+/// `finalize_resolution`. In strict resolution mode, returns `Err(MarketError::WinningOutcomeHasNoBets)` if nobody
+/// bet on the winning outcome.
+///
+/// **Transaction manifest:**
+/// `transactions/resolve_market.rtm`
+        pub fn resolve_market(&mut self, winning_outcome: u32) -> Result<Vec<(String, Decimal)>, MarketError> {
+            self.ensure_no_dispute_window();
 
-        fn reset_and_resolve_market(&mut self) {
-        self.total_staked = Decimal::from(0);
-        self.market_resolved = true;
+            Ok(self.resolve_validated(winning_outcome, false)?.rewards)
+        }
+
+/// Resolves the market to a winning outcome and returns a report of the resolution.
+///
+/// Pays out exactly as `resolve_market` does. Besides the rewards, the returned `ResolutionReport` carries the total
+/// pool, the total paid to the winning bets and shares, the house fee, the house's net and the number of winning
+/// bettors, so front-ends don't need to add up the rewards themselves.
+///
+/// # Parameters:
+///
+/// * `winning_outcome`: The index of the winning outcome. This must be within the range of valid outcomes.
+///
+/// ---
+///
+/// **Access control:** Oracle only.
+///
+/// **Errors:** As `resolve_market`.
+///
+/// **Transaction manifest:**
+/// `transactions/resolve_market_with_report.rtm`
+        pub fn resolve_market_with_report(&mut self, winning_outcome: u32) -> Result<ResolutionReport, MarketError> {
+            self.ensure_no_dispute_window();
+
+            self.resolve_validated(winning_outcome, false)
+        }
+
+/// Resolves the market to a winning outcome, paying the winners pro rata if the market can't cover them in full.
+///
+/// Meant for a `FixedOdds` market whose treasury was under-funded for the outcome that won. Rather than failing
+/// part-way through the payouts, every stake is pooled into the `xrd_vault` and the rewards owed, net of the house
+/// fee, are compared with what it holds. If it falls short, the house fee is waived and every winner is paid
+/// `owed * available / total_owed`, rounded down to the bet token's divisibility. Rounding dust stays in the
+/// `xrd_vault`. If the winners can be paid but the fee can't, the house takes whatever is left over.
+///
+/// The ratio applied is reported in the `MarketResolvedEvent` and in each winner's `ClaimRewardEvent`. It is
+/// `None` when the winners were paid in full, in which case the payouts are the same as with `resolve_market`.
+///
+/// # Parameters:
+///
+/// * `winning_outcome`: The index of the winning outcome. This must be within the range of valid outcomes.
+///
+/// # Returns:
+///
+/// A `Result` containing a vector of tuples with user IDs and the rewards they were actually paid.
+///
+/// ---
+///
+/// **Access control:** Oracle only, like every other resolution path.
+///
+/// **Errors:** As `resolve_market`.
+///
+/// **Transaction manifest:**
+/// `transactions/resolve_market_pro_rata.rtm`
+        pub fn resolve_market_pro_rata(&mut self, winning_outcome: u32) -> Result<Vec<(String, Decimal)>, MarketError> {
+            self.ensure_no_dispute_window();
+
+            Ok(self.resolve_validated(winning_outcome, true)?.rewards)
+        }
+
+/// Proposes a winning outcome, opening the dispute window.
+///
+/// No funds move yet: the proposal can be withdrawn with `cancel_proposed_resolution`, or paid out with
+/// `finalize_resolution` once `dispute_window_epochs` have elapsed. While the proposal is pending the market is
+/// frozen as if locked: bets, cancellations, close-outs, odds and limit changes and new liquidity are all rejected
+/// with `ResolutionPending`. A `ResolutionProposedEvent` is emitted.
+///
+/// # Errors:
+///
+/// * If the market was already resolved or another proposal is pending.
+/// * If the winning outcome is out of bounds.
+///
+/// ---
+///
+/// **Access control:** Oracle only.
+///
+/// **Transaction manifest:**
+/// `transactions/propose_resolution.rtm`
+        pub fn propose_resolution(&mut self, winning_outcome: u32) {
+            self.ensure_market_not_resolved();
+            assert!(self.pending_resolution.is_none(), "{:?}", MarketError::ResolutionPending);
+            self.ensure_outcome_in_bounds(winning_outcome);
+
+            let proposed_at = Runtime::current_epoch();
+            self.pending_resolution = Some((winning_outcome, proposed_at));
+
+            Runtime::emit_event(ResolutionProposedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                winning_outcome,
+                finalizable_from: proposed_at.after(self.dispute_window_epochs).unwrap(),
+            });
+        }
+
+/// Withdraws the pending resolution proposal, returning the market to the unresolved state.
+///
+/// Betting is accepted again unless the market is locked. A `ResolutionProposalCancelledEvent` is emitted.
+///
+/// ---
+///
+/// **Access control:** Oracle only.
+///
+/// **Errors:** If no proposal is pending.
+///
+/// **Transaction manifest:**
+/// `transactions/cancel_proposed_resolution.rtm`
+        pub fn cancel_proposed_resolution(&mut self) {
+            let (winning_outcome, _) = self.pending_resolution.take()
+                .unwrap_or_else(|| panic!("{:?}", MarketError::NoPendingResolution));
+
+            Runtime::emit_event(ResolutionProposalCancelledEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                winning_outcome,
+            });
+        }
+
+/// Resolves the market to the proposed outcome once the dispute window has elapsed.
+///
+/// Rewards are distributed exactly as with `resolve_market` and a `MarketResolvedEvent` is emitted.
+///
+/// # Errors:
+///
+/// * `Err(MarketError::NoPendingResolution)` if no proposal is pending.
+/// * `Err(MarketError::DisputeWindowOpen)` if fewer than `dispute_window_epochs` have passed since the proposal.
+/// * In strict resolution mode, `Err(MarketError::WinningOutcomeHasNoBets)` if nobody bet on the proposed outcome.
+///   The proposal stays pending, so the oracle can cancel it and propose another outcome.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/finalize_resolution.rtm`
+        pub fn finalize_resolution(&mut self) -> Result<Vec<(String, Decimal)>, MarketError> {
+            let (winning_outcome, proposed_at) = match self.pending_resolution {
+                Some(pending_resolution) => pending_resolution,
+                None => return Err(MarketError::NoPendingResolution),
+            };
+            let finalizable_from = proposed_at.after(self.dispute_window_epochs).unwrap();
+            if Runtime::current_epoch() < finalizable_from {
+                return Err(MarketError::DisputeWindowOpen { finalizable_from });
+            }
+
+            let report = self.resolve_validated(winning_outcome, false)?;
+            self.pending_resolution = None;
+            Ok(report.rewards)
+        }
+
+/// Resolves the market with several winning outcomes and distributes rewards accordingly.
+///
+/// This is used for events that resolve to more than one outcome, such as a dead heat. Only the pools of the
+/// non-winning outcomes are moved to the `xrd_vault`. In `FixedOdds` mode the standard dead-heat reduction applies:
+/// every bettor on a winning outcome is paid `stake * odds / number_of_winning_outcomes`. In `Parimutuel` mode the
+/// pool is shared pro-rata across all winning stakes. The house fee is deducted as in `resolve_market`.
+/// A `MarketResolvedMultiEvent` carrying all winners is emitted.
+///
+/// # Parameters:
+/// 
+/// * `winning_outcomes`: The indices of the winning outcomes. Must be non-empty, unique and within the range of valid outcomes.
+///
+/// # Returns:
+///
+/// A `Result` containing a vector of tuples with user IDs and their corresponding rewards, one entry per winning bet.
+/// Rewards are ordered by outcome index, then by the order the bets were placed, whatever the order of `winning_outcomes`.
+///
+/// ---
+///
+/// **Access control:** Oracle only. Only the holder of the market's oracle badge can resolve the market.
+///
+/// **Errors:** If the market has a dispute window. In strict resolution mode, returns
+/// `Err(MarketError::WinningOutcomeHasNoBets)` if nobody bet on one of the winning outcomes.
+///
+/// **Transaction manifest:**
+/// `transactions/resolve_market_multi.rtm`
+        pub fn resolve_market_multi(&mut self, winning_outcomes: Vec<u32>) -> Result<Vec<(String, Decimal)>, MarketError> {
+            // Check that the market is unresolved and the winning outcomes are valid.
+            self.ensure_no_dispute_window();
+            self.ensure_market_not_resolved();
+            assert!(!winning_outcomes.is_empty(), "At least one winning outcome must be provided.");
+            let unique_outcomes: HashSet<&u32> = winning_outcomes.iter().collect();
+            assert_eq!(unique_outcomes.len(), winning_outcomes.len(), "Duplicate winning outcomes provided.");
+            for winning_outcome in &winning_outcomes {
+                self.ensure_outcome_in_bounds(*winning_outcome);
+            }
+            self.ensure_winning_bets(&winning_outcomes)?;
+
+            // Settle in outcome order so the rewards don't depend on the order the winners were listed in.
+            let mut winning_indices: Vec<usize> = winning_outcomes.iter().map(|index| *index as usize).collect();
+            winning_indices.sort_unstable();
+            let (rewards, house_fee, _) = self.settle_market(&winning_indices, false);
+
+            // Emit that the market has been resolved.
+            Runtime::emit_event(MarketResolvedMultiEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                winning_outcomes,
+                house_fee,
+            });
+
+            Ok(rewards)
+        }
+
+/// Issues a resolver badge carrying a voting weight.
+///
+/// Resolver badges let several independent parties settle the market with `vote_resolution` instead of a
+/// single admin. Badges can only be issued before the first vote is cast, so the majority threshold can't
+/// move while voting is under way.
+///
+/// # Parameters:
+/// 
+/// * `weight`: The weight of the badge's vote. Must be greater than zero.
+///
+/// ---
+///
+/// **Access control:** Admin only.
+///
+/// **Transaction manifest:**
+/// `transactions/mint_resolver_badge.rtm`
+        pub fn mint_resolver_badge(&mut self, weight: Decimal) -> Bucket {
+            self.ensure_market_not_resolved();
+            assert!(weight > Decimal::zero(), "Resolver weight must be greater than zero. Provided: {}", weight);
+            assert!(self.resolution_votes.is_empty(), "Resolver badges can't be issued once voting has started.");
+
+            self.resolver_badges_issued += 1;
+            self.total_resolver_weight += weight;
+            self.resolver_badge_manager.mint_non_fungible(
+                &NonFungibleLocalId::integer(self.resolver_badges_issued),
+                ResolverBadgeData { weight },
+            )
+        }
+
+/// Votes for the winning outcome with one or more resolver badges.
+///
+/// Each badge in the proof adds its weight to `outcome` and can only vote once. As soon as an outcome holds more
+/// than half of the total resolver weight the market is resolved to it, exactly as with `resolve_market`.
+/// A resolver majority settles the market immediately, even if it has a dispute window, and supersedes any
+/// pending proposal. It goes through the same checks as `resolve_market`, strict resolution mode included.
+///
+/// # Parameters:
+/// 
+/// * `resolver_proof`: A `Proof` of one or more resolver badges minted by `mint_resolver_badge`.
+/// * `outcome`: The index of the outcome voted for.
+///
+/// # Errors:
+///
+/// * If the proof is not of this market's resolver badge resource.
+/// * If any of the badges has already voted.
+/// * If the outcome is out of bounds or the market was already resolved.
+/// * In strict resolution mode, `Err(MarketError::WinningOutcomeHasNoBets)` if the vote would resolve the market to
+///   an outcome nobody bet on. The vote is not recorded, so the badges can vote for another outcome.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of a resolver badge.
+///
+/// **Transaction manifest:**
+/// `transactions/vote_resolution.rtm`
+        pub fn vote_resolution(&mut self, resolver_proof: Proof, outcome: u32) -> Result<(), MarketError> {
+            self.ensure_market_not_resolved();
+            self.ensure_outcome_in_bounds(outcome);
+
+            let checked_proof = resolver_proof.check(self.resolver_badge_manager.address());
+            let badges = checked_proof.as_non_fungible().non_fungibles::<ResolverBadgeData>();
+            let mut weight = Decimal::zero();
+            for badge in &badges {
+                assert!(
+                    !self.resolution_votes.contains_key(badge.local_id()),
+                    "Resolver badge {} has already voted.",
+                    badge.local_id()
+                );
+                weight += badge.data().weight;
+            }
+
+            // Resolve once the outcome holds a strict majority of the resolver weight. Strict mode is checked before
+            // anything is recorded so a rejected vote leaves the badges free to vote again.
+            let reaches_majority =
+                (self.outcome_vote_weights[outcome as usize] + weight) * Decimal::from(2) > self.total_resolver_weight;
+            if reaches_majority {
+                self.ensure_winning_bets(&[outcome])?;
+            }
+
+            for badge in &badges {
+                self.resolution_votes.insert(badge.local_id().clone(), outcome);
+            }
+            self.outcome_vote_weights[outcome as usize] += weight;
+
+            Runtime::emit_event(ResolutionVoteEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                outcome,
+                weight,
+            });
+
+            if reaches_majority {
+                self.resolve_validated(outcome, false)?;
+                self.pending_resolution = None;
+            }
+            Ok(())
+        }
+
+/// Resolves the market as void, refunding all participants with their betted amounts.
+///
+/// This method is utilized in situations where the market cannot be settled based on a specific outcome, 
+/// due to unforeseen circumstances or other reasons that prevent a definitive resolution. As a result, 
+/// all participants are refunded their initial stake, ensuring no loss or gain from their bets.
+///
+/// # Preconditions
+///
+/// - The market should not have been resolved before.
+///
+/// # Side Effects
+///
+/// - All tokens in the outcome vaults are transferred to the xrd_vault.
+/// - All users are refunded their original staked amounts of XRD from the xrd_vault back to their respective vaults. 
+///   Users can subsequently claim these amounts. Refunds are made in outcome order, then in the order the bets
+///   were placed.
+/// - The market is marked as resolved to prevent further bets or interactions.
+/// - An event, `MarketResolvedAsVoidEvent`, is emitted to signal the resolution.
+///
+/// # Errors
+///
+/// - If the market was already resolved.
+/// - Returns `Err(MarketError::InsufficientCollateral)`, without moving any funds, if the outcome vaults and the xrd_vault
+///   together hold less than the total staked. This guarantees refunds are all-or-nothing.
+/// 
+///  # Returns
+///
+/// - `Ok(())` if the market is successfully resolved as void.
+///
+/// ---
+///
+/// **Access control:** Oracle only. Only the holder of the market's oracle badge can resolve the market.
+///
+/// **Transaction manifest:**
+/// `transactions/resolve_market_as_void.rtm`
+        pub fn resolve_market_as_void(&mut self) -> Result<(), MarketError> {
+            self.void_market(false)
+        }
+
+/// Voids a market the admin failed to resolve before its resolution deadline and refunds all bets.
+///
+/// This is the rescue path for users if the admin disappears: it performs the same refunds as
+/// `resolve_market_as_void`, after which every user can claim their stake back with `claim_refund`.
+/// The emitted `MarketResolvedAsVoidEvent` has `forced` set to `true`.
+///
+/// # Errors
+///
+/// - If the market has no resolution deadline, or the deadline hasn't passed yet.
+/// - If the market was already resolved.
+/// - Returns `Err(MarketError::InsufficientCollateral)`, without moving any funds, if the funds held can't cover every refund.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/force_void_expired_market.rtm`
+        pub fn force_void_expired_market(&mut self) -> Result<(), MarketError> {
+            let deadline = self.resolution_deadline.expect("Market has no resolution deadline.");
+            assert!(
+                Runtime::current_epoch() > deadline,
+                "The admin can still resolve the market until epoch {}.",
+                deadline.number()
+            );
+
+            self.void_market(true)
+        }
+
+/// Reinstates a user who self-excluded, allowing them to bet again.
+///
+/// Reinstatement is only possible once `SELF_EXCLUSION_COOLDOWN_EPOCHS` epochs have passed since the user
+/// excluded themselves.
+///
+/// ---
+///
+/// **Access control:** Admin only.
+///
+/// **Transaction manifest:**
+/// `transactions/reinstate_user.rtm`
+        pub fn reinstate_user(&mut self, user_hash: String) {
+            let excluded_at = self.excluded_users.get(&user_hash)
+                .map(|excluded_at| *excluded_at)
+                .unwrap_or_else(|| panic!("User '{}' is not self-excluded.", user_hash));
+            let cooldown_end = excluded_at.number() + SELF_EXCLUSION_COOLDOWN_EPOCHS;
+            assert!(
+                Runtime::current_epoch().number() >= cooldown_end,
+                "User '{}' cannot be reinstated before epoch {}.",
+                user_hash, cooldown_end
+            );
+
+            self.excluded_users.remove(&user_hash);
+        }
+
+/// Sweeps the rewards and refunds users left unclaimed past the claim deadline into the house admin vault.
+///
+/// Sweeps the users from position `offset` up to `offset + limit` (in the order they first bet) so large markets
+/// can be swept over several transactions. Every one of their vaults still holding funds is emptied, after which
+/// `claim_reward` pays those users nothing. The swept funds can be claimed with `admin_claim("house")`. An
+/// `UnclaimedSweptEvent` with the page's total and number of affected users is emitted.
+///
+/// Returns the total number of users, so the admin knows when to stop paging.
+///
+/// ---
+///
+/// **Access control:** Admin only.
+///
+/// **Errors:** If the market has no claim deadline, hasn't been resolved or voided yet, or the deadline
+/// (`claim_deadline_epochs` after settlement) hasn't passed.
+///
+/// **Transaction manifest:**
+/// `transactions/sweep_unclaimed.rtm`
+        pub fn sweep_unclaimed(&mut self, offset: usize, limit: usize) -> usize {
+            let claim_deadline_epochs = self.claim_deadline_epochs.expect("Market has no claim deadline.");
+            let resolved_at = self.resolved_at
+                .unwrap_or_else(|| panic!("Market '{}' has not been resolved yet.", self.title));
+            let claim_deadline = resolved_at.after(claim_deadline_epochs).unwrap();
+            assert!(
+                Runtime::current_epoch() > claim_deadline,
+                "Users can still claim until epoch {}.",
+                claim_deadline.number()
+            );
+
+            self.ensure_admin_vault_exists(HOUSE_FEE_ADMIN_HASH.to_string());
+            let house_vault = self.admin_vaults.get_mut(HOUSE_FEE_ADMIN_HASH).unwrap();
+            let mut total_swept = Decimal::zero();
+            let mut users_affected = 0u32;
+            for user in self.user_hash_page(offset, limit) {
+                let mut user_vault = self.user_vaults.get_mut(&user).unwrap();
+                if user_vault.is_empty() {
+                    continue;
+                }
+                let unclaimed = user_vault.take_all();
+                total_swept += unclaimed.amount();
+                users_affected += 1;
+                house_vault.put(unclaimed);
+            }
+
+            Runtime::emit_event(UnclaimedSweptEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                total_swept,
+                users_affected,
+            });
+            self.user_count as usize
+        }
+
+      // 3. Betting and Claiming Rewards - Users only:
+
+/// Allows a user to place a bet on a specific outcome of the market.
+///
+/// This method enables users to stake a certain amount of tokens (contained within the `payment` bucket)
+/// on an outcome they predict will win. Once the bet is placed, the staked amount is added to the outcome's
+/// vault and the bet is recorded. If the outcome is correct when the market is resolved, the user can
+/// claim their rewards.
+///
+/// # Preconditions:
+/// 
+/// * The market should not have been resolved before.
+/// * The payment amount should be within valid bounds. `min_bet` only applies to the user's first bet on the
+///   outcome; a top-up can be any positive amount.
+/// * The outcome on which the bet is placed should be valid.
+///
+/// # Side Effects:
+///
+/// * The payment amount is added to the vault associated with the chosen outcome. If a top-up would take the
+///   user's bet past `max_bet`, only the allowed remainder is staked and the excess is returned as change. This also
+///   applies to a first bet larger than `max_bet`.
+/// * The total staked amount in the market is updated by the accepted amount.
+/// * The bet is either updated (if it exists) or added to the list of bets.
+/// * A claim badge is minted the first time a `user_hash` places a bet.
+/// * A bet receipt recording the outcome, the staked amount, the current odds and epoch is minted for the bet.
+/// * An event, `BetPlacedEvent`, is emitted to signal the bet placement.
+///
+/// # Parameters:
+/// 
+/// * `user_hash`: A unique identifier (hash) for the user placing the bet.
+/// * `outcome`: The outcome on which the user is betting.
+/// * `payment`: A `Bucket` object containing the staked tokens for the bet.
+///
+/// # Errors:
+///
+/// * If the market was already resolved.
+/// * If the user has self-excluded.
+/// * If the payment is not in the market's `bet_token`.
+/// * If the user has already bet the maximum allowed on the outcome.
+///
+/// # Returns:
+///
+/// A tuple of:
+/// * An `Option<Bucket>`:
+///     - `Some(Bucket)` containing the user's claim badge if this is their first bet.
+///     - `None` if the user already holds a claim badge for this market.
+/// * An `Option<Bucket>`:
+///     - `Some(Bucket)` containing the change if only part of the payment could be staked.
+///     - `None` if the whole payment was staked.
+/// * A `Bucket` containing the bet receipt.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+///  **Transaction manifest:**
+/// `transactions/place_bet.rtm`
+        pub fn place_bet(&mut self, user_hash: String, outcome: String, payment: Bucket) -> (Option<Bucket>, Option<Bucket>, Bucket) {
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.place_bet_at(user_hash, outcome_position, payment)
+    }
+
+/// Places a bet on the outcome at `outcome_index`, in the order returned by `list_outcomes`.
+///
+/// Behaves exactly like `place_bet`, but skips matching the outcome by name, so a frontend can't be tripped up by
+/// differences in whitespace or casing.
+///
+/// # Errors:
+///
+/// * If `outcome_index` is out of bounds.
+/// * Otherwise the same as `place_bet`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+///  **Transaction manifest:**
+/// `transactions/place_bet_by_index.rtm`
+        pub fn place_bet_by_index(&mut self, user_hash: String, outcome_index: u32, payment: Bucket) -> (Option<Bucket>, Option<Bucket>, Bucket) {
+            self.ensure_outcome_in_bounds(outcome_index);
+            self.place_bet_at(user_hash, outcome_index as usize, payment)
+        }
+
+/// Places bets on several outcomes at once, e.g. to dutch a three-way market.
+///
+/// The payment is split into one leg per `(outcome, amount)` entry, and each leg is validated and staked like a bet
+/// placed with `place_bet`, emitting its own `BetPlacedEvent`. The call is all or nothing: if any leg is rejected,
+/// including a leg that would take the user past `max_bet`, no bet is placed.
+///
+/// # Parameters:
+/// 
+/// * `user_hash`: A unique identifier (hash) for the user placing the bets.
+/// * `bets`: The `(outcome, amount)` legs. The amounts must add up to exactly the payment.
+/// * `payment`: A `Bucket` containing the combined stake.
+///
+/// # Errors:
+///
+/// * If `bets` is empty, or the amounts don't add up to the payment.
+/// * If any leg fails the checks of `place_bet`, or can't be staked in full.
+///
+/// # Returns:
+///
+/// A tuple of:
+/// * An `Option<Bucket>` containing the user's claim badge if these are their first bets.
+/// * A `Vec<Bucket>` with one bet receipt per leg, in the order of `bets`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+///  **Transaction manifest:**
+/// `transactions/place_bets.rtm`
+        pub fn place_bets(&mut self, user_hash: String, bets: Vec<(String, Decimal)>, mut payment: Bucket) -> (Option<Bucket>, Vec<Bucket>) {
+            assert!(!bets.is_empty(), "At least one bet must be provided.");
+            self.ensure_bet_token(&payment);
+            let total = bets.iter().fold(Decimal::zero(), |total, (_, amount)| total + *amount);
+            assert_eq!(
+                total,
+                payment.amount(),
+                "The bet amounts add up to {} but the payment is {}.",
+                total, payment.amount()
+            );
+
+            let claim_badge = self.ensure_user_vault_exists(user_hash.clone());
+            let bet_receipts = bets.into_iter()
+                .map(|(outcome, amount)| {
+                    let outcome_position = self.get_outcome_position(&outcome);
+                    let (change, bet_receipt) = self.stake_bet(user_hash.clone(), outcome_position, payment.take(amount));
+                    // A partly staked leg would unbalance the dutch, so it fails the whole call.
+                    if let Some(change) = change {
+                        panic!(
+                            "{:?}",
+                            MarketError::BetAboveMaximum { amount: self.bet_amount(&user_hash, outcome_position) + change.amount(), maximum: self.max_bet }
+                        );
+                    }
+                    bet_receipt
+                })
+                .collect();
+            payment.drop_empty();
+
+            (claim_badge, bet_receipts)
+        }
+
+/// Stakes on an outcome in exchange for fungible outcome shares, one per token staked.
+///
+/// Unlike `place_bet`, the position isn't tied to a user: it lives in the shares alone, which can be transferred or
+/// traded on a DEX before the market settles, and is paid out to whoever redeems them with `redeem_shares`. No claim
+/// badge, bet receipt or entry in the bet store is created, so the purchase can't be cancelled and doesn't count
+/// towards `max_user_total`.
+///
+/// The stake otherwise counts like a bet: it joins the outcome's pool and the market's total, must be within
+/// `min_bet` and `max_bet`, and is subject to the same caps and betting restrictions. In a `FixedOdds` market it is
+/// paid at the odds quoted now; since shares are fungible, all shares of an outcome are paid the same rate, the
+/// average of the odds they were bought at. A `SharesBoughtEvent` is emitted.
+///
+/// # Parameters:
+///
+/// * `outcome`: The outcome to stake on.
+/// * `payment`: The stake, in the market's bet token.
+///
+/// # Returns:
+///
+/// A `Bucket` of the outcome's share tokens.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Errors:** As `place_bet`.
+///
+///  **Transaction manifest:**
+/// `transactions/buy_shares.rtm`
+        pub fn buy_shares(&mut self, outcome: String, payment: Bucket) -> Bucket {
+            self.ensure_market_not_resolved();
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.ensure_bet_token(&payment);
+            self.validate_bet(&payment);
+            let outcome = self.outcomes[outcome_position].clone();
+            assert!(
+                !self.locked_outcomes.contains(&outcome_position),
+                "{:?}",
+                MarketError::OutcomeLocked { outcome: outcome.clone() }
+            );
+            let amount = payment.amount();
+            assert!(amount >= self.min_bet, "{:?}", MarketError::BetBelowMinimum { amount, minimum: self.min_bet });
+            assert!(amount <= self.max_bet, "{:?}", MarketError::BetAboveMaximum { amount, maximum: self.max_bet });
+
+            let (odds, locked_payout) = self.add_stake(outcome_position, payment);
+            self.shares_outstanding[outcome_position] += amount;
+            self.share_locked_payouts[outcome_position] += locked_payout;
+
+            Runtime::emit_event(SharesBoughtEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                outcome,
+                amount,
+                odds,
+            });
+
+            self.share_managers[outcome_position].mint(amount)
+        }
+
+/// Deposits funds into a user's pre-funded betting balance.
+///
+/// The balance can then be spent with `place_bet_from_balance`, saving the user from supplying a bucket for
+/// every bet. The balance is kept apart from the user's winnings and is never touched by market resolution.
+/// A claim badge is minted if this is the first time the user interacts with the market.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+///  **Transaction manifest:**
+/// `transactions/deposit_user_balance.rtm`
+        pub fn deposit_user_balance(&mut self, user_hash: String, deposit: Bucket) -> Option<Bucket> {
+            assert!(!deposit.is_empty(), "Deposit bucket is empty.");
+            self.ensure_bet_token(&deposit);
+
+            let claim_badge = self.ensure_user_vault_exists(user_hash.clone());
+            if self.user_balances.get(&user_hash).is_none() {
+                self.user_balances.insert(user_hash.clone(), Vault::new(self.bet_token));
+            }
+            self.user_balances.get_mut(&user_hash).unwrap().put(deposit);
+
+            claim_badge
+        }
+
+/// Places a bet funded from the user's pre-funded balance.
+///
+/// Behaves like `place_bet`, except the stake is drawn from the balance of the user identified by the claim
+/// badge. Any part of `amount` that can't be staked because of `max_bet` stays in the balance.
+/// Returns the bet receipt.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of the user's claim badge.
+/// 
+///  **Transaction manifest:**
+/// `transactions/place_bet_from_balance.rtm`
+        pub fn place_bet_from_balance(&mut self, claim_badge: Proof, outcome: String, amount: Decimal) -> Bucket {
+            let user_hash = self.authenticate_user(claim_badge);
+
+            let payment = {
+                let mut balance = self.user_balances.get_mut(&user_hash).expect("No balance has been deposited.");
+                assert!(
+                    balance.amount() >= amount,
+                    "Insufficient balance. Requested: {}, Available: {}",
+                    amount, balance.amount()
+                );
+                balance.take(amount)
+            };
+
+            let outcome_position = self.get_outcome_position(&outcome);
+            let (change, bet_receipt) = self.stake_bet(user_hash.clone(), outcome_position, payment);
+            if let Some(change) = change {
+                self.user_balances.get_mut(&user_hash).unwrap().put(change);
+            }
+
+            bet_receipt
+        }
+
+/// Withdraws the remainder of the user's pre-funded balance.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of the user's claim badge.
+/// 
+///  **Transaction manifest:**
+/// `transactions/withdraw_user_balance.rtm`
+        pub fn withdraw_user_balance(&mut self, claim_badge: Proof) -> Bucket {
+            let user_hash = self.authenticate_user(claim_badge);
+
+            self.user_balances.get_mut(&user_hash).expect("No balance has been deposited.").take_all()
+        }
+
+/// Locks a market whose betting deadline has passed.
+///
+/// Bets are already rejected after the deadline even if the admin never calls `lock_market`. This method lets
+/// anyone flip the market into the locked state afterwards, so a `MarketLockedEvent` is emitted and indexers
+/// see the same event as for a manual lock.
+///
+/// # Errors:
+///
+/// * If the market has no betting deadline, or the deadline hasn't passed yet.
+/// * If the market is already locked or resolved.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+///  **Transaction manifest:**
+/// `transactions/trigger_close.rtm`
+        pub fn trigger_close(&mut self) {
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+
+            let deadline = self.betting_deadline.expect("Market has no betting deadline.");
+            assert!(
+                Runtime::current_epoch() > deadline,
+                "The betting period is still open until epoch {}.",
+                deadline.number()
+            );
+
+            self.lock_market();
+        }
+
+/// Allows a user to exclude themselves from betting on this market.
+///
+/// Once excluded, every `place_bet` for the user is rejected until an admin reinstates them with
+/// `reinstate_user`, which is only possible after a cooldown. Existing bets and claims are unaffected.
+///
+/// # Parameters:
+/// 
+/// * `claim_badge`: A `Proof` of the claim badge returned by `place_bet`. The user excluded is the badge's owner.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of the user's claim badge.
+/// 
+/// **Errors:** If the proof is not of this market's claim badge resource.
+/// 
+///  **Transaction manifest:**
+/// `transactions/self_exclude.rtm`
+        pub fn self_exclude(&mut self, claim_badge: Proof) {
+            let user_hash = self.authenticate_user(claim_badge);
+            self.excluded_users.insert(user_hash, Runtime::current_epoch());
+        }
+
+/// Allows a user to cancel a bet before the market is locked.
+///
+/// The user's stake on the outcome is taken out of the outcome's vault and refunded into the user's vault,
+/// from where it can be claimed with `claim_reward`.
+///
+/// # Preconditions:
+/// 
+/// * The market should not be locked or resolved.
+/// * The user should have a bet on the given outcome.
+///
+/// # Side Effects:
+///
+/// * The bet is removed from the list of bets.
+/// * The staked amount is moved from the outcome's vault to the user's vault.
+/// * The total staked amount in the market is reduced by the refunded amount.
+/// * An event, `BetCancelledEvent`, is emitted to signal the cancellation.
+///
+/// # Parameters:
+/// 
+/// * `claim_badge`: A `Proof` of the claim badge returned by `place_bet`.
+/// * `outcome`: The outcome the bet was placed on.
+///
+/// # Errors:
+///
+/// * If the proof is not of this market's claim badge resource.
+/// * If the market is locked or was already resolved.
+/// * If the user has no bet on the given outcome.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of the user's claim badge.
+/// 
+///  **Transaction manifest:**
+/// `transactions/cancel_bet.rtm`
+        pub fn cancel_bet(&mut self, claim_badge: Proof, outcome: String) {
+            let user_hash = self.authenticate_user(claim_badge);
+
+            // Ensure the market is still open for betting.
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+
+            // Remove the user's bet on the outcome.
+            let outcome_position = self.get_outcome_position(&outcome);
+            let (bet_amount, locked_payout) = self.remove_bet(&user_hash, outcome_position)
+                .unwrap_or_else(|| panic!("No bet from '{}' found on outcome '{}'.", user_hash, outcome));
+
+            // Refund the stake into the user's vault.
+            let refund = self.outcome_tokens[outcome_position].take(bet_amount);
+            self.total_staked -= bet_amount;
+            self.per_outcome_staked[outcome_position] -= bet_amount;
+            self.lifetime_staked -= bet_amount;
+            self.outcome_liabilities[outcome_position] -= locked_payout;
+            self.user_vaults.get_mut(&user_hash).expect("User vault not found.").put(refund);
+
+            Runtime::emit_event(BetCancelledEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                user_hash,
+                outcome,
+                amount: bet_amount,
+            });
+        }
+
+/// Closes out all of a user's positions before the market is locked.
+///
+/// Every bet the user holds is removed and the combined stake across all outcomes is returned directly,
+/// instead of cancelling each outcome separately with `cancel_bet`.
+///
+/// # Preconditions:
+/// 
+/// * The market should not be locked or resolved.
+/// * The user should have at least one bet in the market.
+///
+/// # Side Effects:
+///
+/// * All of the user's bets are removed from the list of bets.
+/// * The staked amounts are taken from the outcomes' vaults and the total staked amount is reduced accordingly.
+/// * A `BetCancelledEvent` is emitted for every closed position.
+///
+/// # Parameters:
+/// 
+/// * `claim_badge`: A `Proof` of the claim badge returned by `place_bet`.
+///
+/// # Errors:
+///
+/// * If the proof is not of this market's claim badge resource.
+/// * If the market is locked or was already resolved.
+/// * If the user has no bets in the market.
+///
+/// # Returns:
+///
+/// * A `Bucket` holding the user's combined stake.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of the user's claim badge.
+/// 
+///  **Transaction manifest:**
+/// `transactions/close_out.rtm`
+        pub fn close_out(&mut self, claim_badge: Proof) -> Bucket {
+            let user_hash = self.authenticate_user(claim_badge);
+
+            // Ensure the market is still open for betting.
+            self.ensure_market_not_resolved();
+            self.ensure_market_not_locked();
+
+            let mut payout = Bucket::new(self.bet_token);
+            for outcome_position in 0..self.outcomes.len() {
+                let outcome = self.outcomes[outcome_position].clone();
+
+                // Remove the user's bet on the outcome, if any.
+                let (bet_amount, locked_payout) = match self.remove_bet(&user_hash, outcome_position) {
+                    Some(bet) => bet,
+                    None => continue,
+                };
+
+                payout.put(self.outcome_tokens[outcome_position].take(bet_amount));
+                self.total_staked -= bet_amount;
+                self.per_outcome_staked[outcome_position] -= bet_amount;
+                self.lifetime_staked -= bet_amount;
+                self.outcome_liabilities[outcome_position] -= locked_payout;
+
+                Runtime::emit_event(BetCancelledEvent {
+                    market_id: self.title.clone(),
+                    sequence: self.next_sequence(),
+                    user_hash: user_hash.clone(),
+                    outcome,
+                    amount: bet_amount,
+                });
+            }
+
+            assert!(!payout.is_empty(), "No bets from '{}' found in market '{}'.", user_hash, self.title);
+            payout
+        }
+
+/// Allows a user to claim their reward after a market is resolved.
+///
+/// This method enables users to retrieve their rewards from a previously placed bet, given that their prediction was accurate. 
+/// The user is identified by the claim badge minted on their first bet, so only the badge holder can claim.
+/// The reward tokens are extracted from the user's vault, and an event is emitted to indicate a successful claim.
+///
+/// # Preconditions:
+/// 
+/// * The market should have been resolved before a user attempts to claim their reward.
+///
+/// # Side Effects:
+///
+/// * The tokens equivalent to the user's reward are removed from their vault.
+/// * An event, `ClaimRewardEvent`, is emitted to signal the successful reward claim. No event is emitted when there
+///   is nothing to claim.
+///
+/// # Parameters:
+/// 
+/// * `claim_badge`: A `Proof` of the claim badge returned by `place_bet`.
+///
+/// # Errors:
+///
+/// * If the proof is not of this market's claim badge resource.
+///
+/// # Returns:
+///
+/// * An `Option<Bucket>`: 
+///     - `Some(Bucket)` containing the tokens if the claim is successful.
+///     - `None` if the user does not have a vault or no reward to claim (e.g. they already claimed or never won).
+///       The transaction still commits in that case.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of the user's claim badge.
+///
+///  **Transaction manifest:**
+/// `transactions/claim_reward.rtm`
+    pub fn claim_reward(&mut self, claim_badge: Proof) -> Option<Bucket> {
+        let user_hash = self.authenticate_user(claim_badge);
+
+        self.withdraw_user_reward(user_hash)
+    }
+
+/// Allows a user to claim their reward using only their `user_hash`.
+///
+/// **Deprecated:** this path is unauthenticated and will be removed in the next release. Use `claim_reward` with the
+/// user's claim badge, or `redeem_receipt` with a bet receipt, instead.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Errors:** If the admin has not turned the path on with `set_hash_claims_enabled`.
+///
+///  **Transaction manifest:**
+/// `transactions/claim_reward_by_hash.rtm`
+    pub fn claim_reward_by_hash(&mut self, user_hash: String) -> Option<Bucket> {
+        assert!(
+            self.hash_claims_enabled,
+            "Claiming by user hash is disabled in market '{}'. Present a claim badge or bet receipt instead.",
+            self.title
+        );
+        self.withdraw_user_reward(user_hash)
+    }
+
+/// Redeems a bet receipt for the settled value of the bet it records, whoever holds it.
+///
+/// The receipt is burned and paid from its own data, out of the bettor's vault:
+/// - A winning bet pays `amount * odds` in `FixedOdds` mode, or its share of the pool in `Parimutuel` mode, net of
+///   the house fee and with the same dead-heat reduction and haircut as `resolve_market`.
+/// - A losing bet pays nothing.
+/// - In a voided market the bet pays back its `amount`.
+///
+/// The payout is debited from the bettor's vault, so it can't be claimed again with `claim_reward`, and is capped
+/// at what the vault still holds. A receipt only pays for stake the bettor still has on the outcome that other
+/// receipts haven't redeemed, so the receipt of a cancelled bet pays nothing. A `ClaimRewardEvent` is emitted
+/// for the bettor.
+///
+/// # Errors:
+///
+/// * If the bucket does not hold exactly one of this market's bet receipts.
+/// * If the market has not been resolved yet, so receipts aren't burned before their bet is settled.
+///
+/// # Returns:
+///
+/// * `Some(Bucket)` containing the bet's payout, or `None` if there is nothing to pay, e.g. for a losing bet.
+///
+/// ---
+///
+/// **Access control:** Public method, requires the bet receipt itself.
+///
+///  **Transaction manifest:**
+/// `transactions/redeem_receipt.rtm`
+    pub fn redeem_receipt(&mut self, bet_receipt: Bucket) -> Option<Bucket> {
+        assert_eq!(
+            bet_receipt.resource_address(),
+            self.bet_receipt_manager.address(),
+            "Bucket does not hold this market's bet receipt."
+        );
+        assert_eq!(bet_receipt.amount(), Decimal::one(), "Exactly one bet receipt must be redeemed at a time.");
+        assert!(self.is_settled(), "Market '{}' has not been resolved yet.", self.title);
+
+        let receipt = bet_receipt
+            .as_non_fungible()
+            .non_fungible::<BetReceiptData>()
+            .data();
+        bet_receipt.burn();
+
+        // Only stake the bettor still has on the outcome, and that no other receipt has redeemed, is paid for.
+        let key = (receipt.user_hash.clone(), receipt.outcome);
+        let redeemed = self.receipt_redeemed_stakes.get(&key).map(|redeemed| *redeemed).unwrap_or(Decimal::zero());
+        let remaining = self.bet_amount(&receipt.user_hash, receipt.outcome as usize) - redeemed;
+        let stake = if receipt.amount < remaining { receipt.amount } else { remaining };
+        self.receipt_redeemed_stakes.insert(key, redeemed + stake);
+
+        let settled_value = match self.status {
+            MarketStatus::Voided => stake,
+            _ => {
+                let base = match self.payout_mode {
+                    PayoutMode::FixedOdds => stake * receipt.odds,
+                    PayoutMode::Parimutuel => stake,
+                };
+                self.round_to_bet_token(base * self.receipt_payout_rates[receipt.outcome as usize])
+            }
+        };
+
+        // Debit the payout from the bettor's vault, so it isn't paid a second time.
+        let payout = {
+            let mut vault = self.user_vaults.get_mut(&receipt.user_hash)?;
+            let available = vault.amount();
+            let amount = if settled_value < available { settled_value } else { available };
+            if amount.is_zero() {
+                return None;
+            }
+            vault.take(amount)
+        };
+
+        Runtime::emit_event(ClaimRewardEvent {
+            market_id: self.title.clone(),
+            sequence: self.next_sequence(),
+            user_hash: receipt.user_hash,
+            reward: payout.amount(),
+            haircut_ratio: self.haircut_ratio,
+        });
+
+        Some(payout)
+    }
+
+/// Redeems outcome shares bought with `buy_shares`, whoever holds them.
+///
+/// The shares are burned and paid their part of the payout set aside for their outcome when the market settled:
+/// - A winning outcome's shares pay their locked-in `stake * odds` in `FixedOdds` mode, or the parimutuel rate,
+///   net of the house fee and with the same dead-heat reduction and haircut as bets.
+/// - A losing outcome's shares pay nothing.
+/// - In a voided market every share pays back its stake, one token per share.
+///
+/// Each redemption pays `shares / shares_outstanding` of what is left for the outcome, rounded down to the bet
+/// token's divisibility, so the last shares redeemed collect any rounding dust. A `SharesRedeemedEvent` is emitted.
+///
+/// # Returns:
+///
+/// A `Bucket` with the payout, empty for a losing outcome.
+///
+/// ---
+///
+/// **Access control:** Public method, requires the shares themselves.
+///
+/// **Errors:** If the market hasn't been resolved or voided yet, or the bucket is empty or doesn't hold one of this
+/// market's share tokens.
+///
+///  **Transaction manifest:**
+/// `transactions/redeem_shares.rtm`
+    pub fn redeem_shares(&mut self, shares: Bucket) -> Bucket {
+        let outcome_position = self.share_managers.iter()
+            .position(|share_manager| share_manager.address() == shares.resource_address())
+            .unwrap_or_else(|| panic!("Bucket does not hold this market's outcome shares."));
+        assert!(!shares.is_empty(), "Share bucket is empty.");
+        assert!(self.is_settled(), "Market '{}' has not been resolved yet.", self.title);
+
+        let amount = shares.amount();
+        let payout = self.round_to_bet_token(
+            amount * self.share_payouts[outcome_position] / self.shares_outstanding[outcome_position]
+        );
+        self.shares_outstanding[outcome_position] -= amount;
+        self.share_payouts[outcome_position] -= payout;
+        shares.burn();
+
+        Runtime::emit_event(SharesRedeemedEvent {
+            market_id: self.title.clone(),
+            sequence: self.next_sequence(),
+            outcome: self.outcomes[outcome_position].clone(),
+            shares: amount,
+            payout,
+        });
+
+        self.share_vault.take(payout)
+    }
+
+/// Allows a user to claim their refunded stake after a market is voided.
+///
+/// Voiding credits every stake back to its bettor's vault. This pays it out like `claim_reward`, but only in a
+/// voided market and with a `RefundClaimedEvent`, so refunds and winnings can be told apart in the event log.
+///
+/// # Parameters:
+/// 
+/// * `claim_badge`: A `Proof` of the claim badge returned by `place_bet`.
+///
+/// # Errors:
+///
+/// * If the proof is not of this market's claim badge resource.
+/// * If the market was not voided.
+///
+/// # Returns:
+///
+/// * `Some(Bucket)` containing the refund, or `None` if there is nothing left to claim.
+///
+/// ---
+///
+/// **Access control:** Public method, requires a proof of the user's claim badge.
+///
+///  **Transaction manifest:**
+/// `transactions/claim_refund.rtm`
+    pub fn claim_refund(&mut self, claim_badge: Proof) -> Option<Bucket> {
+        let user_hash = self.authenticate_user(claim_badge);
+        assert!(self.status == MarketStatus::Voided, "Market '{}' was not voided.", self.title);
+
+        let bucket = self.take_user_vault(&user_hash)?;
+
+        Runtime::emit_event(RefundClaimedEvent {
+            market_id: self.title.clone(),
+            sequence: self.next_sequence(),
+            user_hash,
+            refund: bucket.amount(),
+        });
+
+        Some(bucket)
+    }
+
+        // 4. Getters:
+        
+/// Lists all the outcomes for the market.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/list_outcomes.rtm`
+        pub fn list_outcomes(&self) -> Vec<String> {
+            self.outcomes.clone()
+        }
+
+/// Retrieves the total amount staked in the market.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/get_total_staked.rtm`
+        pub fn get_total_staked(&self) -> Decimal {
+            self.total_staked.clone()
+        }
+
+/// Retrieves the pool contributed by bettors.
+///
+/// Only stakes placed through `place_bet` count towards this figure. Funds seeded into the market by the
+/// admin, such as deposits to the `xrd_vault`, are tracked separately and excluded.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/get_bettor_pool.rtm`
+        pub fn get_bettor_pool(&self) -> Decimal {
+            self.total_staked
+        }
+
+/// Retrieves the details of the market.
+///
+/// Details include the market title, outcomes, odds for each outcome, the total amount staked in the market,
+/// the minimum and maximum bet, whether the market is locked or resolved, the `xrd_vault` balance,
+/// the betting deadline epoch (if any), the payout mode, the configured house fee percentage and the market's status.
+/// `odds` are the odds a new bet is currently priced at, i.e. the configured odds minus any decay as returned by
+/// `get_effective_odds`, and `implied_odds` the odds implied by the pools, as returned by `get_pool_implied_odds`.
+/// `market_locked` follows `status`, so it is also set while a resolution proposal is pending.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_market_details.rtm`
+        pub fn get_market_details(&self) -> MarketDetails {
+            let status = self.get_market_status();
+            MarketDetails {
+                title: self.title.clone(),
+                outcomes: self.outcomes.clone(),
+                odds: (0..self.odds.len()).map(|index| self.effective_odds(index)).collect(),
+                total_staked: self.total_staked,
+                min_bet: self.min_bet,
+                max_bet: self.max_bet,
+                market_locked: status == MarketStatus::Locked,
+                market_resolved: self.is_settled(),
+                xrd_vault_balance: self.xrd_vault.amount(),
+                betting_deadline: self.betting_deadline,
+                payout_mode: self.payout_mode,
+                fee_percent: self.fee_percent,
+                status,
+                description: self.description.clone(),
+                resolution_source: self.resolution_source.clone(),
+                implied_odds: self.get_pool_implied_odds(),
+            }
+        }
+
+/// Retrieves the lifecycle state of the market.
+///
+/// Once resolved, the status carries the winning outcome.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_market_status.rtm`
+        pub fn get_market_status(&self) -> MarketStatus {
+            // An open market with a pending proposal takes no bets, so it is reported as locked.
+            if self.status == MarketStatus::Open && self.pending_resolution.is_some() {
+                MarketStatus::Locked
+            } else {
+                self.status
+            }
+        }
+
+/// Retrieves everything needed to reconstruct the market's full state.
+///
+/// This is intended for an indexer's one-time initial sync and complements the lighter `get_market_details`.
+/// It bundles the market details and status, the current event sequence, per-outcome balances, liabilities and
+/// bets, and every user's claimable and pre-funded balances. To bound the size of the result only the first
+/// `BOOTSTRAP_BETS_PER_OUTCOME` bets of each outcome are included; `bet_counts` tells the indexer which outcomes
+/// must be paged further with `get_bets_for_outcome_paged`. Events with a sequence above `current_sequence`
+/// can then be applied on top.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/bootstrap_state.rtm`
+        pub fn bootstrap_state(&self) -> BootstrapState {
+            let mut users = self.user_hash_page(0, self.user_count as usize);
+            users.sort();
+            let user_vault_balances: Vec<(String, Decimal)> = users.iter()
+                .map(|user| (user.clone(), self.get_claimable_balance(user.clone())))
+                .collect();
+            // Every user with a balance also has a vault, so the balances are found through the vault list.
+            let user_balances: Vec<(String, Decimal)> = users.iter()
+                .filter_map(|user| self.user_balances.get(user).map(|balance| (user.clone(), balance.amount())))
+                .collect();
+
+            BootstrapState {
+                details: self.get_market_details(),
+                status: self.get_market_status(),
+                current_sequence: self.event_sequence,
+                effective_fee_percent: self.get_effective_fee_percent(),
+                outcome_balances: self.outcome_tokens.iter().map(|outcome_vault| outcome_vault.amount()).collect(),
+                outcome_liabilities: self.outcome_liabilities.clone(),
+                bet_counts: self.outcome_bettor_counts.iter().map(|count| *count as usize).collect(),
+                bets: (0..self.outcomes.len())
+                    .map(|index| self.outcome_bets_page(index, 0, BOOTSTRAP_BETS_PER_OUTCOME))
+                    .collect(),
+                user_vault_balances,
+                user_balances,
+            }
+        }
+
+/// Retrieves the odds a new bet on a single outcome is currently priced at.
+///
+/// Same value as `get_effective_odds`: the base odds minus any decay.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_odds_for_outcome.rtm`
+        pub fn get_odds_for_outcome(&self, outcome: String) -> Decimal {
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.effective_odds(outcome_position)
+        }
+
+/// Retrieves the odds of a single outcome after the decay since the market opened.
+///
+/// The base odds drop by `odds_decay_per_epoch` for each epoch since instantiation, up to the betting deadline, and
+/// never fall below 1.01 (or the base odds, if lower). New bets are locked in at these odds. Without decay they
+/// are the base odds.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_effective_odds.rtm`
+        pub fn get_effective_odds(&self, outcome: String) -> Decimal {
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.effective_odds(outcome_position)
+        }
+
+/// Retrieves the probability implied by each outcome's odds, i.e. `1 / odds`.
+///
+/// The odds used are the effective odds, as returned by `get_effective_odds`. The raw probabilities usually sum to
+/// more than 1, the bookmaker's margin or overround; with `normalize` they are scaled to sum to 1.
+///
+/// # Parameters:
+/// 
+/// * `normalize`: Whether to scale the probabilities so they sum to 1.
+///
+/// # Returns:
+///
+/// * A vector of `(outcome, probability)` pairs, in the order the outcomes were defined.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_implied_probabilities.rtm`
+        pub fn get_implied_probabilities(&self, normalize: bool) -> Vec<(String, Decimal)> {
+            let probabilities: Vec<Decimal> = (0..self.outcomes.len())
+                .map(|position| {
+                    let odds = self.effective_odds(position);
+                    // Odds are validated to be above 1 and decay never takes them below that, so this can't divide by zero.
+                    assert!(odds > Decimal::one(), "Odds of outcome '{}' must be greater than 1.", self.outcomes[position]);
+                    Decimal::one() / odds
+                })
+                .collect();
+            let total: Decimal = probabilities.iter().fold(Decimal::zero(), |sum, probability| sum + *probability);
+
+            self.outcomes.iter().cloned()
+                .zip(probabilities)
+                .map(|(outcome, probability)| (outcome, if normalize { probability / total } else { probability }))
+                .collect()
+        }
+
+/// Retrieves how a single outcome will be judged.
+///
+/// Returns an empty string if the admin hasn't set a resolution source for the outcome.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_resolution_source.rtm`
+        pub fn get_resolution_source(&self, outcome: String) -> String {
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.outcome_metadata.get(&self.outcomes[outcome_position])
+                .map(|metadata| metadata.resolution_source.clone())
+                .unwrap_or_default()
+        }
+
+/// Retrieves the payout multiple each outcome would currently pay in a parimutuel market.
+///
+/// For each outcome this is `(total_pool - fee) / outcome_pool`, i.e. the "effective odds" a winner would get if
+/// the market was resolved now. Outcomes nobody has bet on yet return zero. The values are aligned with `list_outcomes`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_parimutuel_odds.rtm`
+        pub fn get_parimutuel_odds(&self) -> Vec<Decimal> {
+            let distributable = self.net_of_fee(self.total_staked);
+            self.per_outcome_staked.iter()
+                .map(|outcome_pool| {
+                    if *outcome_pool > Decimal::zero() {
+                        distributable / *outcome_pool
+                    } else {
+                        Decimal::zero()
+                    }
+                })
+                .collect()
+        }
+
+/// Retrieves the odds implied by the current pool of each outcome.
+///
+/// For each outcome this is `total_pool / outcome_pool`, using the balances of the outcome vaults, so seeded liquidity
+/// counts and no house fee is taken off. An outcome with an empty pool falls back to its configured odds.
+/// The values are aligned with `list_outcomes`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_pool_implied_odds.rtm`
+        pub fn get_pool_implied_odds(&self) -> Vec<Decimal> {
+            let total_pool = self.outcome_tokens.iter()
+                .fold(Decimal::zero(), |total, vault| total + vault.amount());
+            self.outcome_tokens.iter().enumerate()
+                .map(|(index, vault)| {
+                    let outcome_pool = vault.amount();
+                    if outcome_pool > Decimal::zero() {
+                        total_pool / outcome_pool
+                    } else {
+                        self.effective_odds(index)
+                    }
+                })
+                .collect()
+        }
+
+/// Previews what a bet would pay out if its outcome won, net of the house fee.
+///
+/// In `FixedOdds` mode this is `amount * odds - fee`. In `Parimutuel` mode it is an estimate: the bet's share of the
+/// pool as it would be right after the bet, i.e. `amount / (outcome_pool + amount) * (total_pool + amount - fee)`.
+/// Later bets change the final parimutuel payout.
+///
+/// # Parameters:
+/// 
+/// * `outcome`: The outcome to bet on.
+/// * `amount`: The amount to bet. Must be within `min_bet` and `max_bet`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the outcome doesn't exist in the market, or if `amount` is outside `min_bet` and `max_bet`.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_potential_payout.rtm`
+        pub fn get_potential_payout(&self, outcome: String, amount: Decimal) -> Decimal {
+            let outcome_position = self.get_outcome_position(&outcome);
+            assert!(amount >= self.min_bet, "{:?}", MarketError::BetBelowMinimum { amount, minimum: self.min_bet });
+            assert!(amount <= self.max_bet, "{:?}", MarketError::BetAboveMaximum { amount, maximum: self.max_bet });
+
+            match self.payout_mode {
+                PayoutMode::FixedOdds => self.net_of_fee(amount * self.effective_odds(outcome_position)),
+                PayoutMode::Parimutuel => {
+                    let outcome_pool = self.per_outcome_staked[outcome_position] + amount;
+                    amount / outcome_pool * self.net_of_fee(self.total_staked + amount)
+                }
+            }
+        }
+
+/// Previews the rewards `resolve_market` would pay if `winning_outcome` won, without moving any funds.
+///
+/// The rewards are computed exactly as on resolution, net of the house fee and rounded to the bet token's
+/// divisibility, and are listed in the same order: the order the winning bets were placed.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Errors:** If the market was already resolved or voided, or if the winning outcome is out of bounds.
+///
+/// **Transaction manifest:**
+/// `transactions/preview_resolution.rtm`
+        pub fn preview_resolution(&self, winning_outcome: u32) -> Vec<(String, Decimal)> {
+            self.ensure_market_not_resolved();
+            self.ensure_outcome_in_bounds(winning_outcome);
+
+            let (rewards, _) = self.calculate_rewards(&[winning_outcome as usize]);
+            rewards
+        }
+
+/// Previews the refunds voiding the market would credit, without moving any funds.
+///
+/// Returns one `(user_hash, refund)` entry per bet, in outcome order and then in the order the bets were placed,
+/// the same order `resolve_market_as_void` refunds them in.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+///
+/// **Errors:** If the market was already resolved or voided.
+///
+/// **Transaction manifest:**
+/// `transactions/preview_void.rtm`
+        pub fn preview_void(&self) -> Vec<(String, Decimal)> {
+            self.ensure_market_not_resolved();
+
+            (0..self.outcomes.len())
+                .flat_map(|index| self.outcome_bets_page(index, 0, usize::MAX))
+                .collect()
+        }
+
+/// Retrieves the data needed to draw a depth chart of the market.
+///
+/// Returns one `(outcome, pool, implied_probability, potential_payout)` entry per outcome, in outcome order:
+/// - `pool`: The amount currently staked on the outcome.
+/// - `implied_probability`: `1 / odds` in `FixedOdds` mode, or the outcome's share of the total pool in
+///   `Parimutuel` mode (zero while nothing is staked).
+/// - `potential_payout`: The total paid out to the outcome's bettors if it wins, i.e. `pool * odds` minus the
+///   house fee in `FixedOdds` mode, or the whole pool minus the house fee in `Parimutuel` mode.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_depth_data.rtm`
+        pub fn get_depth_data(&self) -> Vec<(String, Decimal, Decimal, Decimal)> {
+            let distributable = self.net_of_fee(self.total_staked);
+            self.outcomes.iter().enumerate()
+                .map(|(index, outcome)| {
+                    let pool = self.per_outcome_staked[index];
+                    let (implied_probability, potential_payout) = match self.payout_mode {
+                        PayoutMode::FixedOdds => {
+                            let odds = self.effective_odds(index);
+                            (Decimal::one() / odds, self.net_of_fee(pool * odds))
+                        }
+                        PayoutMode::Parimutuel => {
+                            if pool > Decimal::zero() {
+                                (pool / self.total_staked, distributable)
+                            } else {
+                                (Decimal::zero(), Decimal::zero())
+                            }
+                        }
+                    };
+                    (outcome.clone(), pool, implied_probability, potential_payout)
+                })
+                .collect()
+        }
+
+/// Retrieves a user's pre-funded betting balance.
+///
+/// Returns zero if the user hasn't deposited a balance. Winnings are not included; read those with
+/// `get_claimable_balance`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_user_balance.rtm`
+        pub fn get_user_balance(&self, user_hash: String) -> Decimal {
+            self.user_balances.get(&user_hash)
+                .map(|balance| balance.amount())
+                .unwrap_or(Decimal::zero())
+        }
+
+/// Retrieves the amount a user can currently claim with `claim_reward`, so they can check it before paying for a claim.
+///
+/// This covers winnings and refunds credited to the user's vault, e.g. after resolution or a cancelled bet. It is
+/// zero until the market is resolved or voided, and for users who lost or never bet. Reading it doesn't create a
+/// vault for unknown users.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_claimable_balance.rtm`
+        pub fn get_claimable_balance(&self, user_hash: String) -> Decimal {
+            self.user_vaults.get(&user_hash)
+                .map(|user_vault| user_vault.amount())
+                .unwrap_or(Decimal::zero())
+        }
+
+/// Retrieves the amount a user can currently claim with `claim_reward`.
+///
+/// **Deprecated:** same value as `get_claimable_balance`, which should be used instead. This alias will be removed
+/// in the next release.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_user_vault_balance.rtm`
+        pub fn get_user_vault_balance(&self, user_hash: String) -> Decimal {
+            self.get_claimable_balance(user_hash)
+        }
+
+/// Retrieves the reward or refund a user has waiting to be claimed.
+///
+/// **Deprecated:** same value as `get_claimable_balance`, which should be used instead. This alias will be removed
+/// in the next release.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_claimable_amount.rtm`
+        pub fn get_claimable_amount(&self, user_hash: String) -> Decimal {
+            self.get_claimable_balance(user_hash)
+        }
+
+/// Retrieves how much an outcome's odds have moved since a reference epoch.
+///
+/// Returns the current odds minus the odds that were in effect at the end of `since_epoch`. A positive value
+/// means the odds lengthened, a negative value that they shortened. Epochs before the market was created
+/// compare against the opening odds.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_odds_delta.rtm`
+        pub fn get_odds_delta(&self, outcome: String, since_epoch: i64) -> Decimal {
+            let outcome_position = self.get_outcome_position(&outcome);
+            let history = &self.odds_history[outcome_position];
+
+            // Find the last odds set at or before the reference epoch, falling back to the opening odds.
+            let odds_at_reference = history.iter()
+                .take_while(|(epoch, _)| (epoch.number() as i64) <= since_epoch)
+                .last()
+                .unwrap_or(&history[0])
+                .1;
+
+            self.odds[outcome_position] - odds_at_reference
+        }
+
+/// Retrieves the last epoch in which bets are accepted.
+///
+/// Returns `None` if the market has no deadline and only closes when locked by the admin.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_betting_deadline.rtm`
+        pub fn get_betting_deadline(&self) -> Option<Epoch> {
+            self.betting_deadline
+        }
+
+/// Retrieves the pending resolution proposal, if any.
+///
+/// Returns the proposed winning outcome and the epoch it was proposed in. It can be finalized
+/// `dispute_window_epochs` after that epoch.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_pending_resolution.rtm`
+        pub fn get_pending_resolution(&self) -> Option<(u32, Epoch)> {
+            self.pending_resolution
+        }
+
+/// Retrieves the house fee percentage the market would currently be charged at resolution.
+///
+/// This is the configured `fee_percent` reduced according to the highest `FEE_TIERS` tier the market's
+/// lifetime volume has reached.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_effective_fee_percent.rtm`
+        pub fn get_effective_fee_percent(&self) -> Decimal {
+            let fee_charged_percent = FEE_TIERS.iter()
+                .rev()
+                .find(|(min_volume, _)| self.lifetime_staked >= Decimal::from(*min_volume))
+                .map(|(_, fee_charged_percent)| *fee_charged_percent)
+                .unwrap_or(100);
+
+            self.fee_percent * Decimal::from(fee_charged_percent) / Decimal::from(100)
+        }
+
+/// Retrieves the total payout owed to an outcome's bettors if it wins.
+///
+/// This is the sum of `bet_amount * odds` over the outcome's bets, each at the odds it was placed at. It only reflects
+/// treasury exposure in `FixedOdds` mode; parimutuel payouts are funded by the pool itself.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_outcome_liability.rtm`
+        pub fn get_outcome_liability(&self, outcome: String) -> Decimal {
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.outcome_liabilities[outcome_position]
+        }
+
+/// Retrieves the worst-case payout, i.e. the largest liability across all outcomes.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_max_liability.rtm`
+        pub fn get_max_liability(&self) -> Decimal {
+            self.outcome_liabilities.iter()
+                .fold(Decimal::zero(), |max, liability| if *liability > max { *liability } else { max })
+        }
+
+/// Retrieves how much of the treasury `xrd_vault` the super-admin can currently withdraw.
+///
+/// While a `FixedOdds` market is unresolved, the treasury must keep enough to pay the winners of any single
+/// outcome: the outcome's liability minus the losing stakes that join the treasury on resolution. Parimutuel
+/// markets are funded by their own pool, and once a market is resolved every reward has already been moved to
+/// the users' vaults, so in both cases the full balance is withdrawable. While LP tokens are in circulation, the
+/// amount is further capped at the house's share of the treasury; the rest belongs to the liquidity providers.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_withdrawable_amount.rtm`
+        pub fn get_withdrawable_amount(&self) -> Decimal {
+            let mut withdrawable = self.xrd_vault.amount() - self.required_reserve();
+            if self.lp_token_supply > Decimal::zero() {
+                let house_share = self.round_to_bet_token(self.xrd_vault.amount() * self.house_lp_units / self.lp_units_total());
+                if house_share < withdrawable {
+                    withdrawable = house_share;
+                }
+            }
+            if withdrawable > Decimal::zero() { withdrawable } else { Decimal::zero() }
+        }
+
+/// Fetches the balance associated with a particular market outcome.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_outcome_balance.rtm`
+        pub fn get_outcome_balance(&self, outcome: String) -> Decimal {
+            let index = self.get_outcome_position(&outcome);
+            self.outcome_tokens[index].amount()
+        }
+
+/// Retrieves the amount staked on an outcome, as tracked alongside `total_staked`.
+///
+/// While the market is open this matches `get_outcome_balance`, less any liquidity seeded with `seed_outcome_liquidity`,
+/// but it is read from the component state instead of the outcome's vault. It drops to zero once the market is resolved or voided, like `total_staked`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_outcome_staked.rtm`
+        pub fn get_outcome_staked(&self, outcome: String) -> Decimal {
+            let index = self.get_outcome_position(&outcome);
+            self.per_outcome_staked[index]
+        }
+
+/// Fetches the balance of every outcome at once, saving front-ends a `get_outcome_balance` call per outcome.
+///
+/// Returns one `(outcome, balance)` pair per outcome, in outcome order. While the market is open the balances sum to
+/// `get_total_staked` plus any seeded liquidity, so front-ends can derive each outcome's share of the market from a
+/// single call.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_all_outcome_balances.rtm`
+        pub fn get_all_outcome_balances(&self) -> Vec<(String, Decimal)> {
+            self.outcomes.iter()
+                .zip(&self.outcome_tokens)
+                .map(|(outcome, outcome_vault)| (outcome.clone(), outcome_vault.amount()))
+                .collect()
+        }
+
+/// Fetches the breakdown of `get_total_staked` per outcome.
+///
+/// Returns the same `(outcome, balance)` pairs as `get_all_outcome_balances`, under the name front-ends look for
+/// when charting how the stakes are spread across the outcomes.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_staked_breakdown.rtm`
+        pub fn get_staked_breakdown(&self) -> Vec<(String, Decimal)> {
+            self.get_all_outcome_balances()
+        }
+
+/// Fetches the balance and the number of bettors of every outcome.
+///
+/// Returns one `(outcome, balance, bettors)` triple per outcome, in outcome order. A user who topped up their bet
+/// on an outcome is counted once.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_outcome_stakes.rtm`
+        pub fn get_outcome_stakes(&self) -> Vec<(String, Decimal, u32)> {
+            self.outcomes.iter()
+                .zip(&self.outcome_tokens)
+                .zip(&self.outcome_bettor_counts)
+                .map(|((outcome, outcome_vault), bettor_count)| {
+                    // Each user is listed at most once per outcome, as top-ups are merged into their bet.
+                    (outcome.clone(), outcome_vault.amount(), *bettor_count as u32)
+                })
+                .collect()
+        }
+
+/// Counts the distinct users with a bet in the market.
+///
+/// A user betting on several outcomes is counted once. Cancelled bets no longer count, and the count stays as it was
+/// when the market is resolved or voided.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_unique_bettor_count.rtm`
+        pub fn get_unique_bettor_count(&self) -> usize {
+            // The bet store can't be iterated, so the bettors are collected from the per-outcome lists.
+            let bettors: HashSet<String> = (0..self.outcomes.len())
+                .flat_map(|index| self.outcome_bettor_page(index, 0, usize::MAX))
+                .collect();
+            bettors.len()
+        }
+
+/// Retrieves how much more can be staked across the market before it reaches `max_total_staked`.
+///
+/// Returns `None` if the market has no `max_total_staked`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_remaining_capacity.rtm`
+        pub fn get_remaining_capacity(&self) -> Option<Decimal> {
+            self.max_total_staked.map(|max_total_staked| max_total_staked - self.total_staked)
+        }
+
+/// Retrieves how much more can be staked on an outcome before it reaches the exposure cap.
+///
+/// Returns `None` if the market has no `max_outcome_exposure`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_outcome_remaining_capacity.rtm`
+        pub fn get_outcome_remaining_capacity(&self, outcome: String) -> Option<Decimal> {
+            let outcome_position = self.get_outcome_position(&outcome);
+            self.max_outcome_exposure
+                .map(|max_outcome_exposure| max_outcome_exposure - self.per_outcome_staked[outcome_position])
+        }
+
+/// Retrieves a user's combined stake across all outcomes of the market.
+///
+/// This is what counts towards `max_user_total`. Returns zero if the user hasn't placed any bets.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_user_total_staked.rtm`
+        pub fn get_user_total_staked(&self, user_hash: String) -> Decimal {
+            self.user_total_staked(&user_hash)
+        }
+
+/// Retrieves a user's positions across all outcomes of the market.
+///
+/// Returns `(outcome, amount)` pairs in outcome order for every outcome the user has a stake on,
+/// or an empty vector if the user hasn't placed any bets.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_user_bets.rtm`
+        pub fn get_user_bets(&self, user_hash: String) -> Vec<(String, Decimal)> {
+            let user_bets = match self.bets.get(&user_hash) {
+                Some(user_bets) => user_bets.amounts.clone(),
+                None => return Vec::new(),
+            };
+            self.outcomes.iter()
+                .zip(user_bets)
+                .filter(|(_, amount)| !amount.is_zero())
+                .map(|(outcome, amount)| (outcome.clone(), amount))
+                .collect()
+        }
+
+/// Retrieves a user's positions together with the odds they are locked in at.
+///
+/// Returns `(outcome, amount, odds)` triples like `get_user_bets`. Each bet is paid at the odds it was placed at, so
+/// after a top-up at different odds `odds` is the stake-weighted average, i.e. the locked-in payout divided by
+/// `amount`.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_user_bets_with_odds.rtm`
+        pub fn get_user_bets_with_odds(&self, user_hash: String) -> Vec<(String, Decimal, Decimal)> {
+            let user_bets = match self.bets.get(&user_hash) {
+                Some(user_bets) => user_bets.clone(),
+                None => return Vec::new(),
+            };
+            self.outcomes.iter()
+                .zip(user_bets.amounts.iter().zip(&user_bets.locked_payouts))
+                .filter(|(_, (amount, _))| !amount.is_zero())
+                .map(|(outcome, (amount, locked_payout))| (outcome.clone(), *amount, *locked_payout / *amount))
+                .collect()
+        }
+
+/// Retrieves a page of the bets placed on an outcome.
+///
+/// Returns the `(user_hash, amount)` bets from position `offset` up to `offset + limit` (clamped to the number of
+/// bets), together with the total number of bets on the outcome so callers know when to stop paging.
+/// The page is empty if `offset` is past the end. Bets are listed in the order they were placed, except that the
+/// outcome's latest bet moves into the position of a cancelled one.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Errors:** If the provided outcome doesn't exist in the market.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_bets_for_outcome_paged.rtm`
+        pub fn get_bets_for_outcome_paged(&self, outcome: String, offset: usize, limit: usize) -> (Vec<(String, Decimal)>, usize) {
+            let outcome_position = self.get_outcome_position(&outcome);
+            (
+                self.outcome_bets_page(outcome_position, offset, limit),
+                self.outcome_bettor_counts[outcome_position] as usize,
+            )
+        }
+
+/// Retrieves the full bet ledger of the market.
+///
+/// Returns one `(outcome, user_hash, amount)` entry per bet, ordered by outcome and then as in
+/// `get_bets_for_outcome_paged`. The result grows with every bettor, so large markets should be read with `get_bets_for_outcome_paged`
+/// instead to stay within transaction limits.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_all_bets.rtm`
+        pub fn get_all_bets(&self) -> Vec<(String, String, Decimal)> {
+            self.outcomes.iter()
+                .enumerate()
+                .flat_map(|(index, outcome)| {
+                    self.outcome_bets_page(index, 0, usize::MAX).into_iter()
+                        .map(move |(user, amount)| (outcome.clone(), user, amount))
+                })
+                .collect()
+        }
+
+/// Retrieves the sequence number of the last event emitted by the market.
+///
+/// Indexers can compare this with the `sequence` of the last event they processed to detect gaps.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/get_current_sequence.rtm`
+        pub fn get_current_sequence(&self) -> u64 {
+            self.event_sequence
+        }
+
+/// Computes the Kelly-optimal stake for a bet at the given decimal odds.
+///
+/// Uses the Kelly criterion `f = (p * odds - 1) / (odds - 1)`, where `p` is the bettor's own estimate of the
+/// outcome's probability, and returns `bankroll * f`. Returns zero when the bet has no positive edge.
+///
+/// # Parameters:
+/// 
+/// * `bankroll`: The amount the bettor is willing to risk across all bets.
+/// * `outcome_odds`: The decimal odds of the outcome, as returned by `get_market_details`. Must be greater than 1.
+/// * `est_probability`: The bettor's estimated probability of the outcome, between 0 and 1.
+///
+/// ---
+///
+/// **Access control:** Public function, can be called by anyone.
+/// 
+/// **Transaction manifest:**
+/// `transactions/kelly_stake.rtm`
+        pub fn kelly_stake(bankroll: Decimal, outcome_odds: Decimal, est_probability: Decimal) -> Decimal {
+            assert!(bankroll >= Decimal::zero(), "Bankroll cannot be negative. Provided: {}", bankroll);
+            assert!(outcome_odds > Decimal::one(), "Odds must be greater than 1. Provided: {}", outcome_odds);
+            assert!(
+                est_probability >= Decimal::zero() && est_probability <= Decimal::one(),
+                "Probability must be between 0 and 1. Provided: {}",
+                est_probability
+            );
+
+            let kelly_fraction = (est_probability * outcome_odds - Decimal::one()) / (outcome_odds - Decimal::one());
+            if kelly_fraction > Decimal::zero() {
+                bankroll * kelly_fraction
+            } else {
+                Decimal::zero()
+            }
+        }
+
+        // 5. Helpers:
+        
+        fn ensure_market_not_resolved(&self) {
+            assert!(!self.is_settled(), "{:?}", MarketError::MarketResolved);
+        }
+
+        // A pending resolution proposal freezes the market like a lock: its outcome is public, so bets, cancellations,
+        // odds changes and liquidity must wait until it is finalized or cancelled.
+        fn ensure_market_not_locked(&self) {
+            assert!(self.status != MarketStatus::Locked, "{:?}", MarketError::MarketLocked);
+            assert!(self.pending_resolution.is_none(), "{:?}", MarketError::ResolutionPending);
+        }
+
+        // In strict resolution mode, reject winning outcomes nobody bet on.
+        fn ensure_winning_bets(&self, winning_outcomes: &[u32]) -> Result<(), MarketError> {
+            if !self.strict_resolution {
+                return Ok(());
+            }
+            match winning_outcomes.iter().find(|outcome| self.per_outcome_staked[**outcome as usize].is_zero()) {
+                Some(outcome) => Err(MarketError::WinningOutcomeHasNoBets { outcome: *outcome }),
+                None => Ok(()),
+            }
+        }
+
+        fn is_settled(&self) -> bool {
+            matches!(self.status, MarketStatus::Resolved { .. } | MarketStatus::Voided)
+        }
+
+        fn ensure_outcome_in_bounds(&self, outcome: u32) {
+            assert!((outcome as usize) < self.outcome_tokens.len(), "{:?}", MarketError::OutcomeOutOfBounds { outcome });
+        }
+
+        // Odds of an outcome after the decay since the market opened. The decay stops at the betting deadline and never
+        // takes the odds below 1.01, or below the base odds if they are lower still.
+        fn effective_odds(&self, outcome_position: usize) -> Decimal {
+            let base_odds = self.odds[outcome_position];
+            if self.odds_decay_per_epoch.is_zero() {
+                return base_odds;
+            }
+
+            let decay_until = match self.betting_deadline {
+                Some(betting_deadline) if betting_deadline < Runtime::current_epoch() => betting_deadline,
+                _ => Runtime::current_epoch(),
+            };
+            let epochs_elapsed = decay_until.number().saturating_sub(self.market_open_epoch.number());
+            let decayed_odds = base_odds - self.odds_decay_per_epoch * Decimal::from(epochs_elapsed);
+            let floor = dec!("1.01");
+            if decayed_odds > floor {
+                decayed_odds
+            } else if base_odds < floor {
+                base_odds
+            } else {
+                floor
+            }
+        }
+
+        fn validate_market_metadata(description: &str, resolution_source: &str) {
+            assert!(
+                description.chars().count() <= MAX_METADATA_LENGTH,
+                "Description must be at most {} characters long.",
+                MAX_METADATA_LENGTH
+            );
+            assert!(
+                resolution_source.chars().count() <= MAX_METADATA_LENGTH,
+                "Resolution source must be at most {} characters long.",
+                MAX_METADATA_LENGTH
+            );
+        }
+
+        fn validate_bet_limits(min_bet: Decimal, max_bet: Decimal, max_outcome_exposure: Option<Decimal>) {
+            assert!(
+                min_bet >= Decimal::from(5),
+                "Minimum bet must be atleast 5. Provided: {}",
+                min_bet
+            );
+
+            assert!(
+                max_bet > min_bet,
+                "Maximum bet must be greater than the minimum bet. Provided: Max bet: {}, Min bet: {}",
+                max_bet, min_bet
+            );
+
+            if let Some(max_outcome_exposure) = max_outcome_exposure {
+                assert!(
+                    max_outcome_exposure >= min_bet,
+                    "Maximum outcome exposure must be at least the minimum bet. Provided: {}",
+                    max_outcome_exposure
+                );
+            }
+        }
+
+        fn validate_odds(odds: Decimal) {
+            assert!(odds > Decimal::one(), "Odds must be greater than 1. Provided: {}", odds);
+            assert!(
+                odds <= Decimal::from(MAX_ODDS),
+                "Odds must be at most {}. Provided: {}",
+                MAX_ODDS, odds
+            );
+        }
+
+        fn ensure_no_dispute_window(&self) {
+            assert!(
+                self.dispute_window_epochs == 0,
+                "{:?}",
+                MarketError::DisputeWindowRequired { dispute_window_epochs: self.dispute_window_epochs }
+            );
+        }
+
+        // Resolve the market to a single winning outcome after the checks every resolution path shares, strict mode
+        // included. Nothing is changed when the check fails.
+        fn resolve_validated(&mut self, winning_outcome: u32, pro_rata: bool) -> Result<ResolutionReport, MarketError> {
+            self.ensure_market_not_resolved();
+            self.ensure_outcome_in_bounds(winning_outcome);
+            self.ensure_winning_bets(&[winning_outcome])?;
+
+            Ok(self.resolve_to_outcome(winning_outcome, pro_rata))
+        }
+
+        // Resolve the market to a single winning outcome, pay the winners and emit a `MarketResolvedEvent`.
+        // With `pro_rata`, the payouts are cut to what the market holds instead of failing.
+        // Returns a report of the resolution, including the rewards paid.
+        fn resolve_to_outcome(&mut self, winning_outcome: u32, pro_rata: bool) -> ResolutionReport {
+            // Check that the market is unresolved and the winning outcome is valid.
+            self.ensure_market_not_resolved();
+            self.ensure_outcome_in_bounds(winning_outcome);
+
+            // Settling resets the total staked, so the pool is read first.
+            let total_pool = self.total_staked;
+            let (rewards, house_fee, haircut_ratio) = self.settle_market(&[winning_outcome as usize], pro_rata);
+            let total_paid = rewards.iter().fold(Decimal::zero(), |total, (_, reward)| total + *reward)
+                + self.share_payouts.iter().fold(Decimal::zero(), |total, payout| total + *payout);
+
+            // Emit that the market has been resolved.
+            Runtime::emit_event(MarketResolvedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                winning_outcome,
+                house_fee,
+                haircut_ratio,
+            });
+
+            ResolutionReport {
+                winning_outcome,
+                total_pool,
+                total_paid,
+                house_fee,
+                house_net: total_pool - total_paid,
+                winning_bettors: rewards.len() as u64,
+                haircut_ratio,
+                rewards,
+            }
+        }
+
+        fn ensure_user_vault_exists(&mut self, user_hash: String) -> Option<Bucket> {
+            // Check if a vault exists for the user, if not, create a new one.
+            if self.user_vaults.get(&user_hash).is_some() {
+                return None;
+            }
+            self.user_vaults.insert(user_hash.clone(), Vault::new(self.bet_token));
+            self.user_hashes.insert(self.user_count, user_hash.clone());
+            self.user_count += 1;
+
+            // Mint the claim badge that authorizes the user's future claims.
+            self.claim_badges_issued += 1;
+            Some(self.claim_badge_manager.mint_non_fungible(
+                &NonFungibleLocalId::integer(self.claim_badges_issued),
+                ClaimBadgeData { user_hash },
+            ))
+        }
+
+        fn ensure_admin_vault_exists(&mut self, admin_hash: String){
+            // Check if a vault exists for the admin, if not, create a new one.
+            if !self.admin_vaults.contains_key(&admin_hash) {
+                self.admin_vaults.insert(admin_hash.clone(), Vault::new(self.bet_token));
+            }
+        }
+
+        fn authenticate_user(&self, claim_badge: Proof) -> String {
+            // Check the proof is of our claim badge and read the user it belongs to.
+            let checked_badge = claim_badge.check(self.claim_badge_manager.address());
+            checked_badge
+                .as_non_fungible()
+                .non_fungible::<ClaimBadgeData>()
+                .data()
+                .user_hash
+        }
+
+        fn ensure_bet_token(&self, bucket: &Bucket) {
+            // Assert the bucket holds the token this market is denominated in.
+            assert_eq!(
+                bucket.resource_address(),
+                self.bet_token,
+                "{:?}",
+                MarketError::WrongBetToken { expected: self.bet_token, provided: bucket.resource_address() }
+            );
+        }
+
+        // Validate the bet using assertions.
+        fn validate_bet(&self, payment: &Bucket) {
+            // Assert the market is not locked, paused or awaiting a proposed resolution.
+            self.ensure_market_not_locked();
+            assert!(!self.market_paused, "{:?}", MarketError::MarketPaused);
+
+            // Assert the betting deadline, if any, hasn't passed.
+            if let Some(deadline) = self.betting_deadline {
+                assert!(Runtime::current_epoch() <= deadline, "{:?}", MarketError::BettingClosed { deadline });
+            }
+        
+        let bet_amount = payment.amount();
+        
+        // `min_bet` is checked in `stake_bet`, as it only applies to a user's first bet on an outcome.
+        assert!(
+                bet_amount > Decimal::from(0),
+                "{:?}",
+                MarketError::BetBelowMinimum { amount: bet_amount, minimum: self.min_bet }
+            );
+        }
+
+        // Get outcome position using assertion. Outcomes are unique regardless of case, so at most one can match.
+        fn get_outcome_position(&self, outcome: &String) -> usize {
+            self.outcomes.iter()
+            .position(|o| if self.case_insensitive_outcomes { o.to_lowercase() == outcome.to_lowercase() } else { o == outcome })
+            .unwrap_or_else(|| panic!("{:?}", MarketError::UnknownOutcome { outcome: outcome.clone() }))
+        } 
+
+        // Get a user's stake on an outcome, zero if they have no bet on it.
+        fn bet_amount(&self, user_hash: &String, outcome_position: usize) -> Decimal {
+            self.bets.get(user_hash)
+                .and_then(|user_bets| user_bets.amounts.get(outcome_position).copied())
+                .unwrap_or(Decimal::zero())
+        }
+
+        // Get a user's stake across all outcomes. A user's bets live in one store entry, so only that entry is read.
+        fn user_total_staked(&self, user_hash: &String) -> Decimal {
+            self.bets.get(user_hash)
+                .map(|user_bets| user_bets.amounts.iter().fold(Decimal::zero(), |total, amount| total + *amount))
+                .unwrap_or(Decimal::zero())
+        }
+
+        // Get a user's locked-in gross payout on an outcome, zero if they have no bet on it.
+        fn locked_payout(&self, user_hash: &String, outcome_position: usize) -> Decimal {
+            self.bets.get(user_hash)
+                .and_then(|user_bets| user_bets.locked_payouts.get(outcome_position).copied())
+                .unwrap_or(Decimal::zero())
+        }
+
+        // Remove a user's bet on an outcome, returning its amount and locked-in payout, or `None` if they have no bet on it.
+        // The outcome's latest bettor takes over the removed entry's index, so the list stays contiguous.
+        fn remove_bet(&mut self, user_hash: &String, outcome_position: usize) -> Option<(Decimal, Decimal)> {
+            let outcome = outcome_position as u32;
+            let index = self.outcome_bettor_indices.remove(&(user_hash.clone(), outcome))?;
+            let last_index = self.outcome_bettor_counts[outcome_position] - 1;
+            let last_user = self.outcome_bettors.remove(&(outcome, last_index)).unwrap();
+            if index != last_index {
+                self.outcome_bettors.insert((outcome, index), last_user.clone());
+                self.outcome_bettor_indices.insert((last_user, outcome), index);
+            }
+            self.outcome_bettor_counts[outcome_position] = last_index;
+
+            let mut user_bets = self.bets.get_mut(user_hash).unwrap();
+            let bet_amount = user_bets.amounts[outcome_position];
+            let locked_payout = user_bets.locked_payouts[outcome_position];
+            user_bets.amounts[outcome_position] = Decimal::zero();
+            user_bets.locked_payouts[outcome_position] = Decimal::zero();
+            Some((bet_amount, locked_payout))
+        }
+
+        // Get the `(user_hash, amount)` bets on an outcome from position `offset` up to `offset + limit`.
+        fn outcome_bets_page(&self, outcome_position: usize, offset: usize, limit: usize) -> Vec<(String, Decimal)> {
+            self.outcome_bettor_page(outcome_position, offset, limit).into_iter()
+                .map(|user| {
+                    let amount = self.bet_amount(&user, outcome_position);
+                    (user, amount)
+                })
+                .collect()
+        }
+
+        // Get the users with a stake on an outcome from position `offset` up to `offset + limit`.
+        fn outcome_bettor_page(&self, outcome_position: usize, offset: usize, limit: usize) -> Vec<String> {
+            let outcome = outcome_position as u32;
+            let end = (offset.saturating_add(limit) as u64).min(self.outcome_bettor_counts[outcome_position]);
+            (offset as u64..end)
+                .map(|index| self.outcome_bettors.get(&(outcome, index)).unwrap().clone())
+                .collect()
+        }
+
+        // Add a user to the list of bettors on an outcome.
+        fn add_outcome_bettor(&mut self, user_hash: String, outcome_position: usize) {
+            let outcome = outcome_position as u32;
+            let index = self.outcome_bettor_counts[outcome_position];
+            self.outcome_bettors.insert((outcome, index), user_hash.clone());
+            self.outcome_bettor_indices.insert((user_hash, outcome), index);
+            self.outcome_bettor_counts[outcome_position] += 1;
+        }
+
+        // Get the users with a vault from position `offset` up to `offset + limit`, in the order the vaults were created.
+        fn user_hash_page(&self, offset: usize, limit: usize) -> Vec<String> {
+            let end = (offset.saturating_add(limit) as u64).min(self.user_count);
+            (offset as u64..end)
+                .map(|index| self.user_hashes.get(&index).unwrap().clone())
+                .collect()
+        }
+
+        fn reset_and_resolve_market(&mut self, status: MarketStatus) {
+        self.total_staked = Decimal::from(0);
+        self.per_outcome_staked = vec![Decimal::zero(); self.outcomes.len()];
+        self.outcome_liabilities = vec![Decimal::zero(); self.outcomes.len()];
+        self.seeded_liquidity.clear();
+        self.status = status;
+        self.resolved_at = Some(Runtime::current_epoch());
+        }
+
+        // In a fixed-odds market the losing pools and any seeded liquidity join the treasury before the winners are paid,
+        // so the treasury only has to cover each outcome's liability minus everything in the vaults but its own stakes.
+        fn required_reserve(&self) -> Decimal {
+            if self.is_settled() || self.payout_mode != PayoutMode::FixedOdds {
+                return Decimal::zero();
+            }
+
+            let pooled = self.outcome_tokens.iter()
+                .fold(Decimal::zero(), |total, outcome_vault| total + outcome_vault.amount());
+            self.outcome_liabilities.iter().enumerate()
+                .map(|(index, liability)| *liability - (pooled - self.per_outcome_staked[index]))
+                .fold(Decimal::zero(), |max, reserve| if reserve > max { reserve } else { max })
+        }
+
+        fn next_sequence(&mut self) -> u64 {
+            self.event_sequence += 1;
+            self.event_sequence
+        }
+
+        // LP units issued against the `xrd_vault`: the LP tokens in circulation plus the house's share.
+        fn lp_units_total(&self) -> Decimal {
+            self.lp_token_supply + self.house_lp_units
+        }
+
+        // LP units worth `amount` at the current `xrd_vault` balance. One per token if the treasury is empty or unshared.
+        fn lp_units_for(&self, amount: Decimal) -> Decimal {
+            let vault_balance = self.xrd_vault.amount();
+            if self.lp_token_supply.is_zero() || vault_balance.is_zero() {
+                amount
+            } else {
+                amount * self.lp_units_total() / vault_balance
+            }
+        }
+
+        // Give the house LP units for funds it is about to add to the `xrd_vault`, while the treasury is shared.
+        fn credit_house_liquidity(&mut self, amount: Decimal) {
+            if self.lp_token_supply > Decimal::zero() {
+                self.house_lp_units += self.lp_units_for(amount);
+            }
+        }
+
+        // Seeded liquidity joins the `xrd_vault` on settlement; it was the house's money, so it adds to the house's share.
+        fn credit_seeded_liquidity(&mut self) {
+            let seeded = self.seeded_liquidity.values().fold(Decimal::zero(), |total, amount| total + *amount);
+            self.credit_house_liquidity(seeded);
+        }
+
+        // Pool the stakes that didn't win, pay every winning bettor into their vault and mark the market as resolved.
+        // With `pro_rata`, every stake is pooled and the payouts are cut to what the `xrd_vault` holds if it falls short.
+        // Returns the rewards paid, the house fee collected and the haircut ratio applied, if any.
+        fn settle_market(&mut self, winning_indices: &[usize], pro_rata: bool) -> (Vec<(String, Decimal)>, Decimal, Option<Decimal>) {
+            let parimutuel = self.payout_mode == PayoutMode::Parimutuel;
+            self.credit_seeded_liquidity();
+
+            // Transfer tokens from losing outcome vaults to the main vault (xrd_vault).
+            // A parimutuel market shares the whole pool, so the winning vaults are moved as well.
+            for (index, outcome_vault) in self.outcome_tokens.iter_mut().enumerate() {
+                if !winning_indices.contains(&index) || parimutuel || pro_rata {
+                    let tokens = outcome_vault.take_all();
+                    self.xrd_vault.put(tokens);
+                } else if let Some(seeded) = self.seeded_liquidity.get(&index) {
+                    // A winning vault keeps the winners' stakes, but the liquidity seeded into it returns to the treasury.
+                    self.xrd_vault.put(outcome_vault.take(*seeded));
+                }
+            }
+
+            // Calculate rewards for users who bet on a winning outcome, and for the winning shares, net of the house fee.
+            let (mut rewards, mut house_fee) = self.calculate_rewards(winning_indices);
+            let (mut share_payouts, share_fee) = self.calculate_share_payouts(winning_indices);
+            house_fee += share_fee;
+
+            // The winners are paid before the house. If even they can't be paid in full, they all take the same haircut.
+            let mut haircut_ratio = None;
+            if pro_rata {
+                let available = self.xrd_vault.amount();
+                let total_owed = rewards.iter().fold(Decimal::zero(), |total, (_, reward)| total + *reward)
+                    + share_payouts.iter().fold(Decimal::zero(), |total, payout| total + *payout);
+                if total_owed > available {
+                    let ratio = available / total_owed;
+                    rewards = rewards.iter()
+                        .map(|(user, reward)| (user.clone(), self.round_to_bet_token(*reward * ratio)))
+                        .collect();
+                    share_payouts = share_payouts.iter().map(|payout| self.round_to_bet_token(*payout * ratio)).collect();
+                    house_fee = Decimal::zero();
+                    haircut_ratio = Some(ratio);
+                } else if total_owed + house_fee > available {
+                    house_fee = available - total_owed;
+                }
+            }
+            self.haircut_ratio = haircut_ratio;
+            self.set_receipt_payout_rates(winning_indices);
+
+            // Move the house fee to the house admin vault.
+            if house_fee > Decimal::zero() {
+                self.ensure_admin_vault_exists(HOUSE_FEE_ADMIN_HASH.to_string());
+                let fee_bucket = self.xrd_vault.take(house_fee);
+                self.admin_vaults.get_mut(HOUSE_FEE_ADMIN_HASH).unwrap().put(fee_bucket);
+            }
+
+            // Transfer each reward from the main vault to the user's individual vault.
+            for (user, user_reward) in &rewards {
+                if let Some(mut user_vault) = self.user_vaults.get_mut(user) {
+                    user_vault.put(self.xrd_vault.take(*user_reward));
+                }
+            }
+
+            // Set the winning shares' payouts aside until they are redeemed.
+            self.set_aside_share_payouts(share_payouts);
+
+            // Reset the market and finalize it as resolved. The winning indices are sorted, so this records the lowest.
+            self.reset_and_resolve_market(MarketStatus::Resolved { winning_outcome: winning_indices[0] as u32 });
+
+            (rewards, house_fee, haircut_ratio)
+        }
+
+        // Calculate the reward for every bet on a winning outcome, net of the house fee.
+        // Returns the rewards and the total house fee.
+        fn calculate_rewards(&self, winning_indices: &[usize]) -> (Vec<(String, Decimal)>, Decimal) {
+            // Every winning bet, paired with the index of the outcome it was placed on.
+            // Only the winning bettors' entries are read from the store.
+            let winning_bets: Vec<(usize, (String, Decimal))> = winning_indices.iter()
+                .flat_map(|index| {
+                    self.outcome_bets_page(*index, 0, usize::MAX).into_iter()
+                        .map(move |bet| (*index, bet))
+                })
+                .collect();
+
+            match self.payout_mode {
+                PayoutMode::FixedOdds => {
+                    // Each bet is paid at the odds locked in when it was placed. With several winners the dead-heat
+                    // reduction divides every payout by their number.
+                    let winner_count = Decimal::from(winning_indices.len() as u64);
+                    let gross_payouts: Vec<(String, Decimal)> = winning_bets.iter()
+                        .map(|(index, (user, _))| {
+                            (user.clone(), self.round_to_bet_token(self.locked_payout(user, *index) / winner_count))
+                        })
+                        .collect();
+                    // The fee is deducted from each payout, so the house gets exactly what the winners don't.
+                    let rewards: Vec<(String, Decimal)> = gross_payouts.iter()
+                        .map(|(user, payout)| (user.clone(), self.round_to_bet_token(self.net_of_fee(*payout))))
+                        .collect();
+                    let gross_total = gross_payouts.iter().fold(Decimal::zero(), |total, (_, payout)| total + *payout);
+                    let net_total = rewards.iter().fold(Decimal::zero(), |total, (_, reward)| total + *reward);
+                    (rewards, gross_total - net_total)
+                }
+                PayoutMode::Parimutuel => {
+                    // The pool to share is every stake minus the house fee. Rounding dust stays in the treasury.
+                    let total_pool = self.round_to_bet_token(self.net_of_fee(self.total_staked));
+                    let total_winning_stake: Decimal = winning_indices.iter()
+                        .fold(Decimal::zero(), |total, index| total + self.per_outcome_staked[*index]);
+                    let rewards = winning_bets.iter()
+                        .map(|(_, (user, bet_amt))| {
+                            let user_proportion = *bet_amt / total_winning_stake;
+                            (user.clone(), self.round_to_bet_token(user_proportion * total_pool))
+                        })
+                        .collect();
+                    (rewards, self.total_staked - total_pool)
+                }
+            }
+        }
+
+        // Deduct the house fee from an amount.
+        fn net_of_fee(&self, amount: Decimal) -> Decimal {
+            amount - amount * self.get_effective_fee_percent() / Decimal::from(100)
+        }
+
+        // Round an amount down to the number of decimal places the bet token supports, so it can be taken from a vault.
+        fn round_to_bet_token(&self, amount: Decimal) -> Decimal {
+            amount.round(self.bet_token_divisibility, RoundingMode::ToZero)
+        }
+
+        // Shared by `place_bet` and `place_bet_by_index` once the outcome's position is known.
+        fn place_bet_at(&mut self, user_hash: String, outcome_position: usize, payment: Bucket) -> (Option<Bucket>, Option<Bucket>, Bucket) {
+            // Ensure user vault exists.
+            let claim_badge = self.ensure_user_vault_exists(user_hash.clone());
+
+            let (change, bet_receipt) = self.stake_bet(user_hash, outcome_position, payment);
+
+            (claim_badge, change, bet_receipt)
+        }
+
+        // Validate and record a bet for a user whose vault already exists.
+        // Returns the part of the payment that couldn't be staked and the bet receipt.
+        fn stake_bet(&mut self, user_hash: String, outcome_position: usize, mut payment: Bucket) -> (Option<Bucket>, Bucket) {
+            // Ensure the market hasn't been resolved before.
+            self.ensure_market_not_resolved();
+            
+            // Validate the bet.
+            assert!(
+                self.excluded_users.get(&user_hash).is_none(),
+                "{:?}",
+                MarketError::UserExcluded { user_hash: user_hash.clone() }
+            );
+            self.ensure_bet_token(&payment);
+            self.validate_bet(&payment);
+        
+            // Make sure the outcome still accepts bets.
+            let outcome = self.outcomes[outcome_position].clone();
+            assert!(
+                !self.locked_outcomes.contains(&outcome_position),
+                "{:?}",
+                MarketError::OutcomeLocked { outcome: outcome.clone() }
+            );
+
+            // Cap the bet at what the user can still stake on this outcome, returning the rest as change.
+            let existing_amount = self.bet_amount(&user_hash, outcome_position);
+            // Top-ups only need to be positive, so a user close to `max_bet` can still fill the gap.
+            if existing_amount.is_zero() {
+                assert!(
+                    payment.amount() >= self.min_bet,
+                    "{:?}",
+                    MarketError::BetBelowMinimum { amount: payment.amount(), minimum: self.min_bet }
+                );
+            }
+            let allowed_amount = self.max_bet - existing_amount;
+            assert!(
+                allowed_amount > Decimal::zero(),
+                "{:?}",
+                MarketError::BetAboveMaximum { amount: existing_amount + payment.amount(), maximum: self.max_bet }
+            );
+            let change = if payment.amount() > allowed_amount {
+                let excess_amount = payment.amount() - allowed_amount;
+                Some(payment.take(excess_amount))
+            } else {
+                None
+            };
+        
+            // Extract payment amount before moving `payment`
+            let payment_amount = payment.amount();
+
+            // Ensure the user stays within their cap across all outcomes.
+            if let Some(max_user_total) = self.max_user_total {
+                let user_total = self.user_total_staked(&user_hash);
+                assert!(
+                    user_total + payment_amount <= max_user_total,
+                    "{:?}",
+                    MarketError::UserTotalExceeded {
+                        user_hash: user_hash.clone(),
+                        remaining_capacity: max_user_total - user_total,
+                    }
+                );
+            }
+
+            // Add the stake to the outcome's pool. The bet is paid at the odds quoted now, whatever happens later.
+            let (odds, locked_payout) = self.add_stake(outcome_position, payment);
+            // Record the bet.
+            if self.bets.get(&user_hash).is_none() {
+                let no_bets = vec![Decimal::zero(); self.outcomes.len()];
+                self.bets.insert(user_hash.clone(), UserBets { amounts: no_bets.clone(), locked_payouts: no_bets });
+            }
+            if existing_amount.is_zero() {
+                self.add_outcome_bettor(user_hash.clone(), outcome_position); // A new bet joins the outcome's list
+            }
+            let outcome_count = self.outcomes.len();
+            let cumulative_amount = {
+                let mut user_bets = self.bets.get_mut(&user_hash).unwrap();
+                // Entries written before an outcome was added with `add_outcome` don't cover it yet.
+                user_bets.amounts.resize(outcome_count, Decimal::zero());
+                user_bets.locked_payouts.resize(outcome_count, Decimal::zero());
+                user_bets.amounts[outcome_position] += payment_amount;
+                user_bets.locked_payouts[outcome_position] += locked_payout;
+                user_bets.amounts[outcome_position]
+            };
+
+            // Mint the receipt recording the bet at the current odds.
+            self.bet_receipts_issued += 1;
+            let bet_receipt = self.bet_receipt_manager.mint_non_fungible(
+                &NonFungibleLocalId::integer(self.bet_receipts_issued),
+                BetReceiptData {
+                    market_id: self.title.clone(),
+                    user_hash: user_hash.clone(),
+                    outcome: outcome_position as u32,
+                    amount: payment_amount,
+                    odds,
+                    epoch: Runtime::current_epoch(),
+                },
+            );
+
+
+            // Emit the BetPlacedEvent.
+            Runtime::emit_event(BetPlacedEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                user_hash,
+                outcome,
+                amount: payment_amount,
+                outcome_index: outcome_position as u32,
+                odds,
+                cumulative_amount,
+                outcome_total_staked: self.per_outcome_staked[outcome_position],
+            });
+
+            (change, bet_receipt)
+        }
+
+        // Deposit a stake into its outcome's vault once the outcome's and the market's caps allow it, and update the
+        // totals and the outcome's liability. Shared by bets and share purchases.
+        // Returns the odds the stake was placed at and the gross payout it locked in.
+        fn add_stake(&mut self, outcome_position: usize, payment: Bucket) -> (Decimal, Decimal) {
+            let payment_amount = payment.amount();
+
+            // Ensure the outcome stays within its exposure cap. Seeded liquidity doesn't count towards it.
+            if let Some(max_outcome_exposure) = self.max_outcome_exposure {
+                let outcome_staked = self.per_outcome_staked[outcome_position];
+                assert!(
+                    outcome_staked + payment_amount <= max_outcome_exposure,
+                    "{:?}",
+                    MarketError::ExposureExceeded {
+                        outcome: self.outcomes[outcome_position].clone(),
+                        remaining_capacity: max_outcome_exposure - outcome_staked,
+                    }
+                );
+            }
+            // Ensure the market stays within its total cap.
+            if let Some(max_total_staked) = self.max_total_staked {
+                assert!(
+                    self.total_staked + payment_amount <= max_total_staked,
+                    "{:?}",
+                    MarketError::PoolCapExceeded { remaining_capacity: max_total_staked - self.total_staked }
+                );
+            }
+            // Deposit the payment into the outcome's vault.
+            self.outcome_tokens[outcome_position].put(payment);
+            // Update the total amount staked in the market.
+            self.total_staked += payment_amount;
+            self.per_outcome_staked[outcome_position] += payment_amount;
+            self.lifetime_staked += payment_amount;
+
+            let odds = self.effective_odds(outcome_position);
+            let locked_payout = payment_amount * odds;
+            self.outcome_liabilities[outcome_position] += locked_payout;
+            (odds, locked_payout)
+        }
+
+        // Record what a winning receipt pays per unit of its locked payout or stake, in line with `calculate_rewards`.
+        // Must run before the market is reset, and after the haircut is known.
+        fn set_receipt_payout_rates(&mut self, winning_indices: &[usize]) {
+            let rate = match self.payout_mode {
+                PayoutMode::FixedOdds => self.net_of_fee(Decimal::one()) / Decimal::from(winning_indices.len() as u64),
+                PayoutMode::Parimutuel => {
+                    let total_pool = self.round_to_bet_token(self.net_of_fee(self.total_staked));
+                    let total_winning_stake: Decimal = winning_indices.iter()
+                        .fold(Decimal::zero(), |total, index| total + self.per_outcome_staked[*index]);
+                    // Nobody bet on the winners, so there is no receipt to pay.
+                    if total_winning_stake.is_zero() {
+                        Decimal::zero()
+                    } else {
+                        total_pool / total_winning_stake
+                    }
+                }
+            };
+            let rate = rate * self.haircut_ratio.unwrap_or(Decimal::one());
+
+            self.receipt_payout_rates = vec![Decimal::zero(); self.outcomes.len()];
+            for index in winning_indices {
+                self.receipt_payout_rates[*index] = rate;
+            }
+        }
+
+        // Move the payout of each outcome's shares from the `xrd_vault` to the `share_vault`.
+        fn set_aside_share_payouts(&mut self, share_payouts: Vec<Decimal>) {
+            let total = share_payouts.iter().fold(Decimal::zero(), |total, payout| total + *payout);
+            self.share_vault.put(self.xrd_vault.take(total));
+            self.share_payouts = share_payouts;
+        }
+
+        // Calculate the payout owed to every outcome's shares, net of the house fee.
+        // Returns the payout of each outcome's shares and the house fee on it.
+        fn calculate_share_payouts(&self, winning_indices: &[usize]) -> (Vec<Decimal>, Decimal) {
+            let mut share_payouts = vec![Decimal::zero(); self.outcomes.len()];
+            let mut house_fee = Decimal::zero();
+            match self.payout_mode {
+                PayoutMode::FixedOdds => {
+                    // Shares are paid their locked-in payout, with the same dead-heat reduction and fee as bets.
+                    let winner_count = Decimal::from(winning_indices.len() as u64);
+                    for index in winning_indices {
+                        let gross = self.round_to_bet_token(self.share_locked_payouts[*index] / winner_count);
+                        share_payouts[*index] = self.round_to_bet_token(self.net_of_fee(gross));
+                        house_fee += gross - share_payouts[*index];
+                    }
+                }
+                PayoutMode::Parimutuel => {
+                    // Shares are part of the winning stake, so they get their share of the pool like bets do. The fee
+                    // is already taken from the whole pool.
+                    let total_pool = self.round_to_bet_token(self.net_of_fee(self.total_staked));
+                    let total_winning_stake: Decimal = winning_indices.iter()
+                        .fold(Decimal::zero(), |total, index| total + self.per_outcome_staked[*index]);
+                    for index in winning_indices {
+                        share_payouts[*index] = self.round_to_bet_token(
+                            self.shares_outstanding[*index] / total_winning_stake * total_pool
+                        );
+                    }
+                }
+            }
+            (share_payouts, house_fee)
+        }
+
+        fn withdraw_user_reward(&mut self, user_hash: String) -> Option<Bucket> {
+            let bucket = self.take_user_vault(&user_hash)?;
+
+            // Emit an event to indicate successful reward claim.
+            Runtime::emit_event(ClaimRewardEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                user_hash,
+                reward: bucket.amount(),
+                haircut_ratio: self.haircut_ratio,
+            });
+            
+            Some(bucket)
+        }
+
+        // Empty a user's vault. Returns `None` if the user has no vault or it's empty.
+        fn take_user_vault(&mut self, user_hash: &String) -> Option<Bucket> {
+            // Attempt to get the user's vault using the provided user_hash. If it does not exist, return None.
+            let mut vault = self.user_vaults.get_mut(user_hash)?;
+            // Nothing to claim, e.g. the user already claimed or never won.
+            if vault.is_empty() {
+                return None;
+            }
+
+            // If the user's vault holds funds, take all tokens from the vault.
+            Some(vault.take_all())
+        }
+
+        fn void_market(&mut self, forced: bool) -> Result<(), MarketError> {
+            // Ensure the market hasn't been resolved before.
+            self.ensure_market_not_resolved();
+
+            // Ensure every bet can be refunded before moving any funds, so no user is left without a refund.
+            let available = self.outcome_tokens.iter()
+                .fold(self.xrd_vault.amount(), |total, outcome_vault| total + outcome_vault.amount());
+            if available < self.total_staked {
+                return Err(MarketError::InsufficientCollateral { required: self.total_staked, available });
+            }
+            self.credit_seeded_liquidity();
+    
+            // Iterate through each outcome's vault.
+            for outcome_vault in &mut self.outcome_tokens {
+                // Take all tokens from the outcome vault.
+                let tokens = outcome_vault.take_all();
+    
+                // Transfer tokens from outcome vaults to the xrd_vault.
+                self.xrd_vault.put(tokens);
+            }
+    
+          // Iterate over all the user bets and refund them, in outcome order and then in the order they were placed.
+            for index in 0..self.outcomes.len() {
+                for (user, bet_amt) in self.outcome_bets_page(index, 0, usize::MAX) {
+                    // Extract the refund amount from the xrd_vault.
+                    let refund_bucket = self.xrd_vault.take(bet_amt);
+    
+                    // Transfer the refund to the user's vault.
+                    if let Some(mut user_vault) = self.user_vaults.get_mut(&user) {
+                        user_vault.put(refund_bucket);
+                    }
+                }
+            }
+    
+            // Every share is refunded its stake.
+            self.set_aside_share_payouts(self.shares_outstanding.clone());
+
+            // Reset the total_staked amount to 0 and mark the market as resolved to prevent further interactions.
+            self.reset_and_resolve_market(MarketStatus::Voided);
+
+            // Emit the MarketResolvedAsVoidEvent right after the market is resolved as void.
+            Runtime::emit_event(MarketResolvedAsVoidEvent {
+                market_id: self.title.clone(),
+                sequence: self.next_sequence(),
+                forced,
+            });
+
+    
+            // Return Ok to indicate the market was successfully resolved as void.
+            Ok(())
         }
 
     }        
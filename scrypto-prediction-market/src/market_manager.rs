@@ -0,0 +1,433 @@
+//! # OVERVIEW:
+//! The `MarketManager` blueprint is a factory and registry for `PredictionMarket` components.
+//! Markets created through it are full-featured `PredictionMarket` instances and are tracked by their `market_id` (title).
+//! Anyone can create a market through it by paying a flat XRD creation fee, which is kept in the manager's protocol
+//! vault until the manager's admin collects it. The manager keeps each market's admin and oracle badges in its own
+//! vaults, so the manager's admin can operate and resolve every registered market through the manager.
+//!
+//! There is no `withdraw_from_vault` proxy. That method is restricted to a market's super admin, and the super-admin
+//! badge goes back to the market's creator rather than being kept by the manager, so control of a market's treasury
+//! stays with its creator.
+//!
+//! ## SPECIFIC FUNCTION AND METHOD OVERVIEWS:
+//! 1.  Initialization and Setup:
+//!         - `new`: Instantiate an empty market manager with a creation fee and return its admin badge.
+//!         - `instantiate_prediction_market`: Create a new prediction market for the creation fee, keep its admin and oracle badges and return its super-admin badge and any change to the caller.
+//!
+//! 2.  Market Management (Admin-only):
+//!         - `lock_market`: Lock a registered market by presenting its stored admin badge.
+//!         - `resolve_market`: Resolve a registered market by presenting its stored oracle badge.
+//!         - `resolve_market_as_void`: Void a registered market by presenting its stored oracle badge, refunding every bet.
+//!         - `remove_market`: Drop a resolved or voided market from the registry.
+//!         - `collect_fees`: Withdraw every creation fee collected so far.
+//!
+//! 3.  Getters:
+//!         - `list_all_markets`: List the ids of all registered markets.
+//!         - `get_market_address`: Get the component address of a registered market.
+//!         - `list_markets_by_status`: List the ids of the registered markets in a given lifecycle state.
+//!         - `list_markets_with_details`: List every registered market with its address and a summary of its state.
+//!         - `list_markets_page`: List a page of the registered markets with their addresses and summaries.
+//!         - `get_protocol_fees_balance`: Get the creation fees held in the protocol vault.
+//!
+//! 4.  Helper Functions (Internal utility functions):
+//!         - `get_market`: Get a registered market, panicking if it doesn't exist.
+//!         - `with_badge`: Call a market with a proof of one of its stored badges.
+//!         - `summarize`: Build a market's summary from its details.
+
+use scrypto::prelude::*;
+use core::mem;
+use crate::{MarketConfig, MarketError, MarketStatus};
+use crate::prediction_market::PredictionMarket;
+
+/// Summary of a registered market's state, returned by `list_markets_with_details` and `list_markets_page`.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct MarketSummary {
+    pub title: String,
+    pub status: MarketStatus,
+    pub market_locked: bool,
+    pub market_resolved: bool,
+    pub total_staked: Decimal,
+    pub outcome_count: u32,
+}
+
+#[blueprint]
+mod market_manager {
+
+    // Method authentication setup.
+    enable_method_auth! {
+        roles {
+            manager_admin => updatable_by: [];
+        },
+
+        methods {
+            // Anyone can create a market by paying the creation fee. The manager keeps its admin and oracle badges,
+            // so the markets are then operated by the manager's admin.
+            instantiate_prediction_market => PUBLIC;
+            lock_market => restrict_to: [manager_admin];
+            resolve_market => restrict_to: [manager_admin];
+            resolve_market_as_void => restrict_to: [manager_admin];
+            remove_market => restrict_to: [manager_admin];
+            collect_fees => restrict_to: [manager_admin];
+
+            list_all_markets => PUBLIC;
+            get_market_address => PUBLIC;
+            list_markets_by_status => PUBLIC;
+            list_markets_with_details => PUBLIC;
+            list_markets_page => PUBLIC;
+            get_protocol_fees_balance => PUBLIC;
+        }
+    }
+
+    // Primary structure for the market manager.
+    pub struct MarketManager {
+        // Markets created through this manager, mapped by market id (the market's title).
+        markets: HashMap<String, Global<PredictionMarket>>,
+
+        // Admin badge of each market, mapped by market id. Presented when calling the markets' admin-only methods.
+        admin_badges: HashMap<String, FungibleVault>,
+
+        // Oracle badge of each market, mapped by market id. Presented when resolving the markets.
+        oracle_badges: HashMap<String, FungibleVault>,
+
+        // Admin and oracle badges of removed markets. Vaults cannot be dropped, so they are kept here.
+        retired_badges: Vec<FungibleVault>,
+
+        // Flat XRD fee charged for every market created through the manager.
+        creation_fee: Decimal,
+
+        // Creation fees collected so far, until the admin withdraws them with `collect_fees`.
+        protocol_vault: Vault,
+    }
+
+    impl MarketManager {
+
+        //1. Initialization and Setup:
+
+/// Instantiates an empty market manager.
+///
+/// A manager admin badge is minted and returned. It is required to operate the markets created through the manager
+/// and to collect the creation fees.
+///
+/// `creation_fee`: The flat amount of XRD charged for every market created through the manager. Can be zero.
+///
+/// ---
+///
+/// **Access control:** Anyone can instantiate a market manager.
+///
+/// **Errors:** If `creation_fee` is negative.
+///
+/// **Transaction manifest:**
+/// `transactions/new_market_manager.rtm`
+        pub fn new(creation_fee: Decimal) -> (Global<MarketManager>, FungibleBucket) {
+            assert!(creation_fee >= Decimal::zero(), "Creation fee cannot be negative. Provided: {}", creation_fee);
+
+            let admin_badge = ResourceBuilder::new_fungible(OwnerRole::None)
+            .metadata(metadata!(init{"name"=>"Market Manager Admin Badge", locked;}))
+            .divisibility(DIVISIBILITY_NONE)
+            .mint_initial_supply(1);
+
+            let component = Self {
+                markets: HashMap::new(),
+                admin_badges: HashMap::new(),
+                oracle_badges: HashMap::new(),
+                retired_badges: Vec::new(),
+                creation_fee,
+                protocol_vault: Vault::new(XRD),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .roles(roles!(
+                manager_admin => rule!(require(admin_badge.resource_address()));
+            ))
+            .globalize();
+
+            (component, admin_badge)
+        }
+
+/// Creates a new prediction market and registers it under its title.
+///
+/// All market parameters are forwarded to `PredictionMarket::instantiate_prediction_market`, which performs the
+/// validation. The market's `admin_badge` and `oracle_badge` are kept by the manager so it can operate and
+/// resolve the market, and its `super_badge` is returned to the caller.
+///
+/// `payment`: XRD covering the manager's creation fee. The fee goes to the protocol vault and the rest is returned.
+///
+/// Will panic if a market with the same title is already registered.
+///
+/// # Returns:
+///
+/// A tuple of the market's super-admin badge and the change left from `payment`, if any.
+///
+/// ---
+///
+/// **Access control:** Public method, can be called by anyone paying the creation fee.
+///
+/// **Errors:** If `payment` is not XRD or doesn't cover the creation fee.
+///
+/// **Transaction manifest:**
+/// `transactions/manager_instantiate_prediction_market.rtm`
+        pub fn instantiate_prediction_market(&mut self, title: String, outcomes_str: String, odds_str: String,
+        min_bet: Decimal, max_bet: Decimal, config: MarketConfig, mut payment: Bucket) -> (FungibleBucket, Option<Bucket>) {
+            assert!(
+                !self.markets.contains_key(&title),
+                "A market with the title '{}' already exists.",
+                title
+            );
+
+            // Keep the creation fee and return the rest as change.
+            assert_eq!(payment.resource_address(), XRD, "The creation fee must be paid in XRD.");
+            assert!(
+                payment.amount() >= self.creation_fee,
+                "Payment of {} does not cover the creation fee of {}.",
+                payment.amount(),
+                self.creation_fee
+            );
+            let change = if payment.amount() > self.creation_fee {
+                let excess_amount = payment.amount() - self.creation_fee;
+                Some(payment.take(excess_amount))
+            } else {
+                None
+            };
+            self.protocol_vault.put(payment);
+
+            let (market, super_admin_badge, admin_badge, oracle_badge) = Blueprint::<PredictionMarket>::instantiate_prediction_market(
+                title.clone(), outcomes_str, odds_str, min_bet, max_bet, config,
+            );
+            self.markets.insert(title.clone(), market);
+            self.admin_badges.insert(title.clone(), FungibleVault::with_bucket(admin_badge));
+            self.oracle_badges.insert(title, FungibleVault::with_bucket(oracle_badge));
+
+            (super_admin_badge, change)
+        }
+
+        // 2. Market Management - Admin only:
+
+/// Locks a registered market, preventing further bets.
+///
+/// The call is forwarded to the market's `lock_market`, authorized with the market's stored admin badge.
+///
+/// ---
+///
+/// **Access control:** Manager admin only.
+///
+/// **Errors:** If no market is registered under `market_id`.
+///
+/// **Transaction manifest:**
+/// `transactions/manager_lock_market.rtm`
+        pub fn lock_market(&mut self, market_id: String) {
+            self.with_badge(&self.admin_badges, &market_id, |market| market.lock_market())
+        }
+
+/// Resolves a registered market with the given winning outcome.
+///
+/// The call is forwarded to the market's `resolve_market`, authorized with the market's stored oracle badge.
+///
+/// # Returns:
+///
+/// The market's rewards, as returned by `PredictionMarket::resolve_market`.
+///
+/// ---
+///
+/// **Access control:** Manager admin only.
+///
+/// **Errors:** If no market is registered under `market_id`, or if the market's resolution fails.
+///
+/// **Transaction manifest:**
+/// `transactions/manager_resolve_market.rtm`
+        pub fn resolve_market(&mut self, market_id: String, winning_outcome: u32) -> Result<Vec<(String, Decimal)>, MarketError> {
+            self.with_badge(&self.oracle_badges, &market_id, |market| market.resolve_market(winning_outcome))
+        }
+
+/// Voids a registered market, refunding every bet.
+///
+/// The call is forwarded to the market's `resolve_market_as_void`, authorized with the market's stored oracle badge.
+///
+/// ---
+///
+/// **Access control:** Manager admin only.
+///
+/// **Errors:** If no market is registered under `market_id`, or if the market can't be voided.
+///
+/// **Transaction manifest:**
+/// `transactions/manager_resolve_market_as_void.rtm`
+        pub fn resolve_market_as_void(&mut self, market_id: String) -> Result<(), MarketError> {
+            self.with_badge(&self.oracle_badges, &market_id, |market| market.resolve_market_as_void())
+        }
+
+/// Removes a market from the registry once it is settled.
+///
+/// Only markets whose status is `Resolved` or `Voided` are removed; the market component itself keeps existing, so
+/// users can still claim their rewards or refunds from it directly. Its admin and oracle badges stay with the manager.
+///
+/// # Returns:
+///
+/// `true` if the market was removed, `false` if no market is registered under `market_id` or it is not settled yet.
+///
+/// ---
+///
+/// **Access control:** Manager admin only.
+///
+/// **Transaction manifest:**
+/// `transactions/remove_market.rtm`
+        pub fn remove_market(&mut self, market_id: String) -> bool {
+            let settled = match self.markets.get(&market_id) {
+                Some(market) => matches!(market.get_market_status(), MarketStatus::Resolved { .. } | MarketStatus::Voided),
+                None => false,
+            };
+            if !settled {
+                return false;
+            }
+
+            self.markets.remove(&market_id);
+            for badges in [&mut self.admin_badges, &mut self.oracle_badges] {
+                if let Some(badge) = badges.remove(&market_id) {
+                    self.retired_badges.push(badge);
+                }
+            }
+            true
+        }
+
+/// Withdraws every creation fee collected so far.
+///
+/// # Returns:
+///
+/// A `Bucket` holding the protocol vault's XRD, which may be empty.
+///
+/// ---
+///
+/// **Access control:** Manager admin only.
+///
+/// **Transaction manifest:**
+/// `transactions/collect_fees.rtm`
+        pub fn collect_fees(&mut self) -> Bucket {
+            self.protocol_vault.take_all()
+        }
+
+        // 3. Getters:
+
+/// Lists the ids of all markets registered with this manager.
+///
+/// ---
+///
+/// **Access control:** Read only, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/list_all_markets.rtm`
+        pub fn list_all_markets(&self) -> Vec<String> {
+            self.markets.keys().cloned().collect()
+        }
+
+/// Retrieves the component address of a registered market.
+///
+/// ---
+///
+/// **Access control:** Read only, can be called by anyone.
+///
+/// **Errors:** If no market is registered under `market_id`.
+///
+/// **Transaction manifest:**
+/// `transactions/get_market_address.rtm`
+        pub fn get_market_address(&self, market_id: String) -> ComponentAddress {
+            self.get_market(&market_id).address()
+        }
+
+/// Lists the ids of the registered markets whose status matches `status`.
+///
+/// Only the status variant is compared, so passing `Resolved` with any winning outcome lists every resolved market.
+/// Each market's `get_market_status` is queried, so the cost grows with the number of registered markets.
+///
+/// ---
+///
+/// **Access control:** Read only, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/list_markets_by_status.rtm`
+        pub fn list_markets_by_status(&self, status: MarketStatus) -> Vec<String> {
+            self.markets.iter()
+                .filter(|(_, market)| mem::discriminant(&market.get_market_status()) == mem::discriminant(&status))
+                .map(|(market_id, _)| market_id.clone())
+                .collect()
+        }
+
+/// Lists every registered market with its component address and a summary of its state.
+///
+/// Markets are ordered by market id. Each market's `get_market_details` is queried, so the cost grows with the number
+/// of registered markets; use `list_markets_page` to read a large registry in several calls.
+///
+/// ---
+///
+/// **Access control:** Read only, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/list_markets_with_details.rtm`
+        pub fn list_markets_with_details(&self) -> Vec<(String, ComponentAddress, MarketSummary)> {
+            self.list_markets_page(0, u32::MAX)
+        }
+
+/// Lists a page of the registered markets with their component addresses and summaries.
+///
+/// Returns the markets from position `offset` up to `offset + limit` in market id order, as in
+/// `list_markets_with_details`. The page is empty if `offset` is past the end.
+///
+/// ---
+///
+/// **Access control:** Read only, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/list_markets_page.rtm`
+        pub fn list_markets_page(&self, offset: u32, limit: u32) -> Vec<(String, ComponentAddress, MarketSummary)> {
+            let mut market_ids: Vec<&String> = self.markets.keys().collect();
+            market_ids.sort();
+
+            market_ids.into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .map(|market_id| {
+                    let market = self.get_market(market_id);
+                    (market_id.clone(), market.address(), Self::summarize(market))
+                })
+                .collect()
+        }
+
+/// Retrieves the creation fees held in the protocol vault, waiting to be collected.
+///
+/// ---
+///
+/// **Access control:** Read only, can be called by anyone.
+///
+/// **Transaction manifest:**
+/// `transactions/get_protocol_fees_balance.rtm`
+        pub fn get_protocol_fees_balance(&self) -> Decimal {
+            self.protocol_vault.amount()
+        }
+
+        // 4. Helpers:
+
+        fn get_market(&self, market_id: &String) -> Global<PredictionMarket> {
+            *self.markets.get(market_id)
+                .unwrap_or_else(|| panic!("Market '{}' does not exist.", market_id))
+        }
+
+        // Builds a market's summary from a single `get_market_details` call.
+        fn summarize(market: Global<PredictionMarket>) -> MarketSummary {
+            let details = market.get_market_details();
+            MarketSummary {
+                title: details.title,
+                status: details.status,
+                market_locked: details.market_locked,
+                market_resolved: details.market_resolved,
+                total_staked: details.total_staked,
+                outcome_count: details.outcomes.len() as u32,
+            }
+        }
+
+        // Calls `f` on the market with a proof of its badge from `badges` in the auth zone.
+        fn with_badge<O, F: FnOnce(Global<PredictionMarket>) -> O>(&self, badges: &HashMap<String, FungibleVault>,
+        market_id: &String, f: F) -> O {
+            let market = self.get_market(market_id);
+            badges.get(market_id)
+                .expect("Badge not found.")
+                .authorize_with_amount(1, || f(market))
+        }
+    }
+}
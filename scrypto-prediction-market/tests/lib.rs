@@ -1,6 +1,7 @@
 use scrypto::prelude::*;
 use scrypto_test::prelude::*;
-use scrypto_unit::TestRunnerBuilder;
+use scrypto_unit::*;
+use radix_engine::transaction::TransactionReceipt;
 
 
 #[test]
@@ -26,7 +27,7 @@ fn test_instantiate_prediction_market() -> Result<(), RuntimeError> {
             package_address,
             "PredictionMarket",
             "instantiate_prediction_market",
-            manifest_args!(title, outcomes_str, odds_str, min_bet, max_bet),
+            manifest_args!(title, outcomes_str, odds_str, min_bet, max_bet, MarketConfig::default()),
         )
         .call_method(
                 account_component,
@@ -69,7 +70,8 @@ fn test_list_outcomes() -> Result<(), RuntimeError> {
                 outcomes_str.clone(),
                 odds_str.clone(),
                 min_bet.clone(),
-                max_bet.clone()
+                max_bet.clone(),
+                MarketConfig::default()
             ),
         )
         .call_method(
@@ -149,3 +151,3565 @@ fn test_deposit() -> Result<(), RuntimeError> {
 
     Ok(())
 }
+
+// Shared helpers used by the tests below.
+
+// Mirror of the blueprint's `PayoutMode`, encodable in manifests.
+#[derive(ManifestSbor, ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+enum PayoutMode {
+    FixedOdds,
+    Parimutuel,
+}
+
+// Mirror of the blueprint's `MarketConfig`, encodable in manifests.
+#[derive(ManifestSbor)]
+struct MarketConfig {
+    bet_token: ResourceAddress,
+    betting_deadline: Option<Epoch>,
+    payout_mode: PayoutMode,
+    fee_percent: Decimal,
+    resolution_deadline: Option<Epoch>,
+    max_outcome_exposure: Option<Decimal>,
+    dispute_window_epochs: u64,
+    claim_deadline_epochs: Option<u64>,
+    odds_decay_per_epoch: Decimal,
+    description: String,
+    resolution_source: String,
+    case_insensitive_outcomes: bool,
+    max_total_staked: Option<Decimal>,
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self {
+            bet_token: XRD,
+            betting_deadline: None,
+            payout_mode: PayoutMode::FixedOdds,
+            fee_percent: dec!("0"),
+            resolution_deadline: None,
+            max_outcome_exposure: None,
+            dispute_window_epochs: 0,
+            claim_deadline_epochs: None,
+            odds_decay_per_epoch: dec!("0"),
+            description: String::new(),
+            resolution_source: String::new(),
+            case_insensitive_outcomes: false,
+            max_total_staked: None,
+        }
+    }
+}
+
+// Mirror of the blueprint's `MarketStatus`, used to decode `get_market_status`.
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+enum MarketStatus {
+    Open,
+    Locked,
+    Resolved { winning_outcome: u32 },
+    Voided,
+}
+
+// Mirror of the blueprint's `MarketError`, used to decode the resolution methods' results.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+enum MarketError {
+    MarketLocked,
+    MarketResolved,
+    ResolutionPending,
+    NoPendingResolution,
+    DisputeWindowRequired { dispute_window_epochs: u64 },
+    DisputeWindowOpen { finalizable_from: Epoch },
+    BettingClosed { deadline: Epoch },
+    WrongBetToken { expected: ResourceAddress, provided: ResourceAddress },
+    BetBelowMinimum { amount: Decimal, minimum: Decimal },
+    BetAboveMaximum { amount: Decimal, maximum: Decimal },
+    ExposureExceeded { outcome: String, remaining_capacity: Decimal },
+    UserExcluded { user_hash: String },
+    UnknownOutcome { outcome: String },
+    OutcomeOutOfBounds { outcome: u32 },
+    InsufficientCollateral { required: Decimal, available: Decimal },
+    WinningOutcomeHasNoBets { outcome: u32 },
+    OutcomeLocked { outcome: String },
+    BetsAlreadyPlaced { total_staked: Decimal },
+    MarketPaused,
+    PoolCapExceeded { remaining_capacity: Decimal },
+    UserTotalExceeded { user_hash: String, remaining_capacity: Decimal },
+    LiquidityLocked { redeemable_after: Option<Epoch> },
+}
+
+// Asserts the transaction was rejected with the given `MarketError` variant.
+fn expect_market_error(receipt: TransactionReceipt, variant: &str) {
+    receipt.expect_specific_failure(|error| format!("{:?}", error).contains(variant));
+}
+
+struct MarketParams {
+    title: String,
+    outcomes_str: String,
+    odds_str: String,
+    min_bet: Decimal,
+    max_bet: Decimal,
+    bet_token: ResourceAddress,
+    betting_deadline: Option<Epoch>,
+    payout_mode: PayoutMode,
+    fee_percent: Decimal,
+    resolution_deadline: Option<Epoch>,
+    max_outcome_exposure: Option<Decimal>,
+    dispute_window_epochs: u64,
+    claim_deadline_epochs: Option<u64>,
+    odds_decay_per_epoch: Decimal,
+    description: String,
+    resolution_source: String,
+    case_insensitive_outcomes: bool,
+    max_total_staked: Option<Decimal>,
+}
+
+impl Default for MarketParams {
+    fn default() -> Self {
+        Self {
+            title: "title".to_string(),
+            outcomes_str: "outcome1,outcome2".to_string(),
+            odds_str: "2,3".to_string(),
+            min_bet: dec!("5"),
+            max_bet: dec!("100"),
+            bet_token: XRD,
+            betting_deadline: None,
+            payout_mode: PayoutMode::FixedOdds,
+            fee_percent: dec!("0"),
+            resolution_deadline: None,
+            max_outcome_exposure: None,
+            dispute_window_epochs: 0,
+            claim_deadline_epochs: None,
+            odds_decay_per_epoch: dec!("0"),
+            description: String::new(),
+            resolution_source: String::new(),
+            case_insensitive_outcomes: false,
+            max_total_staked: None,
+        }
+    }
+}
+
+struct TestMarket {
+    component: ComponentAddress,
+    super_badge: ResourceAddress,
+    admin_badge: ResourceAddress,
+    oracle_badge: ResourceAddress,
+    claim_badge: ResourceAddress,
+    bet_receipt: ResourceAddress,
+    resolver_badge: ResourceAddress,
+    lp_token: ResourceAddress,
+    shares: Vec<ResourceAddress>,
+    bet_token: ResourceAddress,
+}
+
+struct TestEnv {
+    test_runner: DefaultTestRunner,
+    public_key: Secp256k1PublicKey,
+    account: ComponentAddress,
+    package_address: PackageAddress,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        let mut test_runner = TestRunnerBuilder::new().build();
+        let (public_key, _private_key, account) = test_runner.new_allocated_account();
+        let package_address = test_runner.compile_and_publish(this_package!());
+        Self { test_runner, public_key, account, package_address }
+    }
+
+    fn execute(&mut self, manifest: TransactionManifestV1) -> TransactionReceipt {
+        let public_key = self.public_key;
+        self.execute_as(manifest, &public_key)
+    }
+
+    fn execute_as(&mut self, manifest: TransactionManifestV1, public_key: &Secp256k1PublicKey) -> TransactionReceipt {
+        self.test_runner.execute_manifest_ignoring_fee(
+            manifest,
+            vec![NonFungibleGlobalId::from_public_key(public_key)],
+        )
+    }
+
+    fn instantiate_receipt(&mut self, params: &MarketParams) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .call_function(
+                self.package_address,
+                "PredictionMarket",
+                "instantiate_prediction_market",
+                manifest_args!(
+                    params.title.clone(),
+                    params.outcomes_str.clone(),
+                    params.odds_str.clone(),
+                    params.min_bet,
+                    params.max_bet,
+                    MarketConfig {
+                        bet_token: params.bet_token,
+                        betting_deadline: params.betting_deadline,
+                        payout_mode: params.payout_mode,
+                        fee_percent: params.fee_percent,
+                        resolution_deadline: params.resolution_deadline,
+                        max_outcome_exposure: params.max_outcome_exposure,
+                        dispute_window_epochs: params.dispute_window_epochs,
+                        claim_deadline_epochs: params.claim_deadline_epochs,
+                        odds_decay_per_epoch: params.odds_decay_per_epoch,
+                        description: params.description.clone(),
+                        resolution_source: params.resolution_source.clone(),
+                        case_insensitive_outcomes: params.case_insensitive_outcomes,
+                        max_total_staked: params.max_total_staked,
+                    }
+                ),
+            )
+            .deposit_batch(self.account)
+            .build();
+        self.execute(manifest)
+    }
+
+    fn instantiate(&mut self, params: &MarketParams) -> TestMarket {
+        let receipt = self.instantiate_receipt(params);
+        let commit = receipt.expect_commit_success();
+        TestMarket {
+            component: commit.new_component_addresses()[0],
+            super_badge: commit.new_resource_addresses()[0],
+            admin_badge: commit.new_resource_addresses()[1],
+            oracle_badge: commit.new_resource_addresses()[2],
+            claim_badge: commit.new_resource_addresses()[3],
+            bet_receipt: commit.new_resource_addresses()[4],
+            resolver_badge: commit.new_resource_addresses()[5],
+            lp_token: commit.new_resource_addresses()[6],
+            shares: commit.new_resource_addresses()[7..].to_vec(),
+            bet_token: params.bet_token,
+        }
+    }
+
+    fn place_bet(&mut self, market: &TestMarket, user_hash: &str, outcome: &str, amount: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account(self.account, market.bet_token, amount)
+            .take_from_worktop(market.bet_token, amount, "bet")
+            .call_method_with_name_lookup(market.component, "place_bet", |lookup| {
+                (user_hash.to_string(), outcome.to_string(), lookup.bucket("bet"))
+            })
+            .deposit_batch(self.account)
+            .build();
+        self.execute(manifest)
+    }
+
+    fn deposit_to_vault(&mut self, market: &TestMarket, amount: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account(self.account, market.bet_token, amount)
+            .take_from_worktop(market.bet_token, amount, "deposit")
+            .call_method_with_name_lookup(market.component, "deposit_to_xrd_vault", |lookup| {
+                (lookup.bucket("deposit"),)
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn resolve_market(&mut self, market: &TestMarket, winning_outcome: u32) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.oracle_badge, dec!("1"))
+            .call_method(market.component, "resolve_market", manifest_args!(winning_outcome))
+            .build();
+        self.execute(manifest)
+    }
+
+    fn propose_resolution(&mut self, market: &TestMarket, winning_outcome: u32) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.oracle_badge, dec!("1"))
+            .call_method(market.component, "propose_resolution", manifest_args!(winning_outcome))
+            .build();
+        self.execute(manifest)
+    }
+
+    fn cancel_proposed_resolution(&mut self, market: &TestMarket) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.oracle_badge, dec!("1"))
+            .call_method(market.component, "cancel_proposed_resolution", manifest_args!())
+            .build();
+        self.execute(manifest)
+    }
+
+    fn finalize_resolution(&mut self, market: &TestMarket) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "finalize_resolution", manifest_args!())
+            .build();
+        self.execute(manifest)
+    }
+
+    fn lock_market(&mut self, market: &TestMarket) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "lock_market", manifest_args!())
+            .build();
+        self.execute(manifest)
+    }
+
+    fn unlock_market(&mut self, market: &TestMarket) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "unlock_market", manifest_args!())
+            .build();
+        self.execute(manifest)
+    }
+
+    fn set_hash_claims_enabled(&mut self, market: &TestMarket, enabled: bool) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "set_hash_claims_enabled", manifest_args!(enabled))
+            .build();
+        self.execute(manifest)
+    }
+
+    fn set_strict_resolution(&mut self, market: &TestMarket, enabled: bool) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "set_strict_resolution", manifest_args!(enabled))
+            .build();
+        self.execute(manifest)
+    }
+
+    fn claim_reward_by_hash(&mut self, market: &TestMarket, user_hash: &str) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "claim_reward_by_hash", manifest_args!(user_hash.to_string()))
+            .deposit_batch(self.account)
+            .build();
+        self.execute(manifest)
+    }
+
+    fn update_odds(&mut self, market: &TestMarket, outcome: &str, new_odds: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "update_odds", manifest_args!(outcome.to_string(), new_odds))
+            .build();
+        self.execute(manifest)
+    }
+
+    // Cancels a bet with one specific claim badge; badges are numbered in the order the users first bet.
+    fn cancel_bet(&mut self, market: &TestMarket, badge_id: u64, outcome: &str) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungibles(self.account, market.claim_badge, [NonFungibleLocalId::integer(badge_id)])
+            .pop_from_auth_zone("claim_badge")
+            .call_method_with_name_lookup(market.component, "cancel_bet", |lookup| {
+                (lookup.proof("claim_badge"), outcome.to_string())
+            })
+            .build();
+        self.execute(manifest)
+    }
+
+    fn self_exclude(&mut self, market: &TestMarket, badge_id: u64) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungibles(self.account, market.claim_badge, [NonFungibleLocalId::integer(badge_id)])
+            .pop_from_auth_zone("claim_badge")
+            .call_method_with_name_lookup(market.component, "self_exclude", |lookup| (lookup.proof("claim_badge"),))
+            .build();
+        self.execute(manifest)
+    }
+
+    // Resolves the market and returns the rewards it paid out.
+    fn resolve_market_rewards(&mut self, market: &TestMarket, winning_outcome: u32) -> Vec<(String, Decimal)> {
+        let receipt = self.resolve_market(market, winning_outcome);
+        // Outputs: 0 is the fee lock, 1 the oracle proof and 2 the resolution.
+        let result: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+        result.unwrap()
+    }
+
+    fn resolve_market_multi(&mut self, market: &TestMarket, winning_outcomes: Vec<u32>) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.oracle_badge, dec!("1"))
+            .call_method(market.component, "resolve_market_multi", manifest_args!(winning_outcomes))
+            .build();
+        self.execute(manifest)
+    }
+
+    fn resolve_market_as_void(&mut self, market: &TestMarket) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.oracle_badge, dec!("1"))
+            .call_method(market.component, "resolve_market_as_void", manifest_args!())
+            .build();
+        self.execute(manifest)
+    }
+
+    fn claim_reward(&mut self, market: &TestMarket, badge: ResourceAddress) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, badge, dec!("1"))
+            .pop_from_auth_zone("claim_badge")
+            .call_method_with_name_lookup(market.component, "claim_reward", |lookup| {
+                (lookup.proof("claim_badge"),)
+            })
+            .deposit_batch(self.account)
+            .build();
+        self.execute(manifest)
+    }
+
+    // Claims with one specific claim badge, for tests where the account holds the badges of several users.
+    fn claim_reward_with_badge(&mut self, market: &TestMarket, badge_id: u64) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_non_fungibles(self.account, market.claim_badge, [NonFungibleLocalId::integer(badge_id)])
+            .pop_from_auth_zone("claim_badge")
+            .call_method_with_name_lookup(market.component, "claim_reward", |lookup| {
+                (lookup.proof("claim_badge"),)
+            })
+            .deposit_batch(self.account)
+            .build();
+        self.execute(manifest)
+    }
+
+    fn withdraw_from_vault(&mut self, market: &TestMarket, admin_hash: &str, amount: Decimal) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.super_badge, dec!("1"))
+            .call_method(market.component, "withdraw_from_vault", manifest_args!(admin_hash.to_string(), amount))
+            .build();
+        self.execute(manifest)
+    }
+
+    fn admin_claim(&mut self, market: &TestMarket, admin_hash: &str) -> TransactionReceipt {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(self.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "admin_claim", manifest_args!(admin_hash.to_string()))
+            .deposit_batch(self.account)
+            .build();
+        self.execute(manifest)
+    }
+
+    // Returns the payloads of all application events with the given name.
+    fn events_named(&self, receipt: &TransactionReceipt, name: &str) -> Vec<Vec<u8>> {
+        receipt
+            .expect_commit_success()
+            .application_events
+            .iter()
+            .filter(|(identifier, _)| self.test_runner.event_name(identifier) == name)
+            .map(|(_, data)| data.clone())
+            .collect()
+    }
+
+    fn market_details(&mut self, market: &TestMarket) -> MarketDetails {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_market_details", manifest_args!())
+            .build();
+        self.execute(manifest).expect_commit_success().output(1)
+    }
+
+    fn balance(&mut self, resource: ResourceAddress) -> Decimal {
+        let account = self.account;
+        self.test_runner.get_component_balance(account, resource)
+    }
+}
+
+// Creates a market denominated in a fresh token so balance checks aren't affected by fees.
+fn token_market(env: &mut TestEnv, params: MarketParams) -> TestMarket {
+    let account = env.account;
+    let token = env.test_runner.create_fungible_resource(dec!("100000"), DIVISIBILITY_MAXIMUM, account);
+    env.instantiate(&MarketParams { bet_token: token, ..params })
+}
+
+#[test]
+fn test_market_with_custom_bet_token() {
+    let mut env = TestEnv::new();
+    let account = env.account;
+    let token = env.test_runner.create_fungible_resource(dec!("1000"), DIVISIBILITY_MAXIMUM, account);
+    let market = env.instantiate(&MarketParams { bet_token: token, ..Default::default() });
+
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+
+    // A bet paid in XRD is rejected by a market denominated in another token.
+    let xrd_market = TestMarket { bet_token: XRD, ..market };
+    expect_market_error(env.place_bet(&xrd_market, "user1", "outcome1", dec!("50")), "WrongBetToken");
+}
+
+#[test]
+fn test_claim_reward_requires_claim_badge() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    assert_eq!(env.balance(market.claim_badge), dec!("1"));
+    env.resolve_market(&market, 0).expect_commit_success();
+
+    // A proof of any other resource is rejected.
+    env.claim_reward(&market, market.admin_badge).expect_commit_failure();
+
+    // The claim badge minted on the first bet unlocks the reward.
+    let before = env.balance(market.bet_token);
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("100"));
+}
+
+#[test]
+fn test_claim_badge_of_another_user_cannot_claim_their_reward() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+
+    // user2's badge only reaches user2's (empty) vault, not user1's winnings.
+    let before = env.balance(market.bet_token);
+    let receipt = env.claim_reward_with_badge(&market, 2);
+    assert!(env.events_named(&receipt, "ClaimRewardEvent").is_empty());
+    assert_eq!(env.balance(market.bet_token), before);
+
+    env.claim_reward_with_badge(&market, 1).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("100"));
+}
+
+#[test]
+fn test_claim_reward_by_hash_is_disabled_by_default() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+
+    // Knowing the user hash is not enough to claim.
+    env.claim_reward_by_hash(&market, "user1").expect_commit_failure();
+
+    // Only the admin can turn the compatibility path on.
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "set_hash_claims_enabled", manifest_args!(true))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+
+    env.set_hash_claims_enabled(&market, true).expect_commit_success();
+    let before = env.balance(market.bet_token);
+    env.claim_reward_by_hash(&market, "user1").expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("100"));
+}
+
+// Mirror of the blueprint's `BetReceiptData`, used to read bet receipts.
+#[derive(ScryptoSbor, NonFungibleData)]
+struct BetReceiptData {
+    market_id: String,
+    user_hash: String,
+    outcome: u32,
+    amount: Decimal,
+    odds: Decimal,
+    epoch: Epoch,
+}
+
+#[test]
+fn test_bet_receipt_records_bet_and_is_burned_on_redemption() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("20")).expect_commit_success();
+    assert_eq!(env.balance(market.bet_receipt), dec!("2"));
+
+    let receipt: BetReceiptData = env.test_runner.get_non_fungible_data(market.bet_receipt, NonFungibleLocalId::integer(1));
+    assert_eq!(receipt.market_id, "title");
+    assert_eq!(receipt.user_hash, "user1");
+    assert_eq!(receipt.outcome, 0);
+    assert_eq!(receipt.amount, dec!("50"));
+    assert_eq!(receipt.odds, dec!("2"));
+    assert_eq!(receipt.epoch, env.test_runner.get_current_epoch());
+    let receipt: BetReceiptData = env.test_runner.get_non_fungible_data(market.bet_receipt, NonFungibleLocalId::integer(2));
+    assert_eq!((receipt.outcome, receipt.amount, receipt.odds), (1, dec!("20"), dec!("3")));
+
+    let redeem = |env: &mut TestEnv, id: u64| {
+        let manifest = ManifestBuilder::new()
+            .withdraw_non_fungibles_from_account(env.account, market.bet_receipt, [NonFungibleLocalId::integer(id)])
+            .take_all_from_worktop(market.bet_receipt, "bet_receipt")
+            .call_method_with_name_lookup(market.component, "redeem_receipt", |lookup| (lookup.bucket("bet_receipt"),))
+            .deposit_batch(env.account)
+            .build();
+        env.execute(manifest)
+    };
+
+    // Receipts can't be redeemed before the market is resolved.
+    redeem(&mut env, 1).expect_commit_failure();
+
+    env.resolve_market(&market, 0).expect_commit_success();
+    let before = env.balance(market.bet_token);
+    redeem(&mut env, 1).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("100"));
+    assert_eq!(env.balance(market.bet_receipt), dec!("1"));
+
+    // The receipt is burned, so it can't be redeemed again.
+    redeem(&mut env, 1).expect_commit_failure();
+}
+
+#[test]
+fn test_bet_receipts_pay_their_own_bet_to_any_holder() {
+    let mut env = TestEnv::new();
+    let (public_key, account) = (env.public_key, env.account);
+    let (other_public_key, _, other_account) = env.test_runner.new_allocated_account();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    // Receipts 1 and 2 are user1's winning bets, 3 their losing bet and 4 user2's bet, which is cancelled.
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("10")).expect_commit_success();
+    env.cancel_bet(&market, 2, "outcome1").expect_commit_success();
+
+    // Receipt 2 changes hands before the market resolves.
+    let manifest = ManifestBuilder::new()
+        .withdraw_non_fungibles_from_account(account, market.bet_receipt, [NonFungibleLocalId::integer(2)])
+        .try_deposit_batch_or_abort(other_account, None)
+        .build();
+    env.execute(manifest).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+
+    let redeem = |env: &mut TestEnv, id: u64, holder: ComponentAddress, holder_key: &Secp256k1PublicKey| {
+        let before = env.test_runner.get_component_balance(holder, market.bet_token);
+        let manifest = ManifestBuilder::new()
+            .withdraw_non_fungibles_from_account(holder, market.bet_receipt, [NonFungibleLocalId::integer(id)])
+            .take_all_from_worktop(market.bet_receipt, "bet_receipt")
+            .call_method_with_name_lookup(market.component, "redeem_receipt", |lookup| (lookup.bucket("bet_receipt"),))
+            .deposit_batch(holder)
+            .build();
+        env.execute_as(manifest, holder_key).expect_commit_success();
+        env.test_runner.get_component_balance(holder, market.bet_token) - before
+    };
+
+    // Each receipt pays its own bet, whoever redeems it, and a losing or cancelled bet's receipt pays nothing.
+    assert_eq!(redeem(&mut env, 3, account, &public_key), Decimal::zero());
+    assert_eq!(redeem(&mut env, 4, account, &public_key), Decimal::zero());
+    assert_eq!(redeem(&mut env, 2, other_account, &other_public_key), dec!("40"));
+    assert_eq!(redeem(&mut env, 1, account, &public_key), dec!("100"));
+
+    // The receipts were paid out of user1's winnings, so the claim badge has nothing left to claim, while user2
+    // still gets their refund.
+    let before = env.balance(market.bet_token);
+    env.claim_reward_with_badge(&market, 1).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token), before);
+    env.claim_reward_with_badge(&market, 2).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("10"));
+}
+
+#[test]
+fn test_claim_reward_with_nothing_to_claim_commits_without_payout() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+
+    // Claiming a second time commits, pays nothing and emits no event.
+    let before = env.balance(market.bet_token);
+    let receipt = env.claim_reward(&market, market.claim_badge);
+    assert!(env.events_named(&receipt, "ClaimRewardEvent").is_empty());
+    assert_eq!(env.balance(market.bet_token), before);
+
+    // A user who never bet has no vault, so there is nothing to claim either.
+    env.set_hash_claims_enabled(&market, true).expect_commit_success();
+    let receipt = env.claim_reward_by_hash(&market, "user2");
+    assert!(env.events_named(&receipt, "ClaimRewardEvent").is_empty());
+    assert_eq!(env.balance(market.bet_token), before);
+}
+
+#[test]
+fn test_admin_claim_with_nothing_to_claim_commits_without_payout() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.withdraw_from_vault(&market, "admin1", dec!("200")).expect_commit_success();
+    env.admin_claim(&market, "admin1").expect_commit_success();
+
+    // A second claim and a claim for an unknown admin both commit without a payout.
+    let receipt = env.admin_claim(&market, "admin1");
+    assert!(env.events_named(&receipt, "AdminClaimEvent").is_empty());
+    let receipt = env.admin_claim(&market, "admin2");
+    assert!(env.events_named(&receipt, "AdminClaimEvent").is_empty());
+}
+
+#[test]
+fn test_get_user_bets_across_outcomes() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("30")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .call_method(market.component, "get_user_bets", manifest_args!("nobody".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    // Output 0 is the fee lock inserted by the test runner.
+    let user_bets: Vec<(String, Decimal)> = commit.output(1);
+    let no_bets: Vec<(String, Decimal)> = commit.output(2);
+
+    assert_eq!(user_bets, vec![("outcome1".to_string(), dec!("10")), ("outcome2".to_string(), dec!("20"))]);
+    assert!(no_bets.is_empty());
+}
+
+#[test]
+fn test_get_claimable_balance_matches_reward() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("10")).expect_commit_success();
+    let get_claimable = |env: &mut TestEnv, user_hash: &str| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_claimable_balance", manifest_args!(user_hash.to_string()))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+    assert_eq!(get_claimable(&mut env, "user1"), Decimal::zero());
+
+    let rewards = env.resolve_market_rewards(&market, 1);
+    assert_eq!(rewards, vec![("user1".to_string(), dec!("30"))]);
+    assert_eq!(get_claimable(&mut env, "user1"), dec!("30"));
+    assert_eq!(get_claimable(&mut env, "stranger"), Decimal::zero());
+}
+
+#[test]
+fn test_get_user_vault_balance_after_resolution() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("20")).expect_commit_success();
+
+    let get_vault_balance = |env: &mut TestEnv, user_hash: &str| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_user_vault_balance", manifest_args!(user_hash.to_string()))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+    assert_eq!(get_vault_balance(&mut env, "user1"), Decimal::zero());
+    assert_eq!(get_vault_balance(&mut env, "nobody"), Decimal::zero());
+
+    // Only the winning position on outcome2 pays out, at odds of 3.
+    env.resolve_market(&market, 1).expect_commit_success();
+    assert_eq!(get_vault_balance(&mut env, "user1"), dec!("60"));
+}
+
+#[test]
+fn test_get_claimable_amount_after_resolution_and_void() {
+    let mut env = TestEnv::new();
+    let get_claimable = |env: &mut TestEnv, market: &TestMarket, user_hash: &str| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_claimable_amount", manifest_args!(user_hash.to_string()))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "winner", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "loser", "outcome2", dec!("20")).expect_commit_success();
+    assert_eq!(get_claimable(&mut env, &market, "winner"), Decimal::zero());
+    assert_eq!(get_claimable(&mut env, &market, "loser"), Decimal::zero());
+
+    env.resolve_market(&market, 0).expect_commit_success();
+    assert_eq!(get_claimable(&mut env, &market, "winner"), dec!("20"));
+    assert_eq!(get_claimable(&mut env, &market, "loser"), Decimal::zero());
+    // A user who never bet has nothing to claim.
+    assert_eq!(get_claimable(&mut env, &market, "stranger"), Decimal::zero());
+
+    // Refunds of a voided market are claimable the same way.
+    let voided = env.instantiate(&MarketParams { title: "voided".to_string(), ..MarketParams::default() });
+    env.place_bet(&voided, "user1", "outcome2", dec!("20")).expect_commit_success();
+    env.resolve_market_as_void(&voided).expect_commit_success();
+    assert_eq!(get_claimable(&mut env, &voided, "user1"), dec!("20"));
+}
+
+#[test]
+fn test_get_all_bets() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.place_bet(&market, "user1", "outcome2", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("30")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("5")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_all_bets", manifest_args!())
+        .build();
+    let bets: Vec<(String, String, Decimal)> = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(
+        bets,
+        vec![
+            ("outcome1".to_string(), "user2".to_string(), dec!("25")),
+            ("outcome1".to_string(), "user1".to_string(), dec!("30")),
+            ("outcome2".to_string(), "user1".to_string(), dec!("10")),
+        ]
+    );
+}
+
+#[test]
+fn test_get_bets_for_outcome_paged() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome1", dec!("30")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_bets_for_outcome_paged", manifest_args!("outcome1".to_string(), 0usize, 2usize))
+        .call_method(market.component, "get_bets_for_outcome_paged", manifest_args!("outcome1".to_string(), 2usize, 2usize))
+        .call_method(market.component, "get_bets_for_outcome_paged", manifest_args!("outcome1".to_string(), 5usize, 2usize))
+        .call_method(market.component, "get_bets_for_outcome_paged", manifest_args!("outcome2".to_string(), 0usize, 2usize))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let first_page: (Vec<(String, Decimal)>, usize) = commit.output(1);
+    let last_page: (Vec<(String, Decimal)>, usize) = commit.output(2);
+    let past_the_end: (Vec<(String, Decimal)>, usize) = commit.output(3);
+    let no_bets: (Vec<(String, Decimal)>, usize) = commit.output(4);
+
+    assert_eq!(first_page, (vec![("user1".to_string(), dec!("10")), ("user2".to_string(), dec!("20"))], 3));
+    assert_eq!(last_page, (vec![("user3".to_string(), dec!("30"))], 3));
+    assert_eq!(past_the_end, (vec![], 3));
+    assert_eq!(no_bets, (vec![], 0));
+}
+
+#[test]
+fn test_bet_cost_stays_flat_as_bettors_grow() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+
+    // Bets live in per-user stores, so later bettors shouldn't pay for loading everyone else's bets.
+    let costs: Vec<u32> = (0..200)
+        .map(|index| {
+            let receipt = env.place_bet(&market, &format!("user{}", index), "outcome1", dec!("5"));
+            receipt.expect_commit_success();
+            receipt.fee_summary.total_execution_cost_units_consumed
+        })
+        .collect();
+
+    // The first bets create the market's first entries, so compare against a bet placed once things have settled.
+    // Bettor lists live in stores too, so the component state doesn't grow and the cost stays within noise.
+    let early_cost = costs[10] as u64;
+    let late_cost = costs[199] as u64;
+    assert!(late_cost * 100 <= early_cost * 102, "Bet cost grew from {} to {}.", early_cost, late_cost);
+}
+
+#[test]
+fn test_bettor_pool_excludes_seeded_liquidity() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_bettor_pool", manifest_args!())
+        .call_method(market.component, "get_xrd_vault_balance", manifest_args!())
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let bettor_pool: Decimal = commit.output(1);
+    let vault_balance: Decimal = commit.output(2);
+
+    assert_eq!(bettor_pool, dec!("30"));
+    assert_eq!(vault_balance, dec!("500"));
+}
+
+#[test]
+fn test_top_up_over_max_bet_returns_change() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("80")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("10")).expect_commit_success();
+
+    let before = env.balance(market.bet_token);
+    let receipt = env.place_bet(&market, "user1", "outcome1", dec!("50"));
+    receipt.expect_commit_success();
+    // Only 20 is staked, the other 30 comes back as change.
+    assert_eq!(before - env.balance(market.bet_token), dec!("20"));
+    // The event reports the increment along with the user's and the outcome's running totals.
+    let event: BetPlacedEvent = scrypto_decode(&env.events_named(&receipt, "BetPlacedEvent")[0]).unwrap();
+    assert_eq!((event.amount, event.cumulative_amount, event.outcome_total_staked), (dec!("20"), dec!("100"), dec!("110")));
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .call_method(market.component, "get_total_staked", manifest_args!())
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let user_bets: Vec<(String, Decimal)> = commit.output(1);
+    let total_staked: Decimal = commit.output(2);
+    assert_eq!(user_bets, vec![("outcome1".to_string(), dec!("100"))]);
+    assert_eq!(total_staked, dec!("110"));
+
+    // Once at the maximum, further top-ups are rejected.
+    expect_market_error(env.place_bet(&market, "user1", "outcome1", dec!("10")), "BetAboveMaximum");
+}
+
+#[test]
+fn test_first_bet_over_max_bet_returns_change() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+
+    let before = env.balance(market.bet_token);
+    env.place_bet(&market, "user1", "outcome1", dec!("150")).expect_commit_success();
+    // Only the maximum of 100 is staked, the other 50 comes back as change.
+    assert_eq!(before - env.balance(market.bet_token), dec!("100"));
+}
+
+#[test]
+fn test_min_bet_only_applies_to_first_bet_on_an_outcome() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+
+    // A first bet below the minimum is rejected.
+    expect_market_error(env.place_bet(&market, "user1", "outcome1", dec!("4")), "BetBelowMinimum");
+
+    // Once user1 has a position, they can fill the gap to the maximum with a small top-up.
+    env.place_bet(&market, "user1", "outcome1", dec!("96")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("4")).expect_commit_success();
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .build();
+    let user_bets: Vec<(String, Decimal)> = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(user_bets, vec![("outcome1".to_string(), dec!("100"))]);
+
+    // The minimum still applies to their first bet on another outcome.
+    expect_market_error(env.place_bet(&market, "user1", "outcome2", dec!("4")), "BetBelowMinimum");
+}
+
+#[test]
+fn test_place_bets_dutches_several_outcomes_atomically() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams {
+        outcomes_str: "home,draw,away".to_string(),
+        odds_str: "2,3,6".to_string(),
+        ..Default::default()
+    });
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    let place_bets = |env: &mut TestEnv, bets: Vec<(&str, Decimal)>, amount: Decimal| {
+        let bets: Vec<(String, Decimal)> = bets.into_iter().map(|(outcome, amount)| (outcome.to_string(), amount)).collect();
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account(env.account, market.bet_token, amount)
+            .take_from_worktop(market.bet_token, amount, "bets")
+            .call_method_with_name_lookup(market.component, "place_bets", |lookup| {
+                ("user1".to_string(), bets, lookup.bucket("bets"))
+            })
+            .deposit_batch(env.account)
+            .build();
+        env.execute(manifest)
+    };
+    let get_user_bets = |env: &mut TestEnv| -> Vec<(String, Decimal)> {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    // The amounts must add up to the payment.
+    place_bets(&mut env, vec![("home", dec!("30")), ("draw", dec!("20"))], dec!("60")).expect_commit_failure();
+
+    // Every leg pays out 60, whichever outcome wins.
+    let before = env.balance(market.bet_token);
+    let receipt = place_bets(&mut env, vec![("home", dec!("30")), ("draw", dec!("20")), ("away", dec!("10"))], dec!("60"));
+    receipt.expect_commit_success();
+    assert_eq!(env.events_named(&receipt, "BetPlacedEvent").len(), 3);
+    assert_eq!(before - env.balance(market.bet_token), dec!("60"));
+    assert_eq!(env.balance(market.bet_receipt), dec!("3"));
+    assert_eq!(get_user_bets(&mut env), vec![
+        ("home".to_string(), dec!("30")),
+        ("draw".to_string(), dec!("20")),
+        ("away".to_string(), dec!("10")),
+    ]);
+
+    // A leg that would take user1 past max_bet fails the whole call, so the other legs aren't placed either.
+    place_bets(&mut env, vec![("draw", dec!("10")), ("home", dec!("80"))], dec!("90")).expect_commit_failure();
+    assert_eq!(get_user_bets(&mut env)[1], ("draw".to_string(), dec!("20")));
+    assert_eq!(env.market_details(&market).total_staked, dec!("60"));
+}
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct BetPlacedEvent {
+    market_id: String,
+    sequence: u64,
+    user_hash: String,
+    outcome: String,
+    amount: Decimal,
+    outcome_index: u32,
+    odds: Decimal,
+    cumulative_amount: Decimal,
+    outcome_total_staked: Decimal,
+}
+
+#[test]
+fn test_place_bet_by_index_matches_place_bet() {
+    let mut env = TestEnv::new();
+    let by_name = env.instantiate(&MarketParams { title: "by name".to_string(), ..Default::default() });
+    let by_index = env.instantiate(&MarketParams { title: "by index".to_string(), ..Default::default() });
+    let place_bet_by_index = |env: &mut TestEnv, user_hash: &str, outcome_index: u32, amount: Decimal| {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account(env.account, by_index.bet_token, amount)
+            .take_from_worktop(by_index.bet_token, amount, "bet")
+            .call_method_with_name_lookup(by_index.component, "place_bet_by_index", |lookup| {
+                (user_hash.to_string(), outcome_index, lookup.bucket("bet"))
+            })
+            .deposit_batch(env.account)
+            .build();
+        env.execute(manifest)
+    };
+
+    expect_market_error(place_bet_by_index(&mut env, "user1", 2, dec!("10")), "OutcomeOutOfBounds");
+
+    let by_name_receipt = env.place_bet(&by_name, "user1", "outcome2", dec!("10"));
+    by_name_receipt.expect_commit_success();
+    let by_index_receipt = place_bet_by_index(&mut env, "user1", 1, dec!("10"));
+    by_index_receipt.expect_commit_success();
+    env.place_bet(&by_name, "user2", "outcome1", dec!("150")).expect_commit_success();
+    place_bet_by_index(&mut env, "user2", 0, dec!("150")).expect_commit_success();
+
+    // Both paths emit the outcome's name and index.
+    for receipt in [&by_name_receipt, &by_index_receipt] {
+        let events = env.events_named(receipt, "BetPlacedEvent");
+        let event: BetPlacedEvent = scrypto_decode(&events[0]).unwrap();
+        assert_eq!((event.outcome, event.outcome_index), ("outcome2".to_string(), 1));
+    }
+
+    // Apart from the title, both markets end up in the same state.
+    let (by_name_details, by_index_details) = (env.market_details(&by_name), env.market_details(&by_index));
+    assert_eq!(by_name_details.total_staked, dec!("110"));
+    assert_eq!(MarketDetails { title: by_name_details.title.clone(), ..by_index_details }, by_name_details);
+    for user_hash in ["user1", "user2"] {
+        let manifest = ManifestBuilder::new()
+            .call_method(by_name.component, "get_user_bets", manifest_args!(user_hash.to_string()))
+            .call_method(by_index.component, "get_user_bets", manifest_args!(user_hash.to_string()))
+            .build();
+        let receipt = env.execute(manifest);
+        let commit = receipt.expect_commit_success();
+        let by_name_bets: Vec<(String, Decimal)> = commit.output(1);
+        let by_index_bets: Vec<(String, Decimal)> = commit.output(2);
+        assert_eq!(by_name_bets, by_index_bets);
+    }
+}
+
+#[test]
+fn test_cancel_bet_refunds_user_vault() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+
+    // Cancelling a bet that doesn't exist fails with a clear error.
+    env.cancel_bet(&market, 1, "outcome2").expect_commit_failure();
+
+    env.cancel_bet(&market, 1, "outcome1").expect_commit_success();
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_total_staked", manifest_args!())
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let total_staked: Decimal = commit.output(1);
+    let user_bets: Vec<(String, Decimal)> = commit.output(2);
+    assert_eq!(total_staked, Decimal::zero());
+    assert!(user_bets.is_empty());
+
+    // The refund is claimable with the user's claim badge.
+    let before = env.balance(market.bet_token);
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("50"));
+}
+
+#[test]
+fn test_cancelled_bet_is_replaced_by_the_latest_bet() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome1", dec!("30")).expect_commit_success();
+    let get_bets = |env: &mut TestEnv| -> (Vec<(String, Decimal)>, usize) {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_bets_for_outcome_paged", manifest_args!("outcome1".to_string(), 0usize, 10usize))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    // user3's bet moves into user1's place, and a new bet by user1 goes to the end.
+    env.cancel_bet(&market, 1, "outcome1").expect_commit_success();
+    assert_eq!(get_bets(&mut env), (vec![("user3".to_string(), dec!("30")), ("user2".to_string(), dec!("20"))], 2));
+    env.place_bet(&market, "user1", "outcome1", dec!("5")).expect_commit_success();
+    env.cancel_bet(&market, 3, "outcome1").expect_commit_success();
+    assert_eq!(get_bets(&mut env), (vec![("user1".to_string(), dec!("5")), ("user2".to_string(), dec!("20"))], 2));
+
+    // The remaining bets are still paid on resolution.
+    let rewards = env.resolve_market_rewards(&market, 0);
+    assert_eq!(rewards, vec![("user1".to_string(), dec!("10")), ("user2".to_string(), dec!("40"))]);
+}
+
+#[test]
+fn test_cancel_bet_fails_after_lock() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    env.lock_market(&market).expect_commit_success();
+    env.cancel_bet(&market, 1, "outcome1").expect_commit_failure();
+}
+
+#[test]
+fn test_cancel_bet_requires_the_bettors_claim_badge() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+
+    // A stranger without a claim badge can't cancel anything.
+    let (stranger_key, _, stranger_account) = env.test_runner.new_allocated_account();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(stranger_account, market.claim_badge, dec!("1"))
+        .pop_from_auth_zone("claim_badge")
+        .call_method_with_name_lookup(market.component, "cancel_bet", |lookup| {
+            (lookup.proof("claim_badge"), "outcome1".to_string())
+        })
+        .build();
+    env.execute_as(manifest, &stranger_key).expect_commit_failure();
+
+    // Another bettor's badge only reaches their own bets, so user1's bet stays in place.
+    env.cancel_bet(&market, 2, "outcome1").expect_commit_failure();
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .build();
+    let user_bets: Vec<(String, Decimal)> = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(user_bets, vec![("outcome1".to_string(), dec!("50"))]);
+}
+
+#[test]
+fn test_dispute_window_resolution() {
+    let mut env = TestEnv::new();
+    env.test_runner.set_current_epoch(Epoch::of(1));
+    let market = token_market(&mut env, MarketParams { dispute_window_epochs: 5, ..Default::default() });
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+    let get_pending = |env: &mut TestEnv| -> Option<(u32, Epoch)> {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_pending_resolution", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    // Markets with a dispute window can't be resolved directly, and there is nothing to finalize yet.
+    expect_market_error(env.resolve_market(&market, 0), "DisputeWindowRequired");
+    let result: Result<Vec<(String, Decimal)>, MarketError> = env.finalize_resolution(&market).expect_commit_success().output(1);
+    assert_eq!(result, Err(MarketError::NoPendingResolution));
+
+    // A pending proposal blocks betting and can't be finalized before the window has elapsed.
+    let receipt = env.propose_resolution(&market, 1);
+    assert_eq!(env.events_named(&receipt, "ResolutionProposedEvent").len(), 1);
+    assert_eq!(get_pending(&mut env), Some((1, Epoch::of(1))));
+    expect_market_error(env.place_bet(&market, "user2", "outcome2", dec!("20")), "ResolutionPending");
+    let details = env.market_details(&market);
+    assert_eq!(details.status, MarketStatus::Locked);
+    assert!(details.market_locked);
+
+    // The proposed outcome is public, so stakes can't be pulled before it is finalized either.
+    expect_market_error(env.cancel_bet(&market, 1, "outcome1"), "ResolutionPending");
+    let close_out = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.claim_badge, dec!("1"))
+        .pop_from_auth_zone("claim_badge")
+        .call_method_with_name_lookup(market.component, "close_out", |lookup| (lookup.proof("claim_badge"),))
+        .deposit_batch(env.account)
+        .build();
+    expect_market_error(env.execute(close_out), "ResolutionPending");
+    expect_market_error(env.update_odds(&market, "outcome2", dec!("4")), "ResolutionPending");
+    env.test_runner.set_current_epoch(Epoch::of(5));
+    let result: Result<Vec<(String, Decimal)>, MarketError> = env.finalize_resolution(&market).expect_commit_success().output(1);
+    assert_eq!(result, Err(MarketError::DisputeWindowOpen { finalizable_from: Epoch::of(6) }));
+
+    // Cancelling returns the market to the unresolved state.
+    env.cancel_proposed_resolution(&market).expect_commit_success();
+    assert_eq!(get_pending(&mut env), None);
+    let details = env.market_details(&market);
+    assert!(!details.market_locked);
+    assert!(!details.market_resolved);
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    env.cancel_proposed_resolution(&market).expect_commit_failure();
+
+    // Once the window has elapsed, anyone can finalize and the winners are paid.
+    env.propose_resolution(&market, 0).expect_commit_success();
+    env.test_runner.set_current_epoch(Epoch::of(10));
+    let receipt = env.finalize_resolution(&market);
+    let rewards: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(1);
+    assert_eq!(rewards, Ok(vec![("user1".to_string(), dec!("100"))]));
+    assert_eq!(env.events_named(&receipt, "MarketResolvedEvent").len(), 1);
+    assert!(env.market_details(&market).market_resolved);
+    let before = env.balance(market.bet_token);
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("100"));
+}
+
+#[test]
+fn test_unlock_market_reopens_betting() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.lock_market(&market).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_failure();
+
+    let receipt = env.unlock_market(&market);
+    receipt.expect_commit_success();
+    assert_eq!(env.events_named(&receipt, "MarketUnlockedEvent").len(), 1);
+    env.place_bet(&market, "user1", "outcome1", dec!("50")).expect_commit_success();
+}
+
+#[test]
+fn test_unlock_market_fails_after_resolution() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.lock_market(&market).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+    env.unlock_market(&market).expect_commit_failure();
+}
+
+#[test]
+fn test_close_out_returns_every_position() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams {
+        outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+        odds_str: "2,3,4".to_string(),
+        ..Default::default()
+    });
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome3", dec!("30")).expect_commit_success();
+    let before = env.balance(market.bet_token);
+
+    let close_out = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.claim_badge, dec!("1"))
+        .pop_from_auth_zone("claim_badge")
+        .call_method_with_name_lookup(market.component, "close_out", |lookup| {
+            (lookup.proof("claim_badge"),)
+        })
+        .deposit_batch(env.account)
+        .build();
+    let receipt = env.execute(close_out.clone());
+    receipt.expect_commit_success();
+    assert_eq!(env.events_named(&receipt, "BetCancelledEvent").len(), 3);
+    assert_eq!(env.balance(market.bet_token) - before, dec!("60"));
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_total_staked", manifest_args!())
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let total_staked: Decimal = commit.output(1);
+    let user_bets: Vec<(String, Decimal)> = commit.output(2);
+    assert_eq!(total_staked, Decimal::zero());
+    assert!(user_bets.is_empty());
+
+    // Nothing is left to close out.
+    env.execute(close_out).expect_commit_failure();
+}
+
+#[test]
+fn test_event_sequence_increments() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let get_sequence = |env: &mut TestEnv| -> u64 {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_current_sequence", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    // The `MarketCreatedEvent` is sequence 1.
+    assert_eq!(get_sequence(&mut env), 1);
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    assert_eq!(get_sequence(&mut env), 2);
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_success();
+    assert_eq!(get_sequence(&mut env), 3);
+    env.lock_market(&market).expect_commit_success();
+    assert_eq!(get_sequence(&mut env), 4);
+}
+
+// Mirrors of the blueprint's events, used to decode event payloads.
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct VaultDepositEvent {
+    market_id: String,
+    sequence: u64,
+    amount: Decimal,
+    new_balance: Decimal,
+}
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct VaultWithdrawalEvent {
+    market_id: String,
+    sequence: u64,
+    amount: Decimal,
+    remaining_balance: Decimal,
+}
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct AdminClaimEvent {
+    market_id: String,
+    sequence: u64,
+    amount: Decimal,
+}
+
+// Mirror of the blueprint's `MarketDetails`, used to decode `get_market_details`.
+#[allow(dead_code)]
+#[derive(ScryptoSbor, Debug, PartialEq)]
+struct MarketDetails {
+    title: String,
+    outcomes: Vec<String>,
+    odds: Vec<Decimal>,
+    total_staked: Decimal,
+    min_bet: Decimal,
+    max_bet: Decimal,
+    market_locked: bool,
+    market_resolved: bool,
+    xrd_vault_balance: Decimal,
+    betting_deadline: Option<Epoch>,
+    payout_mode: PayoutMode,
+    fee_percent: Decimal,
+    status: MarketStatus,
+    description: String,
+    resolution_source: String,
+    implied_odds: Vec<Decimal>,
+}
+
+// Mirror of the blueprint's `BootstrapState`, used to decode `bootstrap_state`.
+#[derive(ScryptoSbor)]
+struct BootstrapState {
+    details: MarketDetails,
+    status: MarketStatus,
+    current_sequence: u64,
+    effective_fee_percent: Decimal,
+    outcome_balances: Vec<Decimal>,
+    outcome_liabilities: Vec<Decimal>,
+    bet_counts: Vec<usize>,
+    bets: Vec<Vec<(String, Decimal)>>,
+    user_vault_balances: Vec<(String, Decimal)>,
+    user_balances: Vec<(String, Decimal)>,
+}
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct MarketResolvedAsVoidEvent {
+    market_id: String,
+    sequence: u64,
+    forced: bool,
+}
+
+#[test]
+fn test_withdraw_all_from_vault_after_resolution() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+
+    let get_vault_balance = |env: &mut TestEnv| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_xrd_vault_balance", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+    let leftover = get_vault_balance(&mut env);
+
+    // Only the super-admin can sweep the treasury.
+    let account = env.account;
+    let sweep = |badge: ResourceAddress| {
+        ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account, badge, dec!("1"))
+            .call_method(market.component, "withdraw_all_from_vault", manifest_args!("admin1".to_string()))
+            .build()
+    };
+    env.execute(sweep(market.admin_badge)).expect_commit_failure();
+    let receipt = env.execute(sweep(market.super_badge));
+    let event: VaultWithdrawalEvent = scrypto_decode(&env.events_named(&receipt, "VaultWithdrawalEvent")[0]).unwrap();
+    assert_eq!(event.amount, leftover);
+    assert_eq!(event.remaining_balance, Decimal::zero());
+    assert_eq!(get_vault_balance(&mut env), Decimal::zero());
+
+    // Nothing is left for a second sweep.
+    env.execute(sweep(market.super_badge)).expect_commit_failure();
+}
+
+// Mirror of the blueprint's `UnclaimedSweptEvent`, used to decode sweep events.
+#[derive(ScryptoSbor)]
+struct UnclaimedSweptEvent {
+    market_id: String,
+    sequence: u64,
+    total_swept: Decimal,
+    users_affected: u32,
+}
+
+#[test]
+fn test_sweep_unclaimed_after_claim_deadline() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams { claim_deadline_epochs: Some(10), ..Default::default() });
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+    let sweep = |env: &mut TestEnv, offset: usize, limit: usize| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "sweep_unclaimed", manifest_args!(offset, limit))
+            .build();
+        env.execute(manifest)
+    };
+
+    // Nothing can be swept before resolution, nor until the deadline has passed.
+    sweep(&mut env, 0, 10).expect_commit_failure();
+    env.test_runner.set_current_epoch(Epoch::of(10));
+    env.resolve_market(&market, 0).expect_commit_success();
+    let before = env.balance(market.bet_token);
+    env.claim_reward_with_badge(&market, 1).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("20"));
+    env.test_runner.set_current_epoch(Epoch::of(20));
+    sweep(&mut env, 0, 10).expect_commit_failure();
+
+    // Sweeping one user per page: user1 already claimed in time, so only user2's stale reward is swept.
+    env.test_runner.set_current_epoch(Epoch::of(21));
+    let receipt = sweep(&mut env, 0, 1);
+    let user_count: usize = receipt.expect_commit_success().output(2);
+    assert_eq!(user_count, 2);
+    let event: UnclaimedSweptEvent = scrypto_decode(&env.events_named(&receipt, "UnclaimedSweptEvent")[0]).unwrap();
+    assert_eq!(event.total_swept, dec!("0"));
+    assert_eq!(event.users_affected, 0);
+    let receipt = sweep(&mut env, 1, 1);
+    let event: UnclaimedSweptEvent = scrypto_decode(&env.events_named(&receipt, "UnclaimedSweptEvent")[0]).unwrap();
+    assert_eq!(event.total_swept, dec!("40"));
+    assert_eq!(event.users_affected, 1);
+
+    let before = env.balance(market.bet_token);
+    env.claim_reward_with_badge(&market, 2).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token), before);
+    env.admin_claim(&market, "house").expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("40"));
+
+    // Markets without a claim deadline can't be swept at all.
+    let open_ended = token_market(&mut env, MarketParams { title: "open-ended".to_string(), ..Default::default() });
+    env.resolve_market(&open_ended, 0).expect_commit_success();
+    env.test_runner.set_current_epoch(Epoch::of(1_000));
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, open_ended.admin_badge, dec!("1"))
+        .call_method(open_ended.component, "sweep_unclaimed", manifest_args!(0usize, 10usize))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+}
+
+#[test]
+fn test_vault_withdrawal_and_admin_claim_emit_events() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let receipt = env.deposit_to_vault(&market, dec!("300"));
+    let events = env.events_named(&receipt, "VaultDepositEvent");
+    assert_eq!(events.len(), 1);
+    let event: VaultDepositEvent = scrypto_decode(&events[0]).unwrap();
+    assert_eq!(event.amount, dec!("300"));
+    assert_eq!(event.new_balance, dec!("300"));
+    let receipt = env.deposit_to_vault(&market, dec!("200"));
+    let event: VaultDepositEvent = scrypto_decode(&env.events_named(&receipt, "VaultDepositEvent")[0]).unwrap();
+    assert_eq!(event.new_balance, dec!("500"));
+
+    // Zero and negative withdrawals are rejected.
+    env.withdraw_from_vault(&market, "admin1", dec!("0")).expect_commit_failure();
+    env.withdraw_from_vault(&market, "admin1", dec!("-1")).expect_commit_failure();
+
+    let receipt = env.withdraw_from_vault(&market, "admin1", dec!("200"));
+    let events = env.events_named(&receipt, "VaultWithdrawalEvent");
+    assert_eq!(events.len(), 1);
+    let event: VaultWithdrawalEvent = scrypto_decode(&events[0]).unwrap();
+    assert_eq!(event.amount, dec!("200"));
+    assert_eq!(event.remaining_balance, dec!("300"));
+
+    let receipt = env.admin_claim(&market, "admin1");
+    let events = env.events_named(&receipt, "AdminClaimEvent");
+    assert_eq!(events.len(), 1);
+    let event: AdminClaimEvent = scrypto_decode(&events[0]).unwrap();
+    assert_eq!(event.amount, dec!("200"));
+}
+
+#[test]
+fn test_kelly_stake() {
+    let mut env = TestEnv::new();
+    let manifest = ManifestBuilder::new()
+        .call_function(env.package_address, "PredictionMarket", "kelly_stake", manifest_args!(dec!("100"), dec!("2"), dec!("0.6")))
+        .call_function(env.package_address, "PredictionMarket", "kelly_stake", manifest_args!(dec!("100"), dec!("2"), dec!("0.4")))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let favourable: Decimal = commit.output(1);
+    let unfavourable: Decimal = commit.output(2);
+
+    // A 60% chance at even money is a 20% edge, so Kelly stakes 20% of the bankroll.
+    assert_eq!(favourable, dec!("20"));
+    assert_eq!(unfavourable, Decimal::zero());
+}
+
+#[test]
+fn test_betting_deadline() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams { betting_deadline: Some(Epoch::of(10)), ..Default::default() });
+
+    env.test_runner.set_current_epoch(Epoch::of(10));
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    env.test_runner.set_current_epoch(Epoch::of(11));
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_failure();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_betting_deadline", manifest_args!())
+        .build();
+    let receipt = env.execute(manifest);
+    let deadline: Option<Epoch> = receipt.expect_commit_success().output(1);
+    assert_eq!(deadline, Some(Epoch::of(10)));
+}
+
+#[test]
+fn test_parimutuel_payouts_never_exceed_stakes() {
+    for fee_percent in [dec!("0"), dec!("5")] {
+        let mut env = TestEnv::new();
+        let market = env.instantiate(&MarketParams {
+            outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+            odds_str: "2,3,4".to_string(),
+            payout_mode: PayoutMode::Parimutuel,
+            fee_percent,
+            ..Default::default()
+        });
+        env.place_bet(&market, "user1", "outcome1", dec!("30")).expect_commit_success();
+        env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+        env.place_bet(&market, "user3", "outcome2", dec!("50")).expect_commit_success();
+        env.place_bet(&market, "user4", "outcome3", dec!("7")).expect_commit_success();
+
+        // No treasury funding is needed: the pool pays for itself.
+        let rewards = env.resolve_market_rewards(&market, 0);
+        let total_paid = rewards.iter().fold(Decimal::zero(), |total, (_, reward)| total + *reward);
+        let distributable = dec!("107") * (dec!("100") - fee_percent) / dec!("100");
+        assert!(total_paid <= dec!("107"));
+        assert!(total_paid <= distributable);
+        assert_eq!(rewards[0].1, dec!("30") / dec!("50") * distributable);
+        assert_eq!(rewards[1].1, dec!("20") / dec!("50") * distributable);
+    }
+}
+
+#[derive(ScryptoSbor)]
+struct MarketResolvedEvent {
+    market_id: String,
+    sequence: u64,
+    winning_outcome: u32,
+    house_fee: Decimal,
+    haircut_ratio: Option<Decimal>,
+}
+
+#[test]
+fn test_fixed_odds_house_fee_is_deducted_from_payouts() {
+    let mut env = TestEnv::new();
+    let fee_percent = dec!("2.5");
+    let market = token_market(&mut env, MarketParams {
+        odds_str: "1.333333333333333333,3".to_string(),
+        fee_percent,
+        ..Default::default()
+    });
+    assert_eq!(env.market_details(&market).fee_percent, fee_percent);
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("7")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("11")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome2", dec!("20")).expect_commit_success();
+
+    // Each payout is `stake * odds` minus the fee on it, including the fee's rounding.
+    let gross = [dec!("7") * dec!("1.333333333333333333"), dec!("11") * dec!("1.333333333333333333")];
+    let fees = gross.map(|amount| amount * fee_percent / dec!("100"));
+    let receipt = env.resolve_market(&market, 0);
+    let rewards: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+    assert_eq!(
+        rewards.unwrap(),
+        vec![("user1".to_string(), gross[0] - fees[0]), ("user2".to_string(), gross[1] - fees[1])]
+    );
+    let event: MarketResolvedEvent = scrypto_decode(&env.events_named(&receipt, "MarketResolvedEvent")[0]).unwrap();
+    assert_eq!(event.house_fee, fees[0] + fees[1]);
+
+    // The admin claims exactly the accumulated fees.
+    let before = env.balance(market.bet_token);
+    env.admin_claim(&market, "house").expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, fees[0] + fees[1]);
+}
+
+#[test]
+fn test_rewards_are_ordered_deterministically() {
+    // The same market resolved in two separate ledgers, with the winners listed in a different order.
+    let resolve = |winning_outcomes: Vec<u32>| -> Vec<(String, Decimal)> {
+        let mut env = TestEnv::new();
+        let market = env.instantiate(&MarketParams::default());
+        env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+        for (user, outcome) in [("user3", "outcome2"), ("user1", "outcome1"), ("user4", "outcome2"), ("user2", "outcome1")] {
+            env.place_bet(&market, user, outcome, dec!("10")).expect_commit_success();
+        }
+        let result: Result<Vec<(String, Decimal)>, MarketError> =
+            env.resolve_market_multi(&market, winning_outcomes).expect_commit_success().output(2);
+        result.unwrap()
+    };
+
+    // Rewards follow the outcome order, then the order the bets were placed.
+    let expected: Vec<(String, Decimal)> = vec![
+        ("user1".to_string(), dec!("10")),
+        ("user2".to_string(), dec!("10")),
+        ("user3".to_string(), dec!("15")),
+        ("user4".to_string(), dec!("15")),
+    ];
+    assert_eq!(resolve(vec![0, 1]), expected);
+    assert_eq!(resolve(vec![1, 0]), expected);
+}
+
+#[test]
+fn test_rewards_are_rounded_to_bet_token_divisibility() {
+    let mut env = TestEnv::new();
+    let account = env.account;
+    let token = env.test_runner.create_fungible_resource(dec!("100000"), 2, account);
+    let market = env.instantiate(&MarketParams {
+        odds_str: "1.333333333333333333,3".to_string(),
+        bet_token: token,
+        fee_percent: dec!("10"),
+        ..Default::default()
+    });
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    // 10 * 1.333... is rounded down to 13.33, of which 11.99 goes to the winner and 1.34 to the house.
+    let rewards = env.resolve_market_rewards(&market, 0);
+    assert_eq!(rewards, vec![("user1".to_string(), dec!("11.99"))]);
+    let before = env.balance(token);
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+    assert_eq!(env.balance(token) - before, dec!("11.99"));
+    let before = env.balance(token);
+    env.admin_claim(&market, "house").expect_commit_success();
+    assert_eq!(env.balance(token) - before, dec!("1.34"));
+}
+
+#[test]
+fn test_market_requires_two_non_empty_outcomes() {
+    let mut env = TestEnv::new();
+    let single_outcome = MarketParams { outcomes_str: "outcome1".to_string(), odds_str: "2".to_string(), ..Default::default() };
+    env.instantiate_receipt(&single_outcome).expect_commit_failure();
+
+    // A trailing comma produces an empty outcome.
+    let empty_outcome = MarketParams { outcomes_str: "outcome1,outcome2,".to_string(), odds_str: "2,3,4".to_string(), ..Default::default() };
+    env.instantiate_receipt(&empty_outcome).expect_commit_failure();
+}
+
+#[test]
+fn test_duplicate_outcomes_are_rejected_after_trimming() {
+    let mut env = TestEnv::new();
+    let with_outcomes = |outcomes_str: &str| MarketParams {
+        outcomes_str: outcomes_str.to_string(),
+        odds_str: "2,3".to_string(),
+        ..Default::default()
+    };
+    env.instantiate_receipt(&with_outcomes("win, win")).expect_commit_failure();
+    env.instantiate_receipt(&with_outcomes("Win,win")).expect_commit_failure();
+    env.instantiate_receipt(&with_outcomes(" a , a ")).expect_commit_failure();
+
+    // Outcomes are stored trimmed.
+    let market = env.instantiate(&with_outcomes(" win , lose "));
+    assert_eq!(env.market_details(&market).outcomes, vec!["win".to_string(), "lose".to_string()]);
+}
+
+#[test]
+fn test_case_insensitive_outcome_matching() {
+    let mut env = TestEnv::new();
+    let yes_no = MarketParams { outcomes_str: "Yes,No".to_string(), ..Default::default() };
+    let exact = env.instantiate(&yes_no);
+    expect_market_error(env.place_bet(&exact, "user1", "YES", dec!("10")), "UnknownOutcome");
+
+    let relaxed = env.instantiate(&MarketParams { title: "relaxed".to_string(), case_insensitive_outcomes: true, ..yes_no });
+    env.place_bet(&relaxed, "user1", "YES", dec!("10")).expect_commit_success();
+    env.place_bet(&relaxed, "user1", "yes", dec!("5")).expect_commit_success();
+    let manifest = ManifestBuilder::new()
+        .call_method(relaxed.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .build();
+    let user_bets: Vec<(String, Decimal)> = env.execute(manifest).expect_commit_success().output(1);
+    // The bets are recorded against the stored outcome.
+    assert_eq!(user_bets, vec![("Yes".to_string(), dec!("15"))]);
+
+    // "Yes" and "yes" can't coexist in either mode.
+    let clashing = MarketParams { outcomes_str: "Yes,yes".to_string(), case_insensitive_outcomes: true, ..Default::default() };
+    env.instantiate_receipt(&clashing).expect_commit_failure();
+}
+
+#[test]
+fn test_malformed_and_huge_odds_are_rejected() {
+    let mut env = TestEnv::new();
+    let with_odds = |outcomes_str: &str, odds_str: &str| MarketParams {
+        outcomes_str: outcomes_str.to_string(),
+        odds_str: odds_str.to_string(),
+        ..Default::default()
+    };
+    env.instantiate_receipt(&with_odds("outcome1,outcome2,outcome3", "2,,3")).expect_commit_failure();
+    env.instantiate_receipt(&with_odds("outcome1,outcome2", "2,abc")).expect_commit_failure();
+    env.instantiate_receipt(&with_odds("outcome1,outcome2", "2,1000000000000")).expect_commit_failure();
+
+    // The ceiling itself is accepted, and also applies when odds are updated.
+    let market = env.instantiate(&with_odds("outcome1,outcome2", "2,1000"));
+    env.update_odds(&market, "outcome1", dec!("1000.01")).expect_commit_failure();
+}
+
+#[test]
+fn test_fee_percent_is_capped() {
+    let mut env = TestEnv::new();
+    let with_fee = |fee_percent: Decimal| MarketParams { fee_percent, ..Default::default() };
+    env.instantiate_receipt(&with_fee(dec!("10"))).expect_commit_success();
+    env.instantiate_receipt(&with_fee(dec!("10.01"))).expect_commit_failure();
+    env.instantiate_receipt(&with_fee(dec!("-1"))).expect_commit_failure();
+}
+
+#[test]
+fn test_depth_data_for_spread_bets() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams {
+        outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+        odds_str: "2,4,5".to_string(),
+        ..Default::default()
+    });
+    env.place_bet(&market, "user1", "outcome1", dec!("30")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_depth_data", manifest_args!())
+        .build();
+    let depth_data: Vec<(String, Decimal, Decimal, Decimal)> = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(
+        depth_data,
+        vec![
+            ("outcome1".to_string(), dec!("30"), dec!("0.5"), dec!("60")),
+            ("outcome2".to_string(), dec!("10"), dec!("0.25"), dec!("40")),
+            ("outcome3".to_string(), Decimal::zero(), dec!("0.2"), Decimal::zero()),
+        ]
+    );
+}
+
+#[test]
+fn test_outcome_staked_tracks_vault_balances() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams {
+        outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+        odds_str: "2,3,4".to_string(),
+        ..Default::default()
+    });
+    let outcomes = ["outcome1", "outcome2", "outcome3"];
+    let get_staked = |env: &mut TestEnv| -> Vec<(Decimal, Decimal)> {
+        let mut manifest = ManifestBuilder::new();
+        for outcome in outcomes {
+            manifest = manifest
+                .call_method(market.component, "get_outcome_staked", manifest_args!(outcome.to_string()))
+                .call_method(market.component, "get_outcome_balance", manifest_args!(outcome.to_string()));
+        }
+        let receipt = env.execute(manifest.build());
+        let commit = receipt.expect_commit_success();
+        (0..outcomes.len()).map(|index| (commit.output(2 * index + 1), commit.output(2 * index + 2))).collect()
+    };
+
+    // New bets, top-ups and a cancellation.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("15")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome2", dec!("5")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome3", dec!("30")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("7")).expect_commit_success();
+    env.cancel_bet(&market, 3, "outcome2").expect_commit_success();
+
+    let staked = get_staked(&mut env);
+    for (tracked, vault_balance) in &staked {
+        assert_eq!(tracked, vault_balance);
+    }
+    assert_eq!(staked.iter().map(|(tracked, _)| *tracked).collect::<Vec<_>>(), vec![dec!("25"), dec!("27"), dec!("30")]);
+    let total_staked = staked.iter().fold(Decimal::zero(), |total, (tracked, _)| total + *tracked);
+    assert_eq!(total_staked, env.market_details(&market).total_staked);
+
+    // Voiding refunds every stake, so the tracked totals reset with `total_staked`.
+    env.resolve_market_as_void(&market).expect_commit_success();
+    assert!(get_staked(&mut env).iter().all(|(tracked, _)| tracked.is_zero()));
+}
+
+#[test]
+fn test_get_unique_bettor_count() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams {
+        outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+        odds_str: "2,3,4".to_string(),
+        ..Default::default()
+    });
+    let get_count = |env: &mut TestEnv| -> usize {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_unique_bettor_count", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+    assert_eq!(get_count(&mut env), 0);
+
+    // user1 bets on every outcome and tops one up, user2 bets on two outcomes.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome3", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("5")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome3", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome3", dec!("10")).expect_commit_success();
+    assert_eq!(get_count(&mut env), 3);
+
+    // user3 drops out once their only bet is cancelled.
+    env.cancel_bet(&market, 3, "outcome3").expect_commit_success();
+    assert_eq!(get_count(&mut env), 2);
+}
+
+#[test]
+fn test_staked_breakdown_sums_to_total_staked() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("25")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome1", dec!("15")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_staked_breakdown", manifest_args!())
+        .call_method(market.component, "get_total_staked", manifest_args!())
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let breakdown: Vec<(String, Decimal)> = commit.output(1);
+    let total_staked: Decimal = commit.output(2);
+
+    assert_eq!(breakdown, vec![("outcome1".to_string(), dec!("25")), ("outcome2".to_string(), dec!("25"))]);
+    let breakdown_total = breakdown.iter().fold(Decimal::zero(), |total, (_, balance)| total + *balance);
+    assert_eq!(breakdown_total, total_staked);
+}
+
+#[test]
+fn test_get_all_outcome_balances_and_stakes() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams {
+        outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+        odds_str: "2,3,4".to_string(),
+        ..Default::default()
+    });
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("5")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome3", dec!("30")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_all_outcome_balances", manifest_args!())
+        .call_method(market.component, "get_outcome_stakes", manifest_args!())
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let balances: Vec<(String, Decimal)> = commit.output(1);
+    let stakes: Vec<(String, Decimal, u32)> = commit.output(2);
+
+    assert_eq!(
+        balances,
+        vec![
+            ("outcome1".to_string(), dec!("35")),
+            ("outcome2".to_string(), Decimal::zero()),
+            ("outcome3".to_string(), dec!("30")),
+        ]
+    );
+    // The breakdown covers every stake in the market.
+    let breakdown_total = balances.iter().fold(Decimal::zero(), |total, (_, balance)| total + *balance);
+    assert_eq!(breakdown_total, env.market_details(&market).total_staked);
+    // user1's top-up doesn't count as a second bettor.
+    assert_eq!(
+        stakes,
+        vec![
+            ("outcome1".to_string(), dec!("35"), 2),
+            ("outcome2".to_string(), Decimal::zero(), 0),
+            ("outcome3".to_string(), dec!("30"), 1),
+        ]
+    );
+}
+
+#[test]
+fn test_get_potential_payout() {
+    let mut env = TestEnv::new();
+    let get_payout = |env: &mut TestEnv, market: &TestMarket, outcome: &str, amount: Decimal| {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_potential_payout", manifest_args!(outcome.to_string(), amount))
+            .build();
+        env.execute(manifest)
+    };
+
+    // Fixed odds pay `amount * odds` minus the 10% fee.
+    let fixed = env.instantiate(&MarketParams { fee_percent: dec!("10"), ..Default::default() });
+    let payout: Decimal = get_payout(&mut env, &fixed, "outcome2", dec!("10")).expect_commit_success().output(1);
+    assert_eq!(payout, dec!("27"));
+    expect_market_error(get_payout(&mut env, &fixed, "outcome2", dec!("4")), "BetBelowMinimum");
+    expect_market_error(get_payout(&mut env, &fixed, "outcome2", dec!("101")), "BetAboveMaximum");
+    expect_market_error(get_payout(&mut env, &fixed, "outcome3", dec!("10")), "UnknownOutcome");
+
+    // Parimutuel previews the bet's share of the pool including the bet itself: 10 / 20 of 50.
+    let parimutuel = env.instantiate(&MarketParams {
+        title: "parimutuel".to_string(),
+        payout_mode: PayoutMode::Parimutuel,
+        ..Default::default()
+    });
+    env.place_bet(&parimutuel, "user1", "outcome1", dec!("30")).expect_commit_success();
+    env.place_bet(&parimutuel, "user2", "outcome2", dec!("10")).expect_commit_success();
+    let payout: Decimal = get_payout(&mut env, &parimutuel, "outcome2", dec!("10")).expect_commit_success().output(1);
+    assert_eq!(payout, dec!("25"));
+}
+
+#[test]
+fn test_volume_fee_tier_applies_at_resolution() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams {
+        max_bet: dec!("1000"),
+        payout_mode: PayoutMode::Parimutuel,
+        fee_percent: dec!("10"),
+        ..Default::default()
+    });
+    let get_fee = |env: &mut TestEnv| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_effective_fee_percent", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    env.place_bet(&market, "user1", "outcome1", dec!("600")).expect_commit_success();
+    assert_eq!(get_fee(&mut env), dec!("10"));
+
+    // Crossing 1,000 of volume cuts the fee to 75% of the configured 10%.
+    env.place_bet(&market, "user2", "outcome2", dec!("600")).expect_commit_success();
+    assert_eq!(get_fee(&mut env), dec!("7.5"));
+
+    let rewards = env.resolve_market_rewards(&market, 0);
+    assert_eq!(rewards, vec![("user1".to_string(), dec!("1110"))]);
+    let receipt = env.admin_claim(&market, "house");
+    let events = env.events_named(&receipt, "AdminClaimEvent");
+    let event: AdminClaimEvent = scrypto_decode(&events[0]).unwrap();
+    assert_eq!(event.amount, dec!("90"));
+}
+
+#[test]
+fn test_self_exclusion_and_reinstatement() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.test_runner.set_current_epoch(Epoch::of(1));
+
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.self_exclude(&market, 1).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_failure();
+    env.place_bet(&market, "user2", "outcome1", dec!("10")).expect_commit_success();
+
+    let reinstate = |env: &mut TestEnv| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "reinstate_user", manifest_args!("user1".to_string()))
+            .build();
+        env.execute(manifest)
+    };
+
+    // The cooldown has to pass first.
+    reinstate(&mut env).expect_commit_failure();
+    env.test_runner.set_current_epoch(Epoch::of(1 + 288));
+    reinstate(&mut env).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+}
+
+#[test]
+fn test_self_exclude_only_excludes_the_badge_owner() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_success();
+
+    // A stranger without a claim badge can't exclude anyone.
+    let (stranger_key, _, stranger_account) = env.test_runner.new_allocated_account();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(stranger_account, market.claim_badge, dec!("1"))
+        .pop_from_auth_zone("claim_badge")
+        .call_method_with_name_lookup(market.component, "self_exclude", |lookup| (lookup.proof("claim_badge"),))
+        .build();
+    env.execute_as(manifest, &stranger_key).expect_commit_failure();
+
+    // user2's badge excludes user2, never user1.
+    env.self_exclude(&market, 2).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_failure();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+}
+
+#[test]
+fn test_fixed_odds_and_parimutuel_with_same_bets() {
+    let mut payouts = Vec::new();
+    for payout_mode in [PayoutMode::FixedOdds, PayoutMode::Parimutuel] {
+        let mut env = TestEnv::new();
+        let market = env.instantiate(&MarketParams { payout_mode, ..Default::default() });
+        env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+        env.place_bet(&market, "user1", "outcome1", dec!("30")).expect_commit_success();
+        env.place_bet(&market, "user2", "outcome1", dec!("10")).expect_commit_success();
+        env.place_bet(&market, "user3", "outcome2", dec!("60")).expect_commit_success();
+        payouts.push(env.resolve_market_rewards(&market, 0));
+    }
+
+    // Fixed odds pay `stake * odds` (odds of 2 for outcome1).
+    assert_eq!(payouts[0], vec![("user1".to_string(), dec!("60")), ("user2".to_string(), dec!("20"))]);
+    // Parimutuel shares the whole pool of 100 in proportion to the winning stakes of 30 and 10.
+    assert_eq!(payouts[1], vec![("user1".to_string(), dec!("75")), ("user2".to_string(), dec!("25"))]);
+}
+
+#[test]
+fn test_market_details_reflect_lock_state() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    let details = env.market_details(&market);
+    assert_eq!(details.title, "title");
+    assert_eq!(details.total_staked, dec!("10"));
+    assert_eq!((details.min_bet, details.max_bet), (dec!("5"), dec!("100")));
+    assert_eq!(details.xrd_vault_balance, dec!("500"));
+    assert_eq!(details.payout_mode, PayoutMode::FixedOdds);
+    assert!(!details.market_locked);
+    assert!(!details.market_resolved);
+
+    env.lock_market(&market).expect_commit_success();
+    assert!(env.market_details(&market).market_locked);
+}
+
+#[test]
+fn test_update_odds_before_lock() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.update_odds(&market, "outcome2", dec!("2.5")).expect_commit_success();
+    // Odds must stay above 1.
+    env.update_odds(&market, "outcome2", dec!("1")).expect_commit_failure();
+
+    assert_eq!(env.market_details(&market).odds, vec![dec!("2"), dec!("2.5")]);
+
+    // Odds are frozen once the market is locked.
+    env.lock_market(&market).expect_commit_success();
+    expect_market_error(env.update_odds(&market, "outcome2", dec!("3")), "MarketLocked");
+}
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct AllOddsUpdatedEvent {
+    market_id: String,
+    sequence: u64,
+    old_odds: Vec<Decimal>,
+    new_odds: Vec<Decimal>,
+}
+
+#[test]
+fn test_update_all_odds_only_before_first_bet() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let update_all_odds = |env: &mut TestEnv, new_odds: Vec<Decimal>| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "update_all_odds", manifest_args!(new_odds))
+            .build();
+        env.execute(manifest)
+    };
+
+    // The new odds are validated like at instantiation.
+    update_all_odds(&mut env, vec![dec!("1.5")]).expect_commit_failure();
+    update_all_odds(&mut env, vec![dec!("1.5"), dec!("1")]).expect_commit_failure();
+
+    let receipt = update_all_odds(&mut env, vec![dec!("1.5"), dec!("3")]);
+    receipt.expect_commit_success();
+    let events = env.events_named(&receipt, "AllOddsUpdatedEvent");
+    assert_eq!(events.len(), 1);
+    let event: AllOddsUpdatedEvent = scrypto_decode(&events[0]).unwrap();
+    assert_eq!(event.old_odds, vec![dec!("2"), dec!("3")]);
+    assert_eq!(event.new_odds, vec![dec!("1.5"), dec!("3")]);
+    assert_eq!(env.market_details(&market).odds, vec![dec!("1.5"), dec!("3")]);
+
+    // Once a bet is placed the odds can only move one outcome at a time with update_odds.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    expect_market_error(update_all_odds(&mut env, vec![dec!("2"), dec!("2")]), "BetsAlreadyPlaced");
+    assert_eq!(env.market_details(&market).odds, vec![dec!("1.5"), dec!("3")]);
+}
+
+#[test]
+fn test_pause_and_unpause_betting() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let admin_call = |env: &mut TestEnv, method: &str| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, method, manifest_args!())
+            .build();
+        env.execute(manifest)
+    };
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    // Only the admin can pause.
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "pause_market", manifest_args!())
+        .build();
+    env.execute(manifest).expect_commit_failure();
+
+    let receipt = admin_call(&mut env, "pause_market");
+    receipt.expect_commit_success();
+    assert_eq!(env.events_named(&receipt, "MarketPausedEvent").len(), 1);
+    expect_market_error(env.place_bet(&market, "user2", "outcome1", dec!("10")), "MarketPaused");
+    expect_market_error(admin_call(&mut env, "pause_market"), "MarketPaused");
+
+    // A pause isn't a lock: getters and cancellations still work.
+    let details = env.market_details(&market);
+    assert!(!details.market_locked);
+    assert_eq!(details.total_staked, dec!("10"));
+    env.cancel_bet(&market, 1, "outcome1").expect_commit_success();
+
+    let receipt = admin_call(&mut env, "unpause_market");
+    receipt.expect_commit_success();
+    assert_eq!(env.events_named(&receipt, "MarketUnpausedEvent").len(), 1);
+    env.place_bet(&market, "user2", "outcome1", dec!("10")).expect_commit_success();
+    admin_call(&mut env, "unpause_market").expect_commit_failure();
+}
+
+#[test]
+fn test_lock_outcome_keeps_other_outcomes_open() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let lock_outcome = |env: &mut TestEnv, outcome: &str| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "lock_outcome", manifest_args!(outcome.to_string()))
+            .build();
+        env.execute(manifest)
+    };
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    // Only the admin can lock an outcome, and only one that exists.
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "lock_outcome", manifest_args!("outcome1".to_string()))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+    expect_market_error(lock_outcome(&mut env, "outcome3"), "UnknownOutcome");
+
+    let receipt = lock_outcome(&mut env, "outcome1");
+    receipt.expect_commit_success();
+    assert_eq!(env.events_named(&receipt, "OutcomeLockedEvent").len(), 1);
+
+    expect_market_error(env.place_bet(&market, "user2", "outcome1", dec!("10")), "OutcomeLocked");
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_success();
+
+    // Bets placed before the lock still pay out.
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+    assert_eq!(env.market_details(&market).status, MarketStatus::Resolved { winning_outcome: 0 });
+}
+
+#[test]
+fn test_get_implied_probabilities() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams { odds_str: "2,4".to_string(), ..Default::default() });
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_implied_probabilities", manifest_args!(false))
+        .call_method(market.component, "get_implied_probabilities", manifest_args!(true))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let raw: Vec<(String, Decimal)> = commit.output(1);
+    let normalized: Vec<(String, Decimal)> = commit.output(2);
+
+    assert_eq!(raw, vec![("outcome1".to_string(), dec!("0.5")), ("outcome2".to_string(), dec!("0.25"))]);
+    // Without the overround the probabilities are 2/3 and 1/3.
+    assert_eq!(normalized, vec![
+        ("outcome1".to_string(), dec!("0.5") / dec!("0.75")),
+        ("outcome2".to_string(), dec!("0.25") / dec!("0.75")),
+    ]);
+}
+
+#[test]
+fn test_update_bet_limits_applies_to_later_bets() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let update_bet_limits = |env: &mut TestEnv, new_min: Decimal, new_max: Decimal| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "update_bet_limits", manifest_args!(new_min, new_max))
+            .build();
+        env.execute(manifest)
+    };
+    env.place_bet(&market, "user1", "outcome1", dec!("5")).expect_commit_success();
+
+    // The limits are validated like at instantiation, and only the admin can change them.
+    update_bet_limits(&mut env, dec!("4"), dec!("50")).expect_commit_failure();
+    update_bet_limits(&mut env, dec!("20"), dec!("20")).expect_commit_failure();
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "update_bet_limits", manifest_args!(dec!("10"), dec!("50")))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+
+    update_bet_limits(&mut env, dec!("10"), dec!("50")).expect_commit_success();
+    let details = env.market_details(&market);
+    assert_eq!((details.min_bet, details.max_bet), (dec!("10"), dec!("50")));
+
+    // New bets must respect the new limits, while user1's earlier bet is kept.
+    expect_market_error(env.place_bet(&market, "user2", "outcome1", dec!("5")), "BetBelowMinimum");
+    env.place_bet(&market, "user2", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome2", dec!("60")).expect_commit_success();
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .call_method(market.component, "get_user_bets", manifest_args!("user3".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let user1_bets: Vec<(String, Decimal)> = commit.output(1);
+    let user3_bets: Vec<(String, Decimal)> = commit.output(2);
+    assert_eq!(user1_bets, vec![("outcome1".to_string(), dec!("5"))]);
+    assert_eq!(user3_bets, vec![("outcome2".to_string(), dec!("50"))]);
+
+    // The limits are frozen once the market is locked.
+    env.lock_market(&market).expect_commit_success();
+    expect_market_error(update_bet_limits(&mut env, dec!("5"), dec!("100")), "MarketLocked");
+}
+
+#[test]
+fn test_bets_are_paid_at_locked_odds() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.update_odds(&market, "outcome1", dec!("3")).expect_commit_success();
+    let receipt = env.place_bet(&market, "user2", "outcome1", dec!("10"));
+    receipt.expect_commit_success();
+    let event: BetPlacedEvent = scrypto_decode(&env.events_named(&receipt, "BetPlacedEvent")[0]).unwrap();
+    assert_eq!(event.odds, dec!("3"));
+    assert_eq!((event.cumulative_amount, event.outcome_total_staked), (dec!("10"), dec!("20")));
+    // user1 tops up at the new odds, so half of their stake is at 2 and half at 3.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_bets_with_odds", manifest_args!("user1".to_string()))
+        .call_method(market.component, "get_user_bets_with_odds", manifest_args!("user2".to_string()))
+        .call_method(market.component, "get_outcome_liability", manifest_args!("outcome1".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let user1_bets: Vec<(String, Decimal, Decimal)> = commit.output(1);
+    let user2_bets: Vec<(String, Decimal, Decimal)> = commit.output(2);
+    let liability: Decimal = commit.output(3);
+    assert_eq!(user1_bets, vec![("outcome1".to_string(), dec!("20"), dec!("2.5"))]);
+    assert_eq!(user2_bets, vec![("outcome1".to_string(), dec!("10"), dec!("3"))]);
+    assert_eq!(liability, dec!("80"));
+
+    assert_eq!(
+        env.resolve_market_rewards(&market, 0),
+        vec![("user1".to_string(), dec!("50")), ("user2".to_string(), dec!("30"))]
+    );
+}
+
+#[test]
+fn test_get_odds_for_outcome() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.update_odds(&market, "outcome2", dec!("4")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_odds_for_outcome", manifest_args!("outcome1".to_string()))
+        .call_method(market.component, "get_odds_for_outcome", manifest_args!("outcome2".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let outcome1_odds: Decimal = commit.output(1);
+    let outcome2_odds: Decimal = commit.output(2);
+    assert_eq!(outcome1_odds, dec!("2"));
+    assert_eq!(outcome2_odds, dec!("4"));
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_odds_for_outcome", manifest_args!("outcome3".to_string()))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+}
+
+#[test]
+fn test_per_outcome_resolution_sources() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let set_source = |env: &mut TestEnv, outcome: &str, source: &str| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "set_resolution_source", manifest_args!(outcome.to_string(), source.to_string()))
+            .build();
+        env.execute(manifest)
+    };
+    set_source(&mut env, "outcome1", "Official league table").expect_commit_success();
+    set_source(&mut env, "outcome2", "Oracle price feed").expect_commit_success();
+    set_source(&mut env, "outcome3", "Unknown outcome").expect_commit_failure();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_resolution_source", manifest_args!("outcome1".to_string()))
+        .call_method(market.component, "get_resolution_source", manifest_args!("outcome2".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let outcome1_source: String = commit.output(1);
+    let outcome2_source: String = commit.output(2);
+    assert_eq!(outcome1_source, "Official league table");
+    assert_eq!(outcome2_source, "Oracle price feed");
+
+    // Sources are frozen once the market is locked.
+    env.lock_market(&market).expect_commit_success();
+    set_source(&mut env, "outcome1", "Another source").expect_commit_failure();
+}
+
+#[test]
+fn test_market_metadata_round_trip() {
+    let mut env = TestEnv::new();
+    env.instantiate_receipt(&MarketParams { description: "x".repeat(1_001), ..Default::default() })
+        .expect_commit_failure();
+    let market = env.instantiate(&MarketParams {
+        description: "Who wins the final?".to_string(),
+        resolution_source: "Official league table".to_string(),
+        ..Default::default()
+    });
+    let details = env.market_details(&market);
+    assert_eq!(details.description, "Who wins the final?");
+    assert_eq!(details.resolution_source, "Official league table");
+
+    let update_metadata = |env: &mut TestEnv, description: String, resolution_source: &str| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "update_market_metadata", manifest_args!(description, resolution_source.to_string()))
+            .build();
+        env.execute(manifest)
+    };
+    update_metadata(&mut env, "x".repeat(1_001), "").expect_commit_failure();
+    // An empty description is allowed.
+    let receipt = update_metadata(&mut env, String::new(), "Oracle price feed");
+    receipt.expect_commit_success();
+    assert_eq!(env.events_named(&receipt, "MarketMetadataUpdatedEvent").len(), 1);
+    let details = env.market_details(&market);
+    assert_eq!(details.description, "");
+    assert_eq!(details.resolution_source, "Oracle price feed");
+
+    // The metadata is frozen once the market is locked.
+    env.lock_market(&market).expect_commit_success();
+    expect_market_error(update_metadata(&mut env, "Too late".to_string(), "Oracle price feed"), "MarketLocked");
+}
+
+#[test]
+fn test_odds_delta_since_epoch() {
+    let mut env = TestEnv::new();
+    env.test_runner.set_current_epoch(Epoch::of(1));
+    let market = env.instantiate(&MarketParams::default());
+    env.test_runner.set_current_epoch(Epoch::of(5));
+    env.update_odds(&market, "outcome2", dec!("2.5")).expect_commit_success();
+    env.test_runner.set_current_epoch(Epoch::of(8));
+    env.update_odds(&market, "outcome2", dec!("4")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_odds_delta", manifest_args!("outcome2".to_string(), 0i64))
+        .call_method(market.component, "get_odds_delta", manifest_args!("outcome2".to_string(), 6i64))
+        .call_method(market.component, "get_odds_delta", manifest_args!("outcome2".to_string(), 8i64))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let since_open: Decimal = commit.output(1);
+    let since_first_update: Decimal = commit.output(2);
+    let since_latest_update: Decimal = commit.output(3);
+
+    assert_eq!(since_open, dec!("1"));
+    assert_eq!(since_first_update, dec!("1.5"));
+    assert_eq!(since_latest_update, Decimal::zero());
+}
+
+#[test]
+fn test_odds_decay_over_epochs() {
+    let mut env = TestEnv::new();
+    let decaying = MarketParams {
+        outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+        odds_str: "2,3,1.5".to_string(),
+        betting_deadline: Some(Epoch::of(10)),
+        odds_decay_per_epoch: dec!("0.1"),
+        ..Default::default()
+    };
+    env.instantiate_receipt(&MarketParams { odds_decay_per_epoch: dec!("-0.1"), ..Default::default() })
+        .expect_commit_failure();
+    env.test_runner.set_current_epoch(Epoch::of(1));
+    let market = env.instantiate(&decaying);
+    let get_effective_odds = |env: &mut TestEnv| -> Vec<Decimal> {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_effective_odds", manifest_args!("outcome1".to_string()))
+            .call_method(market.component, "get_effective_odds", manifest_args!("outcome2".to_string()))
+            .call_method(market.component, "get_effective_odds", manifest_args!("outcome3".to_string()))
+            .call_method(market.component, "get_odds_for_outcome", manifest_args!("outcome1".to_string()))
+            .build();
+        let receipt = env.execute(manifest);
+        let commit = receipt.expect_commit_success();
+        let odds: Vec<Decimal> = (1..=3).map(|index| commit.output(index)).collect();
+        // New bets are priced at the effective odds.
+        let quoted: Decimal = commit.output(4);
+        assert_eq!(quoted, odds[0]);
+        odds
+    };
+
+    assert_eq!(get_effective_odds(&mut env), vec![dec!("2"), dec!("3"), dec!("1.5")]);
+    env.test_runner.set_current_epoch(Epoch::of(4));
+    assert_eq!(get_effective_odds(&mut env), vec![dec!("1.7"), dec!("2.7"), dec!("1.2")]);
+    env.deposit_to_vault(&market, dec!("100")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    // The odds never drop below 1.01, and stop decaying at the betting deadline.
+    env.test_runner.set_current_epoch(Epoch::of(10));
+    assert_eq!(get_effective_odds(&mut env), vec![dec!("1.1"), dec!("2.1"), dec!("1.01")]);
+    env.test_runner.set_current_epoch(Epoch::of(15));
+    assert_eq!(get_effective_odds(&mut env), vec![dec!("1.1"), dec!("2.1"), dec!("1.01")]);
+
+    // The market details report the effective odds, while user1 is paid at the odds of epoch 4.
+    assert_eq!(env.market_details(&market).odds, vec![dec!("1.1"), dec!("2.1"), dec!("1.01")]);
+    assert_eq!(env.resolve_market_rewards(&market, 0), vec![("user1".to_string(), dec!("17"))]);
+}
+
+#[test]
+fn test_bet_from_prefunded_balance() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    let start = env.balance(market.bet_token);
+
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(env.account, market.bet_token, dec!("100"))
+        .take_from_worktop(market.bet_token, dec!("100"), "deposit")
+        .call_method_with_name_lookup(market.component, "deposit_user_balance", |lookup| {
+            ("user1".to_string(), lookup.bucket("deposit"))
+        })
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.claim_badge, dec!("1"))
+        .pop_from_auth_zone("claim_badge")
+        .call_method_with_name_lookup(market.component, "place_bet_from_balance", |lookup| {
+            (lookup.proof("claim_badge"), "outcome1".to_string(), dec!("30"))
+        })
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_balance", manifest_args!("user1".to_string()))
+        .call_method(market.component, "get_user_bets", manifest_args!("user1".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let user_balance: Decimal = commit.output(1);
+    let user_bets: Vec<(String, Decimal)> = commit.output(2);
+    assert_eq!(user_balance, dec!("70"));
+    assert_eq!(user_bets, vec![("outcome1".to_string(), dec!("30"))]);
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.claim_badge, dec!("1"))
+        .pop_from_auth_zone("claim_badge")
+        .call_method_with_name_lookup(market.component, "withdraw_user_balance", |lookup| {
+            (lookup.proof("claim_badge"),)
+        })
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest).expect_commit_success();
+    assert_eq!(start - env.balance(market.bet_token), dec!("30"));
+}
+
+#[test]
+fn test_trigger_close_after_deadline() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams { betting_deadline: Some(Epoch::of(10)), ..Default::default() });
+    let trigger_close = ManifestBuilder::new()
+        .call_method(market.component, "trigger_close", manifest_args!())
+        .build();
+
+    // Just before the close the market is still open.
+    env.test_runner.set_current_epoch(Epoch::of(10));
+    env.execute(trigger_close.clone()).expect_commit_failure();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    // Just after the close anyone can lock it.
+    env.test_runner.set_current_epoch(Epoch::of(11));
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_failure();
+    let receipt = env.execute(trigger_close.clone());
+    assert_eq!(env.events_named(&receipt, "MarketLockedEvent").len(), 1);
+    env.execute(trigger_close).expect_commit_failure();
+}
+
+#[test]
+fn test_void_after_treasury_withdrawal_refunds_everyone() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("40")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("60")).expect_commit_success();
+
+    // The admin drains as much of the treasury as solvency allows before voiding.
+    env.withdraw_from_vault(&market, "admin1", dec!("360")).expect_commit_success();
+    let receipt = env.resolve_market_as_void(&market);
+    let result: Result<(), MarketError> = receipt.expect_commit_success().output(2);
+    assert!(result.is_ok());
+
+    // Stakes never left the outcome vaults, so the refund is complete.
+    let before = env.balance(market.bet_token);
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("100"));
+}
+
+#[test]
+fn test_force_void_after_resolution_deadline() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams { resolution_deadline: Some(Epoch::of(20)), ..Default::default() });
+    env.place_bet(&market, "user1", "outcome1", dec!("40")).expect_commit_success();
+    let (stranger_key, _, _) = env.test_runner.new_allocated_account();
+    let force_void = ManifestBuilder::new()
+        .call_method(market.component, "force_void_expired_market", manifest_args!())
+        .build();
+
+    // The admin still has until the end of the resolution deadline.
+    env.test_runner.set_current_epoch(Epoch::of(20));
+    env.execute_as(force_void.clone(), &stranger_key).expect_commit_failure();
+
+    // Afterwards anyone can void the market.
+    env.test_runner.set_current_epoch(Epoch::of(21));
+    let receipt = env.execute_as(force_void.clone(), &stranger_key);
+    let result: Result<(), MarketError> = receipt.expect_commit_success().output(1);
+    assert!(result.is_ok());
+    let events = env.events_named(&receipt, "MarketResolvedAsVoidEvent");
+    assert_eq!(events.len(), 1);
+    let event: MarketResolvedAsVoidEvent = scrypto_decode(&events[0]).unwrap();
+    assert!(event.forced);
+
+    // The stake is refunded and the market can't be voided twice.
+    let before = env.balance(market.bet_token);
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("40"));
+    env.execute_as(force_void, &stranger_key).expect_commit_failure();
+}
+
+#[test]
+fn test_force_void_fails_after_normal_resolution() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams { resolution_deadline: Some(Epoch::of(20)), ..Default::default() });
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("40")).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_success();
+
+    env.test_runner.set_current_epoch(Epoch::of(21));
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "force_void_expired_market", manifest_args!())
+        .build();
+    env.execute(manifest).expect_commit_failure();
+}
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct RefundClaimedEvent {
+    market_id: String,
+    sequence: u64,
+    user_hash: String,
+    refund: Decimal,
+}
+
+#[test]
+fn test_claim_refund_only_in_voided_market() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("40")).expect_commit_success();
+    let claim_refund = |env: &mut TestEnv| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.claim_badge, dec!("1"))
+            .pop_from_auth_zone("claim_badge")
+            .call_method_with_name_lookup(market.component, "claim_refund", |lookup| {
+                (lookup.proof("claim_badge"),)
+            })
+            .deposit_batch(env.account)
+            .build();
+        env.execute(manifest)
+    };
+
+    // Nothing to refund while the market is open.
+    claim_refund(&mut env).expect_commit_failure();
+
+    env.resolve_market_as_void(&market).expect_commit_success();
+    let before = env.balance(market.bet_token);
+    let receipt = claim_refund(&mut env);
+    receipt.expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("40"));
+    let events = env.events_named(&receipt, "RefundClaimedEvent");
+    assert_eq!(events.len(), 1);
+    let event: RefundClaimedEvent = scrypto_decode(&events[0]).unwrap();
+    assert_eq!(event.user_hash, "user1");
+    assert_eq!(event.refund, dec!("40"));
+    assert!(env.events_named(&receipt, "ClaimRewardEvent").is_empty());
+
+    // The refund can only be claimed once.
+    let before = env.balance(market.bet_token);
+    let receipt = claim_refund(&mut env);
+    receipt.expect_commit_success();
+    assert_eq!(env.balance(market.bet_token), before);
+    assert!(env.events_named(&receipt, "RefundClaimedEvent").is_empty());
+
+    // A resolved market pays out through claim_reward instead.
+    let resolved = token_market(&mut env, MarketParams { title: "resolved".to_string(), ..Default::default() });
+    env.deposit_to_vault(&resolved, dec!("500")).expect_commit_success();
+    env.place_bet(&resolved, "user1", "outcome1", dec!("40")).expect_commit_success();
+    env.resolve_market(&resolved, 0).expect_commit_success();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, resolved.claim_badge, dec!("1"))
+        .pop_from_auth_zone("claim_badge")
+        .call_method_with_name_lookup(resolved.component, "claim_refund", |lookup| {
+            (lookup.proof("claim_badge"),)
+        })
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest).expect_commit_failure();
+}
+
+#[test]
+fn test_resolve_market_multi_applies_dead_heat_reduction() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams {
+        outcomes_str: "outcome1,outcome2,outcome3".to_string(),
+        odds_str: "2,3,4".to_string(),
+        ..Default::default()
+    });
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome3", dec!("30")).expect_commit_success();
+
+    // Winners must be non-empty, unique and in range.
+    env.resolve_market_multi(&market, vec![]).expect_commit_failure();
+    env.resolve_market_multi(&market, vec![0, 0]).expect_commit_failure();
+    expect_market_error(env.resolve_market_multi(&market, vec![0, 3]), "OutcomeOutOfBounds");
+
+    // In a two-way dead heat each winning bet is paid half of `stake * odds`.
+    let receipt = env.resolve_market_multi(&market, vec![0, 1]);
+    let result: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+    assert_eq!(
+        result.unwrap(),
+        vec![("user1".to_string(), dec!("10")), ("user2".to_string(), dec!("30"))]
+    );
+    assert_eq!(env.events_named(&receipt, "MarketResolvedMultiEvent").len(), 1);
+
+    // Only the losing pool joins the treasury.
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_xrd_vault_balance", manifest_args!())
+        .build();
+    let treasury: Decimal = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(treasury, dec!("500") + dec!("30") - dec!("10") - dec!("30"));
+}
+
+#[test]
+fn test_strict_resolution_rejects_winner_without_bets() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    // Only the admin can turn strict mode on.
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "set_strict_resolution", manifest_args!(true))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+    env.set_strict_resolution(&market, true).expect_commit_success();
+
+    // Nobody bet on outcome2, so the resolution is rejected and the market stays open.
+    let receipt = env.resolve_market(&market, 1);
+    let result: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+    assert_eq!(result, Err(MarketError::WinningOutcomeHasNoBets { outcome: 1 }));
+    assert!(env.events_named(&receipt, "MarketResolvedEvent").is_empty());
+    assert_eq!(env.market_details(&market).status, MarketStatus::Open);
+
+    let receipt = env.resolve_market_multi(&market, vec![0, 1]);
+    let result: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+    assert_eq!(result, Err(MarketError::WinningOutcomeHasNoBets { outcome: 1 }));
+
+    // The permissive default resolves the market and sweeps the stakes to the house.
+    env.set_strict_resolution(&market, false).expect_commit_success();
+    assert!(env.resolve_market_rewards(&market, 1).is_empty());
+    assert_eq!(env.market_details(&market).status, MarketStatus::Resolved { winning_outcome: 1 });
+}
+
+#[test]
+fn test_strict_resolution_applies_to_finalize_resolution() {
+    let mut env = TestEnv::new();
+    env.test_runner.set_current_epoch(Epoch::of(1));
+    let market = env.instantiate(&MarketParams { dispute_window_epochs: 5, ..Default::default() });
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.set_strict_resolution(&market, true).expect_commit_success();
+
+    // Nobody bet on outcome2, so finalizing the proposal fails and leaves it pending.
+    env.propose_resolution(&market, 1).expect_commit_success();
+    env.test_runner.set_current_epoch(Epoch::of(6));
+    let receipt = env.finalize_resolution(&market);
+    let result: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(1);
+    assert_eq!(result, Err(MarketError::WinningOutcomeHasNoBets { outcome: 1 }));
+    assert!(env.events_named(&receipt, "MarketResolvedEvent").is_empty());
+    assert!(!env.market_details(&market).market_resolved);
+
+    // The oracle cancels it and proposes the outcome that was bet on instead.
+    env.cancel_proposed_resolution(&market).expect_commit_success();
+    env.propose_resolution(&market, 0).expect_commit_success();
+    env.test_runner.set_current_epoch(Epoch::of(11));
+    let result: Result<Vec<(String, Decimal)>, MarketError> = env.finalize_resolution(&market).expect_commit_success().output(1);
+    assert_eq!(result, Ok(vec![("user1".to_string(), dec!("20"))]));
+}
+
+#[test]
+fn test_outcome_liabilities_track_bets() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("100")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    // A top-up adds to the existing position.
+    env.place_bet(&market, "user1", "outcome1", dec!("5")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_outcome_liability", manifest_args!("outcome1".to_string()))
+        .call_method(market.component, "get_outcome_liability", manifest_args!("outcome2".to_string()))
+        .call_method(market.component, "get_max_liability", manifest_args!())
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let outcome1_liability: Decimal = commit.output(1);
+    let outcome2_liability: Decimal = commit.output(2);
+    let max_liability: Decimal = commit.output(3);
+    assert_eq!(outcome1_liability, dec!("15") * dec!("2"));
+    assert_eq!(outcome2_liability, dec!("20") * dec!("3"));
+    assert_eq!(max_liability, dec!("60"));
+
+    // If outcome2 wins, 100 - withdrawal + 15 must still cover its liability of 60.
+    expect_market_error(env.withdraw_from_vault(&market, "admin1", dec!("56")), "InsufficientCollateral");
+    env.withdraw_from_vault(&market, "admin1", dec!("55")).expect_commit_success();
+}
+
+#[test]
+fn test_oracle_and_admin_roles_are_separate() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+
+    // The admin badge alone can no longer resolve or void the market.
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+        .call_method(market.component, "resolve_market", manifest_args!(0u32))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+        .call_method(market.component, "resolve_market_as_void", manifest_args!())
+        .build();
+    env.execute(manifest).expect_commit_failure();
+
+    // The oracle badge alone can't touch the treasury.
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.oracle_badge, dec!("1"))
+        .call_method(market.component, "withdraw_from_vault", manifest_args!("admin1".to_string(), dec!("100")))
+        .build();
+    env.execute(manifest).expect_commit_failure();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.oracle_badge, dec!("1"))
+        .call_method(market.component, "admin_claim", manifest_args!("admin1".to_string()))
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest).expect_commit_failure();
+
+    env.resolve_market(&market, 0).expect_commit_success();
+}
+
+#[test]
+fn test_rotated_admin_badge_replaces_old_one() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+        .call_method(market.component, "rotate_admin_badge", manifest_args!())
+        .deposit_batch(env.account)
+        .build();
+    let receipt = env.execute(manifest);
+    let new_admin_badge = receipt.expect_commit_success().new_resource_addresses()[0];
+    assert_eq!(env.balance(new_admin_badge), dec!("1"));
+
+    // The old badge no longer grants the admin role.
+    env.lock_market(&market).expect_commit_failure();
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, new_admin_badge, dec!("1"))
+        .call_method(market.component, "lock_market", manifest_args!())
+        .build();
+    env.execute(manifest).expect_commit_success();
+}
+
+#[test]
+fn test_market_status_lifecycle() {
+    let mut env = TestEnv::new();
+    let get_status = |env: &mut TestEnv, market: &TestMarket| -> MarketStatus {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_market_status", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    let market = env.instantiate(&MarketParams::default());
+    assert_eq!(get_status(&mut env, &market), MarketStatus::Open);
+    env.lock_market(&market).expect_commit_success();
+    assert_eq!(get_status(&mut env, &market), MarketStatus::Locked);
+    assert_eq!(env.market_details(&market).status, MarketStatus::Locked);
+
+    // The winning outcome is kept in the status, which is final.
+    env.resolve_market(&market, 1).expect_commit_success();
+    assert_eq!(get_status(&mut env, &market), MarketStatus::Resolved { winning_outcome: 1 });
+    assert!(env.market_details(&market).market_resolved);
+    expect_market_error(env.lock_market(&market), "MarketResolved");
+    expect_market_error(env.unlock_market(&market), "MarketResolved");
+
+    let voided_market = env.instantiate(&MarketParams { title: "voided".to_string(), ..Default::default() });
+    env.resolve_market_as_void(&voided_market).expect_commit_success();
+    assert_eq!(get_status(&mut env, &voided_market), MarketStatus::Voided);
+}
+
+#[test]
+fn test_bootstrap_state_matches_individual_getters() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome1", dec!("15")).expect_commit_success();
+    env.cancel_bet(&market, 3, "outcome1").expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "bootstrap_state", manifest_args!())
+        .call_method(market.component, "get_market_details", manifest_args!())
+        .call_method(market.component, "get_market_status", manifest_args!())
+        .call_method(market.component, "get_current_sequence", manifest_args!())
+        .call_method(market.component, "get_effective_fee_percent", manifest_args!())
+        .call_method(market.component, "get_outcome_balance", manifest_args!("outcome1".to_string()))
+        .call_method(market.component, "get_outcome_balance", manifest_args!("outcome2".to_string()))
+        .call_method(market.component, "get_outcome_liability", manifest_args!("outcome1".to_string()))
+        .call_method(market.component, "get_outcome_liability", manifest_args!("outcome2".to_string()))
+        .call_method(market.component, "get_bets_for_outcome_paged", manifest_args!("outcome1".to_string(), 0usize, 100usize))
+        .call_method(market.component, "get_bets_for_outcome_paged", manifest_args!("outcome2".to_string(), 0usize, 100usize))
+        .call_method(market.component, "get_user_vault_balance", manifest_args!("user3".to_string()))
+        .build();
+    let receipt = env.execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let state: BootstrapState = commit.output(1);
+    let details: MarketDetails = commit.output(2);
+    let status: MarketStatus = commit.output(3);
+    let sequence: u64 = commit.output(4);
+    let fee: Decimal = commit.output(5);
+    let balances: Vec<Decimal> = vec![commit.output(6), commit.output(7)];
+    let liabilities: Vec<Decimal> = vec![commit.output(8), commit.output(9)];
+    let (outcome1_bets, outcome1_count): (Vec<(String, Decimal)>, usize) = commit.output(10);
+    let (outcome2_bets, outcome2_count): (Vec<(String, Decimal)>, usize) = commit.output(11);
+    let user3_vault: Decimal = commit.output(12);
+
+    assert_eq!(state.details, details);
+    assert_eq!(state.status, status);
+    assert_eq!(state.current_sequence, sequence);
+    assert_eq!(state.effective_fee_percent, fee);
+    assert_eq!(state.outcome_balances, balances);
+    assert_eq!(state.outcome_liabilities, liabilities);
+    assert_eq!(state.bet_counts, vec![outcome1_count, outcome2_count]);
+    assert_eq!(state.bets, vec![outcome1_bets, outcome2_bets]);
+    assert_eq!(
+        state.user_vault_balances,
+        vec![("user1".to_string(), Decimal::zero()), ("user2".to_string(), Decimal::zero()), ("user3".to_string(), user3_vault)]
+    );
+    assert!(state.user_balances.is_empty());
+}
+
+#[test]
+fn test_withdrawable_amount_reserves_winning_payouts() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let get_withdrawable = |env: &mut TestEnv| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_withdrawable_amount", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    env.deposit_to_vault(&market, dec!("200")).expect_commit_success();
+    assert_eq!(get_withdrawable(&mut env), dec!("200"));
+
+    // outcome2 wins 40 * 3 = 120, of which the 10 staked on outcome1 is covered by the losing pool.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("40")).expect_commit_success();
+    assert_eq!(get_withdrawable(&mut env), dec!("90"));
+    env.withdraw_from_vault(&market, "admin1", dec!("91")).expect_commit_failure();
+    env.withdraw_from_vault(&market, "admin1", dec!("90")).expect_commit_success();
+
+    // After resolution the rewards sit in the user vaults and the remainder is free.
+    env.resolve_market(&market, 1).expect_commit_success();
+    assert_eq!(get_withdrawable(&mut env), dec!("0"));
+    env.deposit_to_vault(&market, dec!("50")).expect_commit_success();
+    assert_eq!(get_withdrawable(&mut env), dec!("50"));
+    env.withdraw_from_vault(&market, "admin1", dec!("50")).expect_commit_success();
+}
+
+fn mint_resolver_badges(env: &mut TestEnv, market: &TestMarket, weights: &[Decimal]) {
+    for weight in weights {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "mint_resolver_badge", manifest_args!(*weight))
+            .deposit_batch(env.account)
+            .build();
+        env.execute(manifest).expect_commit_success();
+    }
+}
+
+fn vote_resolution(env: &mut TestEnv, market: &TestMarket, badge_id: u64, outcome: u32) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_non_fungibles(env.account, market.resolver_badge, [NonFungibleLocalId::integer(badge_id)])
+        .pop_from_auth_zone("resolver_badge")
+        .call_method_with_name_lookup(market.component, "vote_resolution", |lookup| {
+            (lookup.proof("resolver_badge"), outcome)
+        })
+        .build();
+    env.execute(manifest)
+}
+
+#[test]
+fn test_weighted_vote_resolves_on_majority() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("10")).expect_commit_success();
+    mint_resolver_badges(&mut env, &market, &[dec!("1"), dec!("1"), dec!("2")]);
+
+    // 1 of 4 is no majority yet.
+    let receipt = vote_resolution(&mut env, &market, 1, 1);
+    assert!(env.events_named(&receipt, "MarketResolvedEvent").is_empty());
+
+    // 1 + 2 of 4 is.
+    let receipt = vote_resolution(&mut env, &market, 3, 1);
+    assert_eq!(env.events_named(&receipt, "MarketResolvedEvent").len(), 1);
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_user_vault_balance", manifest_args!("user1".to_string()))
+        .build();
+    let reward: Decimal = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(reward, dec!("30"));
+}
+
+#[test]
+fn test_split_vote_does_not_reach_quorum() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    mint_resolver_badges(&mut env, &market, &[dec!("1"), dec!("1")]);
+
+    vote_resolution(&mut env, &market, 1, 0).expect_commit_success();
+    let receipt = vote_resolution(&mut env, &market, 2, 1);
+    assert!(env.events_named(&receipt, "MarketResolvedEvent").is_empty());
+
+    // Badges can't be added once voting has started.
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+        .call_method(market.component, "mint_resolver_badge", manifest_args!(dec!("1")))
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest).expect_commit_failure();
+}
+
+#[test]
+fn test_strict_resolution_applies_to_votes() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.set_strict_resolution(&market, true).expect_commit_success();
+    mint_resolver_badges(&mut env, &market, &[dec!("1")]);
+
+    // A majority for outcome2, which nobody bet on, is rejected without recording the vote.
+    let receipt = vote_resolution(&mut env, &market, 1, 1);
+    assert!(env.events_named(&receipt, "ResolutionVoteEvent").is_empty());
+    assert!(env.events_named(&receipt, "MarketResolvedEvent").is_empty());
+    assert_eq!(env.market_details(&market).status, MarketStatus::Open);
+
+    // So the badge can still vote for the outcome that was bet on.
+    let receipt = vote_resolution(&mut env, &market, 1, 0);
+    assert_eq!(env.events_named(&receipt, "MarketResolvedEvent").len(), 1);
+    assert_eq!(env.market_details(&market).status, MarketStatus::Resolved { winning_outcome: 0 });
+}
+
+#[test]
+fn test_resolver_badge_cannot_vote_twice() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    mint_resolver_badges(&mut env, &market, &[dec!("1"), dec!("1"), dec!("1")]);
+
+    vote_resolution(&mut env, &market, 1, 0).expect_commit_success();
+    vote_resolution(&mut env, &market, 1, 0).expect_commit_failure();
+    vote_resolution(&mut env, &market, 1, 1).expect_commit_failure();
+}
+
+#[test]
+fn test_outcome_exposure_cap() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams { max_outcome_exposure: Some(dec!("50")), ..Default::default() });
+    let get_capacity = |env: &mut TestEnv, outcome: &str| -> Option<Decimal> {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_outcome_remaining_capacity", manifest_args!(outcome.to_string()))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    env.place_bet(&market, "user1", "outcome1", dec!("30")).expect_commit_success();
+    assert_eq!(get_capacity(&mut env, "outcome1"), Some(dec!("20")));
+    assert_eq!(get_capacity(&mut env, "outcome2"), Some(dec!("50")));
+
+    // 30 + 25 would exceed the cap, 30 + 20 reaches it exactly.
+    expect_market_error(env.place_bet(&market, "user2", "outcome1", dec!("25")), "ExposureExceeded");
+    env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+    assert_eq!(get_capacity(&mut env, "outcome1"), Some(Decimal::zero()));
+
+    let uncapped = env.instantiate(&MarketParams { title: "uncapped".to_string(), ..Default::default() });
+    let manifest = ManifestBuilder::new()
+        .call_method(uncapped.component, "get_outcome_remaining_capacity", manifest_args!("outcome1".to_string()))
+        .build();
+    let capacity: Option<Decimal> = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(capacity, None);
+}
+
+#[test]
+fn test_user_total_cap_across_outcomes() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    let set_max_user_total = |env: &mut TestEnv, max_user_total: Option<Decimal>| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "set_max_user_total", manifest_args!(max_user_total))
+            .build();
+        env.execute(manifest)
+    };
+    let get_user_total = |env: &mut TestEnv, user_hash: &str| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_user_total_staked", manifest_args!(user_hash.to_string()))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+    set_max_user_total(&mut env, Some(dec!("4"))).expect_commit_failure();
+    set_max_user_total(&mut env, Some(dec!("150"))).expect_commit_success();
+
+    // user1 maxes out outcome1 and can then only add 50 on outcome2.
+    env.place_bet(&market, "user1", "outcome1", dec!("100")).expect_commit_success();
+    expect_market_error(env.place_bet(&market, "user1", "outcome2", dec!("60")), "UserTotalExceeded");
+    env.place_bet(&market, "user1", "outcome2", dec!("50")).expect_commit_success();
+    assert_eq!(get_user_total(&mut env, "user1"), dec!("150"));
+    expect_market_error(env.place_bet(&market, "user1", "outcome2", dec!("5")), "UserTotalExceeded");
+
+    // Other users have their own allowance.
+    env.place_bet(&market, "user2", "outcome2", dec!("100")).expect_commit_success();
+    assert_eq!(get_user_total(&mut env, "user2"), dec!("100"));
+    assert_eq!(get_user_total(&mut env, "user3"), Decimal::zero());
+
+    // Without the cap only max_bet applies.
+    set_max_user_total(&mut env, None).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome2", dec!("50")).expect_commit_success();
+    assert_eq!(get_user_total(&mut env, "user1"), dec!("200"));
+}
+
+#[test]
+fn test_total_stake_cap() {
+    let mut env = TestEnv::new();
+    env.instantiate_receipt(&MarketParams { max_total_staked: Some(Decimal::zero()), ..Default::default() })
+        .expect_commit_failure();
+    let market = env.instantiate(&MarketParams { max_total_staked: Some(dec!("100")), ..Default::default() });
+    let get_capacity = |env: &mut TestEnv| -> Option<Decimal> {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_remaining_capacity", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+    assert_eq!(get_capacity(&mut env), Some(dec!("100")));
+
+    // Bets on every outcome, including top-ups, count towards the cap.
+    env.place_bet(&market, "user1", "outcome1", dec!("40")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("30")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("20")).expect_commit_success();
+    assert_eq!(get_capacity(&mut env), Some(dec!("10")));
+
+    expect_market_error(env.place_bet(&market, "user3", "outcome2", dec!("11")), "PoolCapExceeded");
+    env.place_bet(&market, "user3", "outcome2", dec!("10")).expect_commit_success();
+    assert_eq!(get_capacity(&mut env), Some(Decimal::zero()));
+    expect_market_error(env.place_bet(&market, "user4", "outcome1", dec!("5")), "PoolCapExceeded");
+
+    // A cancelled bet frees up its share of the cap.
+    env.cancel_bet(&market, 3, "outcome2").expect_commit_success();
+    assert_eq!(get_capacity(&mut env), Some(dec!("10")));
+}
+
+#[allow(dead_code)]
+#[derive(ScryptoSbor)]
+struct ClaimRewardEvent {
+    market_id: String,
+    sequence: u64,
+    user_hash: String,
+    reward: Decimal,
+    haircut_ratio: Option<Decimal>,
+}
+
+#[test]
+fn test_resolve_market_pro_rata_shares_short_collateral() {
+    let mut env = TestEnv::new();
+    let resolve_pro_rata = |env: &mut TestEnv, market: &TestMarket, badge: ResourceAddress, winning_outcome: u32| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, badge, dec!("1"))
+            .call_method(market.component, "resolve_market_pro_rata", manifest_args!(winning_outcome))
+            .build();
+        env.execute(manifest)
+    };
+
+    // Nothing was deposited, so the 31 staked can't cover the 54 owed to the winners at odds of 3.
+    let market = token_market(&mut env, MarketParams { odds_str: "3,2".to_string(), ..Default::default() });
+    env.place_bet(&market, "user1", "outcome1", dec!("7")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("11")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome2", dec!("13")).expect_commit_success();
+    env.resolve_market(&market, 0).expect_commit_failure();
+
+    // Only the oracle can settle the market, so the admin can't pick a winner and apply the haircut itself.
+    resolve_pro_rata(&mut env, &market, market.admin_badge, 0).expect_commit_failure();
+    let receipt = resolve_pro_rata(&mut env, &market, market.oracle_badge, 0);
+    let rewards: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+    let rewards = rewards.unwrap();
+    let ratio = dec!("31") / dec!("54");
+    let event: MarketResolvedEvent = scrypto_decode(&env.events_named(&receipt, "MarketResolvedEvent")[0]).unwrap();
+    assert_eq!((event.haircut_ratio, event.house_fee), (Some(ratio), Decimal::zero()));
+
+    // Every winner takes the same cut and together they get everything but the rounding dust.
+    assert_eq!(rewards.iter().map(|(user, _)| user.as_str()).collect::<Vec<_>>(), vec!["user1", "user2"]);
+    let paid = rewards.iter().fold(Decimal::zero(), |total, (_, reward)| total + *reward);
+    assert!(paid <= dec!("31") && dec!("31") - paid <= dec!("0.000000000000000002"));
+    let dust: Decimal = env.execute(ManifestBuilder::new()
+        .call_method(market.component, "get_xrd_vault_balance", manifest_args!())
+        .build()).expect_commit_success().output(1);
+    assert_eq!(dust, dec!("31") - paid);
+
+    // Each winner's claim pays their share and records the haircut.
+    for (badge_id, (_, reward)) in [1, 2].into_iter().zip(&rewards) {
+        assert!(*reward > Decimal::zero());
+        let before = env.balance(market.bet_token);
+        let receipt = env.claim_reward_with_badge(&market, badge_id);
+        assert_eq!(env.balance(market.bet_token) - before, *reward);
+        let claim: ClaimRewardEvent = scrypto_decode(&env.events_named(&receipt, "ClaimRewardEvent")[0]).unwrap();
+        assert_eq!(claim.haircut_ratio, Some(ratio));
+    }
+
+    // A market that can pay in full is resolved as usual, without a haircut.
+    let funded = token_market(&mut env, MarketParams { title: "funded".to_string(), odds_str: "3,2".to_string(), ..Default::default() });
+    env.deposit_to_vault(&funded, dec!("100")).expect_commit_success();
+    env.place_bet(&funded, "user1", "outcome1", dec!("7")).expect_commit_success();
+    let receipt = resolve_pro_rata(&mut env, &funded, funded.oracle_badge, 0);
+    let rewards: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+    assert_eq!(rewards.unwrap(), vec![("user1".to_string(), dec!("21"))]);
+    let event: MarketResolvedEvent = scrypto_decode(&env.events_named(&receipt, "MarketResolvedEvent")[0]).unwrap();
+    assert_eq!(event.haircut_ratio, None);
+}
+
+#[test]
+fn test_instantiate_from_probabilities() {
+    let mut env = TestEnv::new();
+    let instantiate = |env: &mut TestEnv, probabilities_str: &str| {
+        let manifest = ManifestBuilder::new()
+            .call_function(
+                env.package_address,
+                "PredictionMarket",
+                "instantiate_from_probabilities",
+                manifest_args!("title".to_string(), "outcome1,outcome2".to_string(), probabilities_str.to_string(), dec!("5"), dec!("100")),
+            )
+            .deposit_batch(env.account)
+            .build();
+        env.execute(manifest)
+    };
+
+    // Each probability becomes odds of 1 / p.
+    let component = instantiate(&mut env, "0.5,0.5").expect_commit_success().new_component_addresses()[0];
+    let manifest = ManifestBuilder::new()
+        .call_method(component, "get_market_details", manifest_args!())
+        .build();
+    let details: MarketDetails = env.execute(manifest).expect_commit_success().output(1);
+    assert_eq!(details.odds, vec![dec!("2"), dec!("2")]);
+    assert_eq!(details.payout_mode, PayoutMode::FixedOdds);
+
+    // Probabilities summing to 1.2 are rejected, as are probabilities outside (0, 1).
+    instantiate(&mut env, "0.5,0.7").expect_commit_failure();
+    instantiate(&mut env, "0,1").expect_commit_failure();
+    instantiate(&mut env, "0.5,abc").expect_commit_failure();
+}
+
+#[test]
+fn test_preview_resolution_matches_resolve_market() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams {
+        odds_str: "1.333333333333333333,3".to_string(),
+        fee_percent: dec!("2.5"),
+        ..Default::default()
+    });
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("7")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome1", dec!("11")).expect_commit_success();
+    let preview_resolution = |env: &mut TestEnv, winning_outcome: u32| {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "preview_resolution", manifest_args!(winning_outcome))
+            .build();
+        env.execute(manifest)
+    };
+    let preview_void = |env: &mut TestEnv| {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "preview_void", manifest_args!())
+            .build();
+        env.execute(manifest)
+    };
+
+    // A void would refund every bet, in outcome order.
+    let refunds: Vec<(String, Decimal)> = preview_void(&mut env).expect_commit_success().output(1);
+    assert_eq!(refunds, vec![
+        ("user1".to_string(), dec!("7")),
+        ("user3".to_string(), dec!("11")),
+        ("user2".to_string(), dec!("20")),
+    ]);
+
+    preview_resolution(&mut env, 2).expect_commit_failure();
+    let previewed: Vec<(String, Decimal)> = preview_resolution(&mut env, 0).expect_commit_success().output(1);
+    assert_eq!(previewed.len(), 2);
+    assert_eq!(env.resolve_market_rewards(&market, 0), previewed);
+
+    // Once resolved there is nothing left to preview.
+    preview_resolution(&mut env, 0).expect_commit_failure();
+    preview_void(&mut env).expect_commit_failure();
+}
+
+#[test]
+fn test_seed_outcome_liquidity_is_not_a_bet() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    let seed = |env: &mut TestEnv, outcome: &str, amount: Decimal| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .withdraw_from_account(env.account, market.bet_token, amount)
+            .take_from_worktop(market.bet_token, amount, "funds")
+            .call_method_with_name_lookup(market.component, "seed_outcome_liquidity", |lookup| {
+                (outcome.to_string(), lookup.bucket("funds"))
+            })
+            .build();
+        env.execute(manifest)
+    };
+    let get_decimal = |env: &mut TestEnv, method: &str, outcome: &str| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, method, manifest_args!(outcome.to_string()))
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    env.place_bet(&market, "user1", "outcome1", dec!("5")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_success();
+    seed(&mut env, "outcome3", dec!("50")).expect_commit_failure();
+    let receipt = seed(&mut env, "outcome1", dec!("50"));
+    assert_eq!(env.events_named(&receipt, "LiquiditySeededEvent").len(), 1);
+
+    // The seed sits in the outcome's vault but isn't counted as a stake.
+    assert_eq!(get_decimal(&mut env, "get_outcome_balance", "outcome1"), dec!("55"));
+    assert_eq!(get_decimal(&mut env, "get_outcome_staked", "outcome1"), dec!("5"));
+    assert_eq!(env.market_details(&market).total_staked, dec!("15"));
+
+    // The seeded outcome wins: user1 is paid 10 and the seed returns to the treasury along with the losing pool.
+    assert_eq!(env.resolve_market_rewards(&market, 0), vec![("user1".to_string(), dec!("10"))]);
+    assert_eq!(env.market_details(&market).xrd_vault_balance, dec!("50"));
+    assert_eq!(get_decimal(&mut env, "get_outcome_balance", "outcome1"), dec!("5"));
+    seed(&mut env, "outcome1", dec!("10")).expect_commit_failure();
+}
+
+fn provide_liquidity(env: &mut TestEnv, market: &TestMarket, amount: Decimal) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(env.account, market.bet_token, amount)
+        .take_from_worktop(market.bet_token, amount, "liquidity")
+        .call_method_with_name_lookup(market.component, "provide_liquidity", |lookup| (lookup.bucket("liquidity"),))
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest)
+}
+
+// Redeems `lp_amount` LP tokens, depositing the payout into the account.
+fn redeem_liquidity(env: &mut TestEnv, market: &TestMarket, lp_amount: Decimal) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(env.account, market.lp_token, lp_amount)
+        .take_from_worktop(market.lp_token, lp_amount, "lp_tokens")
+        .call_method_with_name_lookup(market.component, "redeem_liquidity", |lookup| (lookup.bucket("lp_tokens"),))
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest)
+}
+
+fn get_withdrawable_amount(env: &mut TestEnv, market: &TestMarket) -> Decimal {
+    let manifest = ManifestBuilder::new()
+        .call_method(market.component, "get_withdrawable_amount", manifest_args!())
+        .build();
+    env.execute(manifest).expect_commit_success().output(1)
+}
+
+#[test]
+fn test_liquidity_providers_share_house_profit() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams { claim_deadline_epochs: Some(10), ..Default::default() });
+
+    // The house funds 100 before two providers add 100 and 300, so each LP token is worth one bet token.
+    env.deposit_to_vault(&market, dec!("100")).expect_commit_success();
+    provide_liquidity(&mut env, &market, dec!("100")).expect_commit_success();
+    provide_liquidity(&mut env, &market, dec!("300")).expect_commit_success();
+    assert_eq!(env.balance(market.lp_token), dec!("400"));
+    assert_eq!(get_withdrawable_amount(&mut env, &market), dec!("100"));
+    expect_market_error(redeem_liquidity(&mut env, &market, dec!("100")), "LiquidityLocked");
+
+    // user2's losing 40 joins the treasury, which pays user1 20: a profit of 20 on 500.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("40")).expect_commit_success();
+    env.test_runner.set_current_epoch(Epoch::of(10));
+    env.resolve_market(&market, 0).expect_commit_success();
+    env.test_runner.set_current_epoch(Epoch::of(20));
+    expect_market_error(redeem_liquidity(&mut env, &market, dec!("100")), "LiquidityLocked");
+
+    // Once the claim window closes, each party gets its share of the 520.
+    env.test_runner.set_current_epoch(Epoch::of(21));
+    for (lp_amount, payout) in [(dec!("100"), dec!("104")), (dec!("300"), dec!("312"))] {
+        let before = env.balance(market.bet_token);
+        redeem_liquidity(&mut env, &market, lp_amount).expect_commit_success();
+        assert_eq!(env.balance(market.bet_token) - before, payout);
+    }
+    assert_eq!(env.balance(market.lp_token), Decimal::zero());
+    assert_eq!(get_withdrawable_amount(&mut env, &market), dec!("104"));
+}
+
+#[test]
+fn test_liquidity_providers_share_house_loss() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    provide_liquidity(&mut env, &market, dec!("100")).expect_commit_success();
+    provide_liquidity(&mut env, &market, dec!("300")).expect_commit_success();
+
+    // The admin can't withdraw the providers' funds.
+    assert_eq!(get_withdrawable_amount(&mut env, &market), Decimal::zero());
+    env.withdraw_from_vault(&market, "admin", dec!("1")).expect_commit_failure();
+
+    // user1's losing 10 joins the treasury, which pays user2 120: a loss of 110 on 400.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome2", dec!("40")).expect_commit_success();
+    env.resolve_market(&market, 1).expect_commit_success();
+
+    // Without a claim deadline the providers can redeem right away, sharing the 290 left.
+    for (lp_amount, payout) in [(dec!("100"), dec!("72.5")), (dec!("300"), dec!("217.5"))] {
+        let before = env.balance(market.bet_token);
+        redeem_liquidity(&mut env, &market, lp_amount).expect_commit_success();
+        assert_eq!(env.balance(market.bet_token) - before, payout);
+    }
+    assert_eq!(env.market_details(&market).xrd_vault_balance, Decimal::zero());
+}
+
+#[test]
+fn test_provide_liquidity_rejected_once_the_outcome_is_known() {
+    let mut env = TestEnv::new();
+    env.test_runner.set_current_epoch(Epoch::of(1));
+    let market = token_market(&mut env, MarketParams { dispute_window_epochs: 5, ..Default::default() });
+    env.deposit_to_vault(&market, dec!("500")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+
+    // A pending proposal makes the outcome public, so providers can't buy into the treasury until it is settled.
+    env.propose_resolution(&market, 0).expect_commit_success();
+    expect_market_error(provide_liquidity(&mut env, &market, dec!("100")), "ResolutionPending");
+    env.test_runner.set_current_epoch(Epoch::of(6));
+    env.finalize_resolution(&market).expect_commit_success();
+    expect_market_error(provide_liquidity(&mut env, &market, dec!("100")), "MarketResolved");
+
+    // Locked and voided markets reject liquidity too.
+    let locked = token_market(&mut env, MarketParams { title: "locked".to_string(), ..Default::default() });
+    env.lock_market(&locked).expect_commit_success();
+    expect_market_error(provide_liquidity(&mut env, &locked, dec!("100")), "MarketLocked");
+    let voided = token_market(&mut env, MarketParams { title: "voided".to_string(), ..Default::default() });
+    env.resolve_market_as_void(&voided).expect_commit_success();
+    expect_market_error(provide_liquidity(&mut env, &voided, dec!("100")), "MarketResolved");
+}
+
+fn buy_shares(env: &mut TestEnv, market: &TestMarket, outcome: &str, amount: Decimal) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(env.account, market.bet_token, amount)
+        .take_from_worktop(market.bet_token, amount, "stake")
+        .call_method_with_name_lookup(market.component, "buy_shares", |lookup| (outcome.to_string(), lookup.bucket("stake")))
+        .deposit_batch(env.account)
+        .build();
+    env.execute(manifest)
+}
+
+// Redeems `amount` shares of the outcome at `outcome_index` held by `account`, signing with `public_key`.
+fn redeem_shares(
+    env: &mut TestEnv,
+    market: &TestMarket,
+    outcome_index: usize,
+    amount: Decimal,
+    account: ComponentAddress,
+    public_key: &Secp256k1PublicKey,
+) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account, market.shares[outcome_index], amount)
+        .take_from_worktop(market.shares[outcome_index], amount, "shares")
+        .call_method_with_name_lookup(market.component, "redeem_shares", |lookup| (lookup.bucket("shares"),))
+        .deposit_batch(account)
+        .build();
+    env.execute_as(manifest, public_key)
+}
+
+#[test]
+fn test_outcome_shares_can_be_transferred_and_redeemed() {
+    let mut env = TestEnv::new();
+    let (public_key, account) = (env.public_key, env.account);
+    let (other_public_key, _, other_account) = env.test_runner.new_allocated_account();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("100")).expect_commit_success();
+
+    // Shares are minted one per token staked and count towards the outcome's stake.
+    buy_shares(&mut env, &market, "outcome1", dec!("10")).expect_commit_success();
+    buy_shares(&mut env, &market, "outcome2", dec!("20")).expect_commit_success();
+    expect_market_error(buy_shares(&mut env, &market, "outcome1", dec!("4")), "BetBelowMinimum");
+    env.place_bet(&market, "user1", "outcome1", dec!("5")).expect_commit_success();
+    assert_eq!(env.balance(market.shares[0]), dec!("10"));
+    assert_eq!(env.market_details(&market).total_staked, dec!("35"));
+    redeem_shares(&mut env, &market, 0, dec!("10"), account, &public_key).expect_commit_failure();
+
+    // The outcome1 shares change hands before the market resolves.
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account, market.shares[0], dec!("10"))
+        .try_deposit_batch_or_abort(other_account, None)
+        .build();
+    env.execute(manifest).expect_commit_success();
+    assert_eq!(env.test_runner.get_component_balance(other_account, market.shares[0]), dec!("10"));
+    env.resolve_market(&market, 0).expect_commit_success();
+
+    // The new holder is paid the locked-in odds of 2, whatever they redeem at a time.
+    for (shares, payout) in [(dec!("4"), dec!("8")), (dec!("6"), dec!("12"))] {
+        let before = env.test_runner.get_component_balance(other_account, market.bet_token);
+        redeem_shares(&mut env, &market, 0, shares, other_account, &other_public_key).expect_commit_success();
+        assert_eq!(env.test_runner.get_component_balance(other_account, market.bet_token) - before, payout);
+    }
+    assert_eq!(env.test_runner.get_component_balance(other_account, market.shares[0]), Decimal::zero());
+
+    // The losing shares are burned for nothing, while the bettor still claims as usual.
+    let before = env.balance(market.bet_token);
+    redeem_shares(&mut env, &market, 1, dec!("20"), account, &public_key).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token), before);
+    assert_eq!(env.balance(market.shares[1]), Decimal::zero());
+    env.claim_reward(&market, market.claim_badge).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("10"));
+}
+
+#[test]
+fn test_outcome_shares_are_refunded_when_voided() {
+    let mut env = TestEnv::new();
+    let (public_key, account) = (env.public_key, env.account);
+    let market = token_market(&mut env, MarketParams::default());
+    buy_shares(&mut env, &market, "outcome2", dec!("15")).expect_commit_success();
+    env.resolve_market_as_void(&market).expect_commit_success();
+
+    let before = env.balance(market.bet_token);
+    redeem_shares(&mut env, &market, 1, dec!("15"), account, &public_key).expect_commit_success();
+    assert_eq!(env.balance(market.bet_token) - before, dec!("15"));
+}
+
+#[test]
+fn test_add_outcome_before_first_bet() {
+    let mut env = TestEnv::new();
+    let market = token_market(&mut env, MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    let add_outcome = |env: &mut TestEnv, outcome: &str, odds: Decimal| {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(env.account, market.admin_badge, dec!("1"))
+            .call_method(market.component, "add_outcome", manifest_args!(outcome.to_string(), odds))
+            .build();
+        env.execute(manifest)
+    };
+
+    // A bet that was cancelled leaves nothing staked, so the outcome can still be added.
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.cancel_bet(&market, 1, "outcome1").expect_commit_success();
+    add_outcome(&mut env, " OUTCOME2 ", dec!("4")).expect_commit_failure();
+    add_outcome(&mut env, "outcome3", dec!("1")).expect_commit_failure();
+    let receipt = add_outcome(&mut env, "outcome3", dec!("4"));
+    assert_eq!(env.events_named(&receipt, "OutcomeAddedEvent").len(), 1);
+    let details = env.market_details(&market);
+    assert_eq!(details.outcomes, vec!["outcome1", "outcome2", "outcome3"]);
+    assert_eq!(details.odds, vec![dec!("2"), dec!("3"), dec!("4")]);
+
+    // The new outcome takes bets, including from users who bet before it existed, and can win.
+    env.place_bet(&market, "user1", "outcome3", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("10")).expect_commit_success();
+    expect_market_error(add_outcome(&mut env, "outcome4", dec!("5")), "BetsAlreadyPlaced");
+    assert_eq!(env.resolve_market_rewards(&market, 2), vec![("user1".to_string(), dec!("40"))]);
+}
+
+#[test]
+fn test_pool_implied_odds_follow_lopsided_pools() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams::default());
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    let get_pool_implied_odds = |env: &mut TestEnv| -> Vec<Decimal> {
+        let manifest = ManifestBuilder::new()
+            .call_method(market.component, "get_pool_implied_odds", manifest_args!())
+            .build();
+        env.execute(manifest).expect_commit_success().output(1)
+    };
+
+    // Empty pools fall back to the configured odds.
+    assert_eq!(get_pool_implied_odds(&mut env), vec![dec!("2"), dec!("3")]);
+
+    env.place_bet(&market, "user1", "outcome1", dec!("90")).expect_commit_success();
+    assert_eq!(get_pool_implied_odds(&mut env), vec![dec!("1"), dec!("3")]);
+
+    // The heavily backed outcome's odds shorten and the thin one's lengthen.
+    env.place_bet(&market, "user2", "outcome2", dec!("10")).expect_commit_success();
+    assert_eq!(get_pool_implied_odds(&mut env), vec![dec!("100") / dec!("90"), dec!("10")]);
+
+    // More money on outcome2 shortens its odds and lengthens outcome1's.
+    env.place_bet(&market, "user3", "outcome2", dec!("40")).expect_commit_success();
+    let implied_odds = get_pool_implied_odds(&mut env);
+    assert_eq!(implied_odds, vec![dec!("140") / dec!("90"), dec!("2.8")]);
+
+    // The details carry both the configured line and the pool-implied odds.
+    let details = env.market_details(&market);
+    assert_eq!(details.odds, vec![dec!("2"), dec!("3")]);
+    assert_eq!(details.implied_odds, implied_odds);
+}
+
+// Mirror of the blueprint's `ResolutionReport`, used to decode `resolve_market_with_report`.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+struct ResolutionReport {
+    winning_outcome: u32,
+    total_pool: Decimal,
+    total_paid: Decimal,
+    house_fee: Decimal,
+    house_net: Decimal,
+    winning_bettors: u64,
+    haircut_ratio: Option<Decimal>,
+    rewards: Vec<(String, Decimal)>,
+}
+
+#[test]
+fn test_resolve_market_with_report_is_consistent() {
+    let mut env = TestEnv::new();
+    let market = env.instantiate(&MarketParams { fee_percent: dec!("10"), ..Default::default() });
+    env.deposit_to_vault(&market, dec!("1000")).expect_commit_success();
+    env.place_bet(&market, "user1", "outcome1", dec!("10")).expect_commit_success();
+    env.place_bet(&market, "user2", "outcome1", dec!("20")).expect_commit_success();
+    env.place_bet(&market, "user3", "outcome2", dec!("40")).expect_commit_success();
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(env.account, market.oracle_badge, dec!("1"))
+        .call_method(market.component, "resolve_market_with_report", manifest_args!(0u32))
+        .build();
+    let result: Result<ResolutionReport, MarketError> = env.execute(manifest).expect_commit_success().output(2);
+    let report = result.unwrap();
+
+    // The winners are owed 20 and 40 at odds of 2, less the 10% fee.
+    assert_eq!(report.rewards, vec![("user1".to_string(), dec!("18")), ("user2".to_string(), dec!("36"))]);
+    assert_eq!(report.winning_outcome, 0);
+    assert_eq!(report.total_pool, dec!("70"));
+    assert_eq!(report.house_fee, dec!("6"));
+    assert_eq!(report.haircut_ratio, None);
+
+    // The figures agree with each other.
+    let summed_rewards = report.rewards.iter().fold(Decimal::zero(), |total, (_, reward)| total + *reward);
+    assert_eq!(report.total_paid, summed_rewards);
+    assert_eq!(report.winning_bettors, report.rewards.len() as u64);
+    assert_eq!(report.house_net, report.total_pool - report.total_paid);
+    assert_eq!(report.house_net, dec!("16"));
+}
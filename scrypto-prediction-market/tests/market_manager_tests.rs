@@ -0,0 +1,512 @@
+use scrypto::prelude::*;
+use scrypto_test::prelude::*;
+use scrypto_unit::*;
+
+// Mirror of the blueprint's `PayoutMode`, encodable in manifests.
+#[derive(ManifestSbor, Clone, Copy)]
+enum PayoutMode {
+    FixedOdds,
+}
+
+// Mirror of the blueprint's `MarketConfig`, encodable in manifests.
+#[derive(ManifestSbor)]
+struct MarketConfig {
+    bet_token: ResourceAddress,
+    betting_deadline: Option<Epoch>,
+    payout_mode: PayoutMode,
+    fee_percent: Decimal,
+    resolution_deadline: Option<Epoch>,
+    max_outcome_exposure: Option<Decimal>,
+    dispute_window_epochs: u64,
+    claim_deadline_epochs: Option<u64>,
+    odds_decay_per_epoch: Decimal,
+    description: String,
+    resolution_source: String,
+    case_insensitive_outcomes: bool,
+    max_total_staked: Option<Decimal>,
+}
+
+// Mirror of the blueprint's `MarketStatus`, encodable in manifests and decodable from summaries.
+#[derive(ManifestSbor, ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+enum MarketStatus {
+    Open,
+    Locked,
+    Resolved { winning_outcome: u32 },
+}
+
+// Mirror of the manager's `MarketSummary`, used to decode `list_markets_with_details`.
+#[derive(ScryptoSbor, Debug, PartialEq)]
+struct MarketSummary {
+    title: String,
+    status: MarketStatus,
+    market_locked: bool,
+    market_resolved: bool,
+    total_staked: Decimal,
+    outcome_count: u32,
+}
+
+// Mirror of the blueprint's `MarketError`, reduced to its leading variants so discriminators still match.
+#[derive(ScryptoSbor, Debug)]
+#[allow(dead_code)]
+enum MarketError {
+    MarketLocked,
+    MarketResolved,
+}
+
+// Creation fee charged by the managers created with `create_manager`.
+fn creation_fee() -> Decimal {
+    dec!("10")
+}
+
+// Creates a manager and deposits its admin badge into `account`. Returns the manager and its admin badge.
+fn create_manager(test_runner: &mut DefaultTestRunner, public_key: &Secp256k1PublicKey, account: ComponentAddress,
+    package_address: PackageAddress) -> (ComponentAddress, ResourceAddress) {
+    let manifest = ManifestBuilder::new()
+        .call_function(package_address, "MarketManager", "new", manifest_args!(creation_fee()))
+        .deposit_batch(account)
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(public_key)]);
+    let commit = receipt.expect_commit_success();
+    (commit.new_component_addresses()[0], commit.new_resource_addresses()[0])
+}
+
+// Creates a two-outcome fixed-odds XRD market through the manager, paying `payment` XRD towards the creation fee.
+fn create_market_receipt(test_runner: &mut DefaultTestRunner, public_key: &Secp256k1PublicKey, account: ComponentAddress,
+    manager: ComponentAddress, title: &str, payment: Decimal) -> TransactionReceipt {
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account, XRD, payment)
+        .take_from_worktop(XRD, payment, "payment")
+        .call_method_with_name_lookup(manager, "instantiate_prediction_market", |lookup| {
+            (
+                title.to_string(),
+                "outcome1,outcome2".to_string(),
+                "2,3".to_string(),
+                dec!("5"),
+                dec!("100"),
+                MarketConfig {
+                    bet_token: XRD,
+                    betting_deadline: None,
+                    payout_mode: PayoutMode::FixedOdds,
+                    fee_percent: dec!("0"),
+                    resolution_deadline: None,
+                    max_outcome_exposure: None,
+                    dispute_window_epochs: 0,
+                    claim_deadline_epochs: None,
+                    odds_decay_per_epoch: dec!("0"),
+                    description: String::new(),
+                    resolution_source: String::new(),
+                    case_insensitive_outcomes: false,
+                    max_total_staked: None,
+                },
+                lookup.bucket("payment"),
+            )
+        })
+        .deposit_batch(account)
+        .build();
+    test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(public_key)])
+}
+
+// Creates a market through the manager for exactly the creation fee. Returns the market's super badge.
+fn create_market(test_runner: &mut DefaultTestRunner, public_key: &Secp256k1PublicKey, account: ComponentAddress,
+    manager: ComponentAddress, title: &str) -> ResourceAddress {
+    let receipt = create_market_receipt(test_runner, public_key, account, manager, title, creation_fee());
+    receipt.expect_commit_success().new_resource_addresses()[0]
+}
+
+fn get_market_address(test_runner: &mut DefaultTestRunner, public_key: &Secp256k1PublicKey, manager: ComponentAddress,
+    title: &str) -> ComponentAddress {
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "get_market_address", manifest_args!(title.to_string()))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(public_key)]);
+    receipt.expect_commit_success().output(1)
+}
+
+#[test]
+fn test_manager_creates_full_prediction_market() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, _manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    let super_badge = create_market(&mut test_runner, &public_key, account, manager, "title");
+
+    // The super badge is returned to the caller, the admin badge stays with the manager.
+    assert_eq!(test_runner.get_component_balance(account, super_badge), dec!("1"));
+    assert_eq!(test_runner.get_component_balance(manager, super_badge), dec!("0"));
+
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "list_all_markets", manifest_args!())
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    let markets: Vec<String> = receipt.expect_commit_success().output(1);
+    assert_eq!(markets, vec!["title".to_string()]);
+
+    // The returned super badge also satisfies the market's admin role.
+    let market = get_market_address(&mut test_runner, &public_key, manager, "title");
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, super_badge, dec!("1"))
+        .call_method(market, "lock_market", manifest_args!())
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_success();
+}
+
+#[test]
+fn test_anyone_can_create_a_market_by_paying_the_fee() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let (creator_key, _, creator) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+
+    // An account without the manager badge pays the fee and gets the market's super badge.
+    let balance_before = test_runner.get_component_balance(creator, XRD);
+    let super_badge = create_market(&mut test_runner, &creator_key, creator, manager, "title");
+    assert_eq!(test_runner.get_component_balance(creator, XRD), balance_before - creation_fee());
+    assert_eq!(test_runner.get_component_balance(creator, super_badge), dec!("1"));
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "get_protocol_fees_balance", manifest_args!())
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    let fees: Decimal = receipt.expect_commit_success().output(1);
+    assert_eq!(fees, creation_fee());
+
+    // The manager's admin still operates the market through the proxies.
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "lock_market", manifest_args!("title".to_string()))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&creator_key)]);
+    receipt.expect_commit_failure();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "lock_market", manifest_args!("title".to_string()))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_success();
+}
+
+#[test]
+fn test_manager_charges_creation_fee() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    let get_protocol_fees_balance = |test_runner: &mut DefaultTestRunner| -> Decimal {
+        let manifest = ManifestBuilder::new()
+            .call_method(manager, "get_protocol_fees_balance", manifest_args!())
+            .build();
+        let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+        receipt.expect_commit_success().output(1)
+    };
+
+    // An underpayment is rejected.
+    let receipt = create_market_receipt(&mut test_runner, &public_key, account, manager, "market1", dec!("9"));
+    receipt.expect_commit_failure();
+
+    // An exact payment is kept in full.
+    let balance_before = test_runner.get_component_balance(account, XRD);
+    let receipt = create_market_receipt(&mut test_runner, &public_key, account, manager, "market1", dec!("10"));
+    receipt.expect_commit_success();
+    assert_eq!(test_runner.get_component_balance(account, XRD), balance_before - dec!("10"));
+
+    // An overpayment returns the change.
+    let balance_before = test_runner.get_component_balance(account, XRD);
+    let receipt = create_market_receipt(&mut test_runner, &public_key, account, manager, "market2", dec!("25"));
+    receipt.expect_commit_success();
+    assert_eq!(test_runner.get_component_balance(account, XRD), balance_before - dec!("10"));
+    assert_eq!(get_protocol_fees_balance(&mut test_runner), dec!("20"));
+
+    // Only the manager admin can collect the fees.
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "collect_fees", manifest_args!())
+        .deposit_batch(account)
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_failure();
+
+    let balance_before = test_runner.get_component_balance(account, XRD);
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "collect_fees", manifest_args!())
+        .deposit_batch(account)
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_success();
+    assert_eq!(test_runner.get_component_balance(account, XRD), balance_before + dec!("20"));
+    assert_eq!(get_protocol_fees_balance(&mut test_runner), Decimal::zero());
+}
+
+#[test]
+fn test_manager_resolves_market_end_to_end() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    create_market(&mut test_runner, &public_key, account, manager, "title");
+    let market = get_market_address(&mut test_runner, &public_key, manager, "title");
+
+    // Fund the treasury and place a winning bet directly on the child market.
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account, XRD, dec!("100"))
+        .take_from_worktop(XRD, dec!("100"), "deposit")
+        .call_method_with_name_lookup(market, "deposit_to_xrd_vault", |lookup| (lookup.bucket("deposit"),))
+        .withdraw_from_account(account, XRD, dec!("10"))
+        .take_from_worktop(XRD, dec!("10"), "bet")
+        .call_method_with_name_lookup(market, "place_bet", |lookup| {
+            ("user1".to_string(), "outcome1".to_string(), lookup.bucket("bet"))
+        })
+        .deposit_batch(account)
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_success();
+
+    // Resolve through the manager, which presents the market's admin badge it holds.
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "resolve_market", manifest_args!("title".to_string(), 0u32))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    let rewards: Result<Vec<(String, Decimal)>, MarketError> = receipt.expect_commit_success().output(2);
+    assert!(rewards.is_ok());
+
+    let manifest = ManifestBuilder::new()
+        .call_method(market, "get_user_vault_balance", manifest_args!("user1".to_string()))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    let balance: Decimal = receipt.expect_commit_success().output(1);
+    assert_eq!(balance, dec!("20"));
+
+    // Resolving an unknown market through the manager fails.
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "resolve_market", manifest_args!("unknown".to_string(), 0u32))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_failure();
+}
+
+#[test]
+fn test_manager_proxies_resolution_of_several_markets() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    create_market(&mut test_runner, &public_key, account, manager, "market1");
+    create_market(&mut test_runner, &public_key, account, manager, "market2");
+    let market1 = get_market_address(&mut test_runner, &public_key, manager, "market1");
+    let market2 = get_market_address(&mut test_runner, &public_key, manager, "market2");
+    let mut execute = |manifest: TransactionManifestV1| {
+        test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)])
+    };
+
+    for market in [market1, market2] {
+        let manifest = ManifestBuilder::new()
+            .withdraw_from_account(account, XRD, dec!("10"))
+            .take_from_worktop(XRD, dec!("10"), "bet")
+            .call_method_with_name_lookup(market, "place_bet", |lookup| {
+                ("user1".to_string(), "outcome1".to_string(), lookup.bucket("bet"))
+            })
+            .deposit_batch(account)
+            .build();
+        execute(manifest).expect_commit_success();
+
+        // The markets reject direct calls from anyone without their badges.
+        for method in ["lock_market", "resolve_market_as_void"] {
+            execute(ManifestBuilder::new().call_method(market, method, manifest_args!()).build()).expect_commit_failure();
+        }
+        let manifest = ManifestBuilder::new().call_method(market, "resolve_market", manifest_args!(0u32)).build();
+        execute(manifest).expect_commit_failure();
+    }
+
+    // The proxies themselves require the manager admin role.
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "resolve_market_as_void", manifest_args!("market2".to_string()))
+        .build();
+    execute(manifest).expect_commit_failure();
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "resolve_market", manifest_args!("market1".to_string(), 1u32))
+        .call_method(manager, "resolve_market_as_void", manifest_args!("market2".to_string()))
+        .build();
+    let receipt = execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let rewards: Result<Vec<(String, Decimal)>, MarketError> = commit.output(2);
+    let voided: Result<(), MarketError> = commit.output(3);
+    assert_eq!(rewards.unwrap(), vec![]);
+    assert!(voided.is_ok());
+
+    // Both markets are settled: market1 resolved to outcome2 and market2 refunded user1.
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "list_markets_by_status", manifest_args!(MarketStatus::Resolved { winning_outcome: 1 }))
+        .call_method(market2, "get_user_vault_balance", manifest_args!("user1".to_string()))
+        .build();
+    let receipt = execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let resolved: Vec<String> = commit.output(1);
+    let refund: Decimal = commit.output(2);
+    assert_eq!(resolved, vec!["market1".to_string()]);
+    assert_eq!(refund, dec!("10"));
+}
+
+#[test]
+fn test_manager_locks_market() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    create_market(&mut test_runner, &public_key, account, manager, "title");
+    let market = get_market_address(&mut test_runner, &public_key, manager, "title");
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "lock_market", manifest_args!("title".to_string()))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_success();
+
+    // Bets on the locked market are rejected.
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account, XRD, dec!("10"))
+        .take_from_worktop(XRD, dec!("10"), "bet")
+        .call_method_with_name_lookup(market, "place_bet", |lookup| {
+            ("user1".to_string(), "outcome1".to_string(), lookup.bucket("bet"))
+        })
+        .deposit_batch(account)
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_failure();
+}
+
+#[test]
+fn test_manager_removes_only_settled_markets() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    for title in ["market1", "market2", "market3"] {
+        create_market(&mut test_runner, &public_key, account, manager, title);
+    }
+
+    let remove_market = |test_runner: &mut DefaultTestRunner, title: &str| -> bool {
+        let manifest = ManifestBuilder::new()
+            .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+            .call_method(manager, "remove_market", manifest_args!(title.to_string()))
+            .build();
+        let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+        receipt.expect_commit_success().output(2)
+    };
+
+    // An open market is not removed.
+    assert!(!remove_market(&mut test_runner, "market2"));
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "resolve_market", manifest_args!("market2".to_string(), 0u32))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_success();
+
+    assert!(remove_market(&mut test_runner, "market2"));
+    assert!(!remove_market(&mut test_runner, "market2"));
+
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "list_all_markets", manifest_args!())
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    let mut markets: Vec<String> = receipt.expect_commit_success().output(1);
+    markets.sort();
+    assert_eq!(markets, vec!["market1".to_string(), "market3".to_string()]);
+}
+
+#[test]
+fn test_manager_lists_markets_by_status() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    for title in ["market1", "market2", "market3"] {
+        create_market(&mut test_runner, &public_key, account, manager, title);
+    }
+
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "resolve_market", manifest_args!("market2".to_string(), 0u32))
+        .build();
+    let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+    receipt.expect_commit_success();
+
+    let mut list_markets_by_status = |status: MarketStatus| -> Vec<String> {
+        let manifest = ManifestBuilder::new()
+            .call_method(manager, "list_markets_by_status", manifest_args!(status))
+            .build();
+        let receipt = test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)]);
+        let mut markets: Vec<String> = receipt.expect_commit_success().output(1);
+        markets.sort();
+        markets
+    };
+    assert_eq!(list_markets_by_status(MarketStatus::Open), vec!["market1".to_string(), "market3".to_string()]);
+    // Only the variant is compared, not the winning outcome.
+    assert_eq!(list_markets_by_status(MarketStatus::Resolved { winning_outcome: 1 }), vec!["market2".to_string()]);
+}
+
+#[test]
+fn test_manager_lists_markets_with_details() {
+    let mut test_runner = TestRunnerBuilder::new().build();
+    let (public_key, _private_key, account) = test_runner.new_allocated_account();
+    let package_address = test_runner.compile_and_publish(this_package!());
+    let (manager, manager_badge) = create_manager(&mut test_runner, &public_key, account, package_address);
+    for title in ["market1", "market2", "market3"] {
+        create_market(&mut test_runner, &public_key, account, manager, title);
+    }
+    let market1 = get_market_address(&mut test_runner, &public_key, manager, "market1");
+    let mut execute = |manifest: TransactionManifestV1| {
+        test_runner.execute_manifest_ignoring_fee(manifest, vec![NonFungibleGlobalId::from_public_key(&public_key)])
+    };
+
+    // market1 takes a bet, market2 is locked and market3 is resolved.
+    let manifest = ManifestBuilder::new()
+        .withdraw_from_account(account, XRD, dec!("10"))
+        .take_from_worktop(XRD, dec!("10"), "bet")
+        .call_method_with_name_lookup(market1, "place_bet", |lookup| {
+            ("user1".to_string(), "outcome1".to_string(), lookup.bucket("bet"))
+        })
+        .deposit_batch(account)
+        .build();
+    execute(manifest).expect_commit_success();
+    let manifest = ManifestBuilder::new()
+        .create_proof_from_account_of_amount(account, manager_badge, dec!("1"))
+        .call_method(manager, "lock_market", manifest_args!("market2".to_string()))
+        .call_method(manager, "resolve_market", manifest_args!("market3".to_string(), 1u32))
+        .build();
+    execute(manifest).expect_commit_success();
+
+    let summary = |title: &str, status: MarketStatus, total_staked: Decimal| MarketSummary {
+        title: title.to_string(),
+        status,
+        market_locked: status == MarketStatus::Locked,
+        market_resolved: matches!(status, MarketStatus::Resolved { .. }),
+        total_staked,
+        outcome_count: 2,
+    };
+    let manifest = ManifestBuilder::new()
+        .call_method(manager, "list_markets_with_details", manifest_args!())
+        .call_method(manager, "list_markets_page", manifest_args!(1u32, 1u32))
+        .call_method(manager, "list_markets_page", manifest_args!(5u32, 2u32))
+        .build();
+    let receipt = execute(manifest);
+    let commit = receipt.expect_commit_success();
+    let markets: Vec<(String, ComponentAddress, MarketSummary)> = commit.output(1);
+    let page: Vec<(String, ComponentAddress, MarketSummary)> = commit.output(2);
+    let past_the_end: Vec<(String, ComponentAddress, MarketSummary)> = commit.output(3);
+
+    let ids: Vec<&String> = markets.iter().map(|(market_id, _, _)| market_id).collect();
+    assert_eq!(ids, vec!["market1", "market2", "market3"]);
+    assert_eq!(markets[0].1, market1);
+    assert_eq!(markets[0].2, summary("market1", MarketStatus::Open, dec!("10")));
+    assert_eq!(markets[1].2, summary("market2", MarketStatus::Locked, Decimal::zero()));
+    assert_eq!(markets[2].2, summary("market3", MarketStatus::Resolved { winning_outcome: 1 }, Decimal::zero()));
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].0, "market2");
+    assert!(past_the_end.is_empty());
+}